@@ -0,0 +1,130 @@
+//! # Project Encryption
+//!
+//! Password-based encryption for project files, so investigations can be
+//! stored on disk without exposing their contents.
+//!
+//! ## File Layout
+//!
+//! | Offset | Length | Content                                  |
+//! |--------|--------|------------------------------------------|
+//! | 0      | 4      | Magic bytes `OSEP`                       |
+//! | 4      | 1      | Format version (currently 1)             |
+//! | 5      | 16     | Argon2id salt                            |
+//! | 21     | 12     | AES-GCM nonce                            |
+//! | 33     | rest   | AES-256-GCM ciphertext and tag           |
+//!
+//! The key is derived from the password with Argon2id and the per-file salt.
+//! AES-GCM authenticates the ciphertext, so a wrong password or a tampered
+//! file is rejected instead of producing garbage.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+
+/// Magic bytes identifying an encrypted project file
+const MAGIC: &[u8; 4] = b"OSEP";
+
+/// Current encrypted file format version
+const FORMAT_VERSION: u8 = 1;
+
+/// Length of the Argon2 salt in bytes
+const SALT_LEN: usize = 16;
+
+/// Length of the AES-GCM nonce in bytes
+const NONCE_LEN: usize = 12;
+
+/// Length of the header preceding the ciphertext
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// Derives a 256-bit key from a password and salt with Argon2id
+fn derive_key(password: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>, String> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypts data with a password
+///
+/// # Arguments
+/// * `plaintext` - Data to encrypt
+/// * `password` - Password to derive the key from
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Header followed by the ciphertext
+/// * `Err(String)` - Error message if key derivation or encryption fails
+pub fn encrypt(plaintext: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(password, &salt)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = Aes256Gcm::new(&key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut output = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    output.extend_from_slice(MAGIC);
+    output.push(FORMAT_VERSION);
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// Decrypts data produced by [`encrypt`]
+///
+/// # Arguments
+/// * `data` - Encrypted file contents
+/// * `password` - Password the data was encrypted with
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The original plaintext
+/// * `Err(String)` - Error message if the file is not an encrypted project,
+///   the password is wrong, or the data was modified
+pub fn decrypt(data: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        return Err("Not an encrypted project file".to_string());
+    }
+    let version = data[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(format!("Unsupported encrypted project version {}", version));
+    }
+
+    let salt_start = MAGIC.len() + 1;
+    let nonce_start = salt_start + SALT_LEN;
+    let salt = &data[salt_start..nonce_start];
+    let nonce = Nonce::from_slice(&data[nonce_start..HEADER_LEN]);
+    let key = derive_key(password, salt)?;
+
+    Aes256Gcm::new(&key)
+        .decrypt(nonce, &data[HEADER_LEN..])
+        .map_err(|_| "Incorrect password or corrupted file".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_requires_the_right_password() {
+        let plaintext = br#"{"nodes":[],"relationships":[]}"#;
+        let encrypted = encrypt(plaintext, "correct horse").unwrap();
+
+        assert_eq!(&encrypted[..MAGIC.len()], MAGIC);
+        assert!(!encrypted.windows(plaintext.len()).any(|window| window == plaintext));
+        assert_eq!(decrypt(&encrypted, "correct horse").unwrap(), plaintext);
+        assert_eq!(decrypt(&encrypted, "wrong").unwrap_err(), "Incorrect password or corrupted file");
+    }
+
+    #[test]
+    fn tampered_or_foreign_data_is_rejected() {
+        let mut encrypted = encrypt(b"secret", "pw").unwrap();
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+        assert!(decrypt(&encrypted, "pw").is_err());
+        assert_eq!(decrypt(b"{}", "pw").unwrap_err(), "Not an encrypted project file");
+    }
+}
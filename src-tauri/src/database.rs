@@ -9,6 +9,7 @@
 //! The database uses:
 //! - `HashMap<Uuid, Node>` for fast node lookups by ID
 //! - `Vec<Relationship>` for relationship storage (allows duplicates)
+//! - `Vec<AuditEntry>` as an append-only log of every create/update/delete
 //! - Thread-safe access through Arc<Mutex<>> for multi-threaded operations
 //!
 //! ## Performance
@@ -21,13 +22,815 @@
 //!
 //! All operations are thread-safe. The database can be shared across
 //! multiple threads and accessed concurrently without data races.
+//!
+//! A panic while a lock is held does not take the database down with it:
+//! poisoned locks are recovered so the rest of the session keeps working.
 
-use crate::entities::{Node, Relationship};
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use crate::entities::{
+    merge_metadata, normalize_identifier, normalize_tags, source_references, Node, NodeType, Relationship, RelationType,
+    DEFAULT_NODE_CONFIDENCE, DEFAULT_RELATIONSHIP_CONFIDENCE, DEFAULT_RELATIONSHIP_WEIGHT,
+    MAX_RELATIONSHIP_WEIGHT,
+};
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use uuid::Uuid;
 use anyhow::Result;
 
+/// Connection between two nodes after consolidating parallel relationships
+///
+/// Graph algorithms work on these rather than raw relationships so that
+/// several relationships between the same pair are not double counted.
+#[derive(Debug, Clone, Copy)]
+struct CollapsedEdge {
+    /// Sum of the weights of all consolidated relationships
+    weight: f64,
+    /// Highest confidence among the consolidated relationships
+    confidence: f32,
+    /// Number of relationships consolidated into this edge
+    count: usize,
+    /// Most recent `updated_at` among the consolidated relationships
+    updated_at: DateTime<Utc>,
+}
+
+/// Restrictions applied when searching for paths between nodes
+#[derive(Debug, Clone, Default)]
+pub struct PathConstraints {
+    /// Node types that may not appear inside a path (endpoints are exempt)
+    pub avoid_node_types: Vec<NodeType>,
+    /// Relationship types a path may traverse; empty allows every type
+    pub allowed_relation_types: Vec<RelationType>,
+    /// Only follow relationships from source to target (symmetric types go both ways)
+    pub directed: bool,
+}
+
+/// Degree centrality scores for a single node
+///
+/// In directed mode the in/out split is reported separately, which matters for
+/// ownership and control hierarchies where edge direction carries meaning.
+/// In undirected mode only the total degree is populated. Degrees count
+/// distinct neighbors; parallel relationships add to `strength` instead.
+#[derive(Debug, Clone, Serialize)]
+pub struct DegreeCentrality {
+    /// Node the scores belong to
+    pub node_id: Uuid,
+    /// Number of relationships pointing at this node (directed mode only)
+    pub in_degree: Option<usize>,
+    /// Number of relationships originating from this node (directed mode only)
+    pub out_degree: Option<usize>,
+    /// Total number of distinct neighbors
+    pub degree: usize,
+    /// Sum of the weights of all relationships touching this node
+    pub strength: f64,
+    /// Degree normalized by the maximum possible degree (n - 1)
+    pub centrality: f64,
+    /// Sum of recency factors of the node's edges (recency weighting only)
+    ///
+    /// Each edge contributes `0.5^(age / half-life)`, where age is measured
+    /// from its most recently updated relationship, so an edge updated today
+    /// counts as 1 and one a half-life old counts as 0.5.
+    pub recency_weighted: Option<f64>,
+}
+
+/// Shortest-path statistics of the largest connected component
+#[derive(Debug, Clone, Serialize)]
+pub struct PathLengthStats {
+    /// Number of nodes in the component the statistics describe
+    pub component_size: usize,
+    /// Longest shortest path, in hops
+    pub diameter: usize,
+    /// Mean shortest path length over all pairs of distinct nodes
+    pub average_path_length: f64,
+    /// Two nodes whose shortest path is the diameter; `None` for a single node or empty graph
+    pub diameter_endpoints: Option<(Uuid, Uuid)>,
+}
+
+/// Result of a bulk node removal such as orphan cleanup, leaf trimming, or expiry purge
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkRemovalReport {
+    /// IDs of the nodes that were removed, or would be in a dry run
+    pub deleted: Vec<Uuid>,
+    /// Number of nodes that qualified for removal but were kept because they are pinned
+    pub skipped_pinned: usize,
+}
+
+/// Result of setting the confidence of many nodes at once
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkConfidenceUpdate {
+    /// Number of nodes whose confidence was set
+    pub updated: usize,
+    /// Requested IDs that matched no node
+    pub missing: Vec<Uuid>,
+}
+
+/// Result of compacting the database
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionReport {
+    /// Relationships dropped because an endpoint node no longer exists
+    pub dangling_relationships_removed: usize,
+    /// Unused relationship slots released by shrinking storage
+    pub relationship_capacity_freed: usize,
+    /// Unused node slots released by shrinking storage
+    pub node_capacity_freed: usize,
+}
+
+/// Group of nodes sharing the same normalized identifier
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentifierDuplicateGroup {
+    /// Normalized identifier shared by the group
+    pub normalized: String,
+    /// IDs of the nodes in the group
+    pub node_ids: Vec<Uuid>,
+}
+
+/// Node whose source is not corroborated by any of its relationships
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceMismatch {
+    /// ID of the flagged node
+    pub node_id: Uuid,
+    /// Source claimed by the node
+    pub node_source: String,
+    /// Distinct sources of the node's relationships, sorted
+    pub relationship_sources: Vec<String>,
+}
+
+/// Structural similarity of another node to a reference node
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarityScore {
+    /// ID of the similar node
+    pub node_id: Uuid,
+    /// Weighted Jaccard similarity of the two neighborhoods (0.0 to 1.0)
+    pub score: f64,
+}
+
+/// Neighbors shared between two groups of nodes
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeSetComparison {
+    /// Nodes adjacent to at least one member of each set, ordered by label
+    pub common_neighbors: Vec<Node>,
+    /// Number of distinct neighbors of the first set
+    pub neighbors_a: usize,
+    /// Number of distinct neighbors of the second set
+    pub neighbors_b: usize,
+    /// Number of common neighbors
+    pub overlap: usize,
+}
+
+/// Confidence statistics for the connected component containing a node
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterConfidence {
+    /// IDs of the nodes in the component, sorted
+    pub node_ids: Vec<Uuid>,
+    /// Mean confidence of the component's nodes
+    pub mean_node_confidence: f64,
+    /// Lowest node confidence in the component
+    pub min_node_confidence: f32,
+    /// Highest node confidence in the component
+    pub max_node_confidence: f32,
+    /// Number of relationships inside the component
+    pub relationship_count: usize,
+    /// Mean confidence of those relationships, if there are any
+    pub mean_relationship_confidence: Option<f64>,
+}
+
+/// Kind of change recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditOperation {
+    /// Entity was created
+    Create,
+    /// Entity was modified
+    Update,
+    /// Entity was removed
+    Delete,
+}
+
+/// Kind of entity an audit entry refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditEntityType {
+    /// A node
+    Node,
+    /// A relationship
+    Relationship,
+}
+
+/// Single entry in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the change happened
+    pub timestamp: DateTime<Utc>,
+    /// What kind of change it was
+    pub operation: AuditOperation,
+    /// Kind of entity that changed
+    pub entity_type: AuditEntityType,
+    /// ID of the entity that changed
+    pub entity_id: Uuid,
+    /// Names of the fields that changed (updates only)
+    pub changed_fields: Vec<String>,
+    /// Old and new value of each changed field (updates only; absent in older projects)
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+}
+
+/// Value of one field before and after an update
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Name of the field as it appears in the entity's JSON
+    pub field: String,
+    /// Value before the update
+    pub old: serde_json::Value,
+    /// Value after the update
+    pub new: serde_json::Value,
+}
+
+impl FieldChange {
+    /// Records a single field change from any serializable old and new values
+    ///
+    /// # Arguments
+    /// * `field` - Name of the field
+    /// * `old` - Value before the update
+    /// * `new` - Value after the update
+    pub fn new<T: Serialize>(field: &str, old: T, new: T) -> Self {
+        Self {
+            field: field.to_string(),
+            old: serde_json::to_value(old).unwrap_or(serde_json::Value::Null),
+            new: serde_json::to_value(new).unwrap_or(serde_json::Value::Null),
+        }
+    }
+}
+
+/// Everything that changed after a point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct Delta {
+    /// Nodes updated after the cutoff
+    pub nodes: Vec<Node>,
+    /// Relationships updated after the cutoff
+    pub relationships: Vec<Relationship>,
+    /// IDs of nodes deleted after the cutoff
+    pub deleted_nodes: Vec<Uuid>,
+    /// IDs of relationships deleted after the cutoff
+    pub deleted_relationships: Vec<Uuid>,
+}
+
+/// Provenance record for a single relationship
+///
+/// Gathers everything needed to review how a connection was established:
+/// where it came from, how confident we are, and when it was recorded.
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    /// Relationship the record describes
+    pub relationship_id: Uuid,
+    /// Source node of the relationship
+    pub source_id: Uuid,
+    /// Target node of the relationship
+    pub target_id: Uuid,
+    /// Type of the relationship
+    pub relation_type: RelationType,
+    /// Source references attesting the relationship
+    pub sources: Vec<String>,
+    /// Current confidence score
+    pub confidence: f32,
+    /// When the relationship was created
+    pub created_at: DateTime<Utc>,
+    /// When the relationship was last updated
+    pub updated_at: DateTime<Utc>,
+    /// Audit log entries for this relationship, oldest first
+    pub audit_entries: Vec<AuditEntry>,
+}
+
+/// Result of computing the transitive closure of `SameAs` relationships
+#[derive(Debug, Clone, Serialize)]
+pub struct SameAsInference {
+    /// Groups of node IDs that all refer to the same real-world entity
+    pub clusters: Vec<Vec<Uuid>>,
+    /// IDs of `SameAs` relationships created to complete the clusters
+    pub created: Vec<Uuid>,
+}
+
+/// Result of folding duplicate nodes into one
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeMerge {
+    /// Node that absorbed the others
+    pub kept: Uuid,
+    /// Nodes that were folded in and removed
+    pub merged: Vec<Uuid>,
+    /// Relationships re-pointed at the kept node
+    pub relationships_moved: usize,
+    /// Relationships dropped because they only linked merged nodes to each other
+    pub relationships_removed: usize,
+    /// Re-pointed relationships folded into an existing one with the same endpoints and type
+    pub relationships_deduplicated: usize,
+    /// Requested nodes left in place because they are pinned
+    pub skipped_pinned: Vec<Uuid>,
+}
+
+/// Copy of the graph and its audit history, used to undo a multi-step operation
+///
+/// Taken with [`Database::snapshot`] and put back with
+/// [`Database::restore_snapshot`].
+#[derive(Debug, Clone)]
+pub struct GraphSnapshot {
+    nodes: HashMap<Uuid, Node>,
+    relationships: Vec<Relationship>,
+    audit_log: Vec<AuditEntry>,
+}
+
+/// Result of automatically merging `SameAs` clusters
+#[derive(Debug, Clone, Serialize)]
+pub struct SameAsMergeReport {
+    /// Merges performed, one per qualifying cluster
+    pub merges: Vec<NodeMerge>,
+    /// Clusters left alone because a linking edge was below the threshold
+    pub skipped: Vec<Vec<Uuid>>,
+}
+
+/// A node label offered by autocomplete
+#[derive(Debug, Clone, Serialize)]
+pub struct LabelSuggestion {
+    /// ID of the suggested node
+    pub id: Uuid,
+    /// The node's label
+    pub label: String,
+    /// The node's type
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+}
+
+/// Search matches together with their immediate surroundings in the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchContext {
+    /// Nodes matching the query, sorted by label
+    pub matches: Vec<Node>,
+    /// Relationships touching at least one match
+    pub relationships: Vec<Relationship>,
+    /// Nodes at the other end of those relationships that are not matches themselves, sorted by label
+    pub neighbors: Vec<Node>,
+}
+
+/// A node returned by ranked search together with its relevance score
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    /// The matching node
+    pub node: Node,
+    /// Relevance score; higher is better
+    pub score: f64,
+    /// Every place the query matched, for highlighting
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Location of a query match inside a node field
+///
+/// Offsets count characters (Unicode scalar values), not bytes, so they can
+/// be used directly for highlighting non-ASCII text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    /// Field that matched: "label", "description", or "tags.N" for the Nth tag
+    pub field: String,
+    /// Character index where the match starts
+    pub start: usize,
+    /// Character index just past the end of the match
+    pub end: usize,
+}
+
+/// Comparison applied when querying metadata values
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataComparator {
+    /// Values are equal (numbers compare numerically)
+    #[default]
+    Eq,
+    /// Values are not equal
+    Ne,
+    /// Stored value is greater than the query value
+    Gt,
+    /// Stored value is greater than or equal to the query value
+    Gte,
+    /// Stored value is less than the query value
+    Lt,
+    /// Stored value is less than or equal to the query value
+    Lte,
+    /// Stored string contains the query string, or stored array contains the query value
+    Contains,
+}
+
+/// Node field usable as a sort key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeSortField {
+    /// Node type, ordered by type name
+    NodeType,
+    /// Display label
+    Label,
+    /// Confidence score
+    Confidence,
+    /// Creation time
+    CreatedAt,
+    /// Last update time
+    UpdatedAt,
+    /// Number of relationships touching the node
+    Degree,
+}
+
+impl NodeSortField {
+    /// Field names accepted by [`NodeSortField::from_name`]
+    pub const NAMES: [&'static str; 6] = ["node_type", "label", "confidence", "created_at", "updated_at", "degree"];
+
+    /// Parses a field name as used in node JSON
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "node_type" => Some(NodeSortField::NodeType),
+            "label" => Some(NodeSortField::Label),
+            "confidence" => Some(NodeSortField::Confidence),
+            "created_at" => Some(NodeSortField::CreatedAt),
+            "updated_at" => Some(NodeSortField::UpdatedAt),
+            "degree" => Some(NodeSortField::Degree),
+            _ => None,
+        }
+    }
+
+    /// Compares two nodes on this field in ascending order
+    fn compare(self, a: &Node, b: &Node) -> std::cmp::Ordering {
+        match self {
+            NodeSortField::NodeType => format!("{:?}", a.node_type).cmp(&format!("{:?}", b.node_type)),
+            NodeSortField::Label => a.label.to_lowercase().cmp(&b.label.to_lowercase()),
+            NodeSortField::Confidence => a.confidence.total_cmp(&b.confidence),
+            NodeSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            NodeSortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            NodeSortField::Degree => a.degree.cmp(&b.degree),
+        }
+    }
+}
+
+/// How confidences combine when a duplicate relationship is merged into an existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfidenceAggregation {
+    /// Keep the higher of the two confidences
+    #[default]
+    Max,
+    /// Average the existing and incoming confidences
+    Mean,
+    /// Treat both as independent evidence: `1 - (1 - a)(1 - b)`
+    NoisyOr,
+}
+
+impl ConfidenceAggregation {
+    /// Policy names accepted by [`ConfidenceAggregation::from_name`]
+    pub const NAMES: [&'static str; 3] = ["max", "mean", "noisy_or"];
+
+    /// Parses a policy name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "max" => Some(ConfidenceAggregation::Max),
+            "mean" => Some(ConfidenceAggregation::Mean),
+            "noisy_or" => Some(ConfidenceAggregation::NoisyOr),
+            _ => None,
+        }
+    }
+
+    /// Combines an existing confidence with an incoming one
+    pub fn combine(self, existing: f32, incoming: f32) -> f32 {
+        match self {
+            ConfidenceAggregation::Max => existing.max(incoming),
+            ConfidenceAggregation::Mean => (existing + incoming) / 2.0,
+            ConfidenceAggregation::NoisyOr => 1.0 - (1.0 - existing) * (1.0 - incoming),
+        }
+    }
+}
+
+/// How source references combine when a duplicate relationship is merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceMerge {
+    /// Keep every distinct source, joined with `"; "`
+    #[default]
+    Union,
+    /// Use the incoming source, keeping the existing one only if none is given
+    Replace,
+}
+
+impl SourceMerge {
+    /// Policy names accepted by [`SourceMerge::from_name`]
+    pub const NAMES: [&'static str; 2] = ["union", "replace"];
+
+    /// Parses a policy name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "union" => Some(SourceMerge::Union),
+            "replace" => Some(SourceMerge::Replace),
+            _ => None,
+        }
+    }
+
+    /// Combines an existing source reference with an incoming one
+    pub fn combine(self, existing: Option<String>, incoming: Option<String>) -> Option<String> {
+        match (self, existing, incoming) {
+            (SourceMerge::Union, Some(existing), Some(incoming)) => {
+                let mut sources: Vec<&str> = source_references(Some(&existing)).collect();
+                for reference in source_references(Some(&incoming)) {
+                    if !sources.contains(&reference) {
+                        sources.push(reference);
+                    }
+                }
+                Some(sources.join("; "))
+            }
+            (_, existing, incoming) => incoming.or(existing),
+        }
+    }
+}
+
+/// Policy for merging a new relationship into an existing duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MergePolicy {
+    /// How the confidences combine
+    pub confidence: ConfidenceAggregation,
+    /// How the source references combine
+    pub sources: SourceMerge,
+}
+
+/// User-configurable behaviour applied by the command layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    /// Store tags lowercased; when false the casing of the first occurrence is kept
+    pub lowercase_tags: bool,
+    /// Number of relationships a single node may have before new ones are flagged
+    #[serde(default = "default_relationship_limit")]
+    pub relationship_limit: usize,
+    /// Reject relationships over the limit instead of only warning
+    #[serde(default)]
+    pub reject_over_limit: bool,
+    /// User overrides of the built-in description templates, by node type
+    #[serde(default)]
+    pub description_templates: HashMap<NodeType, String>,
+    /// Age in days at which a node's exported `freshness` reaches 0.0
+    #[serde(default = "default_freshness_max_age_days")]
+    pub freshness_max_age_days: u32,
+    /// How duplicate relationships are merged when creation asks for it
+    #[serde(default)]
+    pub merge_policy: MergePolicy,
+    /// Tags added automatically to every new node of a type
+    #[serde(default)]
+    pub auto_tag_rules: HashMap<NodeType, Vec<String>>,
+}
+
+/// Default per-node relationship limit, high enough to only catch runaway imports
+fn default_relationship_limit() -> usize {
+    10_000
+}
+
+/// Default age at which nodes are considered fully stale in exports
+fn default_freshness_max_age_days() -> u32 {
+    365
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            lowercase_tags: true,
+            relationship_limit: default_relationship_limit(),
+            reject_over_limit: false,
+            description_templates: HashMap::new(),
+            freshness_max_age_days: default_freshness_max_age_days(),
+            merge_policy: MergePolicy::default(),
+            auto_tag_rules: HashMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Adds the auto-tag rule tags for a node's type to the node
+    ///
+    /// The combined tags are normalized, so a tag the node already carries
+    /// is not repeated. Nodes whose type has no rule are left untouched.
+    ///
+    /// # Arguments
+    /// * `node` - Node about to be created
+    pub fn apply_auto_tags(&self, node: &mut Node) {
+        if let Some(rule_tags) = self.auto_tag_rules.get(&node.node_type) {
+            let mut tags = std::mem::take(&mut node.tags);
+            tags.extend(rule_tags.iter().cloned());
+            node.tags = normalize_tags(tags, self.lowercase_tags);
+        }
+    }
+
+    /// Returns the description template for a node type
+    ///
+    /// A user override takes precedence over the built-in template.
+    pub fn description_template(&self, node_type: &NodeType) -> String {
+        self.description_templates
+            .get(node_type)
+            .cloned()
+            .unwrap_or_else(|| node_type.description_template().to_string())
+    }
+}
+
+/// Two nodes that are each claimed to be the parent of the other
+#[derive(Debug, Clone, Serialize)]
+pub struct HierarchyConflict {
+    /// One node of the pair (the smaller ID)
+    pub node_a: Uuid,
+    /// The other node of the pair
+    pub node_b: Uuid,
+    /// `ParentOf`/`ChildOf` relationships stating that `node_a` is the parent
+    pub a_parent_of_b: Vec<Uuid>,
+    /// `ParentOf`/`ChildOf` relationships stating that `node_b` is the parent
+    pub b_parent_of_a: Vec<Uuid>,
+}
+
+/// Result of checking parent/child relationships for contradictions
+#[derive(Debug, Clone, Serialize)]
+pub struct HierarchyValidation {
+    /// Contradictory pairs found
+    pub conflicts: Vec<HierarchyConflict>,
+    /// Relationships deleted by the automatic fix
+    pub removed: Vec<Uuid>,
+}
+
+/// How many nodes cite one source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceCount {
+    /// The source reference, as stored on the nodes
+    pub source: String,
+    /// Number of nodes with this source
+    pub count: usize,
+}
+
+/// Number of a node's relationships of one type, split by direction
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct DirectionCounts {
+    /// Relationships pointing at the node
+    pub incoming: usize,
+    /// Relationships starting at the node
+    pub outgoing: usize,
+}
+
+/// When two nodes first and last interacted, based on the relationships between them
+#[derive(Debug, Clone, Serialize)]
+pub struct PairInteractionSummary {
+    /// Number of relationships between the two nodes, in either direction
+    pub count: usize,
+    /// Earliest relationship timestamp, if any relationship exists
+    pub first: Option<DateTime<Utc>>,
+    /// Latest relationship timestamp, if any relationship exists
+    pub last: Option<DateTime<Utc>>,
+}
+
+/// Confidence distribution for a single relationship type
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationConfidenceStats {
+    /// Relationship type the figures describe
+    pub relation_type: RelationType,
+    /// Number of relationships of this type
+    pub count: usize,
+    /// Mean confidence across those relationships
+    pub mean: f32,
+    /// Lowest confidence seen
+    pub min: f32,
+    /// Highest confidence seen
+    pub max: f32,
+}
+
+/// Data-quality summary of the investigation
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReport {
+    /// Confidence below which an entity counts as low confidence
+    pub threshold: f32,
+    /// Total number of nodes
+    pub total_nodes: usize,
+    /// Nodes with confidence below the threshold
+    pub low_confidence_nodes: usize,
+    /// Low-confidence nodes as a percentage of all nodes (0 when there are none)
+    pub low_confidence_node_percent: f64,
+    /// Nodes without any source attribution
+    pub unsourced_nodes: usize,
+    /// Total number of relationships
+    pub total_relationships: usize,
+    /// Relationships with confidence below the threshold
+    pub low_confidence_relationships: usize,
+    /// Low-confidence relationships as a percentage of all relationships (0 when there are none)
+    pub low_confidence_relationship_percent: f64,
+    /// Relationships without any source attribution
+    pub unsourced_relationships: usize,
+}
+
+/// Share of an edge's display weight removed when it ends at a leaf node
+///
+/// An edge whose less-connected endpoint has `n` neighbors keeps
+/// `1 - LEAF_EDGE_DECAY / n` of its weight.
+pub const LEAF_EDGE_DECAY: f64 = 0.5;
+
+/// Most buckets [`Database::get_growth_timeline`] will return
+///
+/// Ten thousand days is over 27 years, so only implausible `created_at`
+/// values (e.g. from a hand-edited project) hit this limit.
+pub const MAX_GROWTH_BUCKETS: usize = 10_000;
+
+/// Label shown for a relationship endpoint whose node no longer exists
+pub const MISSING_NODE_LABEL: &str = "(deleted node)";
+
+/// A relationship together with the labels and types of its endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationshipDetail {
+    /// The stored relationship
+    #[serde(flatten)]
+    pub relationship: Relationship,
+    /// Label of the source node, or [`MISSING_NODE_LABEL`] if it is gone
+    pub source_label: String,
+    /// Type of the source node, if it exists
+    pub source_type: Option<NodeType>,
+    /// Label of the target node, or [`MISSING_NODE_LABEL`] if it is gone
+    pub target_label: String,
+    /// Type of the target node, if it exists
+    pub target_type: Option<NodeType>,
+}
+
+/// Calendar period used to group timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBucket {
+    /// One UTC calendar day
+    Day,
+    /// One ISO week, starting on Monday
+    Week,
+    /// One calendar month
+    Month,
+}
+
+impl TimeBucket {
+    /// Bucket names accepted by [`TimeBucket::from_name`]
+    pub const NAMES: [&'static str; 3] = ["day", "week", "month"];
+
+    /// Parses a bucket name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "day" => Some(TimeBucket::Day),
+            "week" => Some(TimeBucket::Week),
+            "month" => Some(TimeBucket::Month),
+            _ => None,
+        }
+    }
+
+    /// First day of the bucket containing `date`
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            TimeBucket::Day => date,
+            TimeBucket::Week => date
+                .checked_sub_days(Days::new(date.weekday().num_days_from_monday() as u64))
+                .unwrap_or(date),
+            TimeBucket::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// First day of the bucket following the one starting at `start`
+    ///
+    /// Returns `None` past the last representable date.
+    fn next(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            TimeBucket::Day => start.checked_add_days(Days::new(1)),
+            TimeBucket::Week => start.checked_add_days(Days::new(7)),
+            TimeBucket::Month => start.checked_add_months(Months::new(1)),
+        }
+    }
+}
+
+/// Cumulative graph size at the end of one time bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct GrowthPoint {
+    /// First day of the bucket
+    pub bucket_start: NaiveDate,
+    /// Nodes created up to the end of the bucket
+    pub nodes: usize,
+    /// Relationships created up to the end of the bucket
+    pub relationships: usize,
+}
+
+/// One bucket of a value histogram
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBucket {
+    /// Lower bound of the bucket (inclusive)
+    pub min: f64,
+    /// Upper bound of the bucket (exclusive, except for the last bucket)
+    pub max: f64,
+    /// Number of values falling in the bucket
+    pub count: usize,
+}
+
+/// What deleting a node would remove, computed without mutating anything
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeDeletionPreview {
+    /// The node that would be deleted
+    pub node: Node,
+    /// Relationships that would be deleted along with it
+    pub relationships: Vec<Relationship>,
+}
+
+/// Free-form case notes stored with the project rather than any node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectNotes {
+    /// Analyst narrative and working notes
+    pub notes: String,
+    /// Short summary of the case
+    pub case_summary: String,
+}
+
 /// In-memory database for OSINT investigation data
 ///
 /// Provides thread-safe storage and operations for nodes and relationships.
@@ -50,6 +853,14 @@ pub struct Database {
     nodes: Arc<Mutex<HashMap<Uuid, Node>>>,
     /// Thread-safe storage for relationships
     relationships: Arc<Mutex<Vec<Relationship>>>,
+    /// Thread-safe storage for user settings
+    settings: Arc<Mutex<Settings>>,
+    /// Thread-safe, append-only history of changes
+    audit_log: Arc<Mutex<Vec<AuditEntry>>>,
+    /// Thread-safe storage for project-level case notes
+    project_notes: Arc<Mutex<ProjectNotes>>,
+    /// When the current project was first saved, if it has been
+    project_created_at: Arc<Mutex<Option<DateTime<Utc>>>>,
 }
 
 impl Database {
@@ -61,21 +872,106 @@ impl Database {
         Self {
             nodes: Arc::new(Mutex::new(HashMap::new())),
             relationships: Arc::new(Mutex::new(Vec::new())),
+            settings: Arc::new(Mutex::new(Settings::default())),
+            audit_log: Arc::new(Mutex::new(Vec::new())),
+            project_notes: Arc::new(Mutex::new(ProjectNotes::default())),
+            project_created_at: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Returns a copy of the current settings
+    ///
+    /// # Returns
+    /// * `Ok(Settings)` - The current settings
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn settings(&self) -> Result<Settings> {
+        let settings = lock(&self.settings);
+        Ok(settings.clone())
+    }
+
+    /// Applies a change to the current settings
+    ///
+    /// # Arguments
+    /// * `update` - Closure that mutates the settings in place
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the update succeeds
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn update_settings(&self, update: impl FnOnce(&mut Settings)) -> Result<()> {
+        let mut settings = lock(&self.settings);
+        update(&mut settings);
+        Ok(())
+    }
+
+    /// Returns a copy of the project-level case notes
+    ///
+    /// # Returns
+    /// * `Ok(ProjectNotes)` - The current notes
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn project_notes(&self) -> Result<ProjectNotes> {
+        Ok(lock(&self.project_notes).clone())
+    }
+
+    /// Replaces the project-level case notes
+    ///
+    /// # Arguments
+    /// * `notes` - New notes
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the update succeeds
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn set_project_notes(&self, notes: ProjectNotes) -> Result<()> {
+        *lock(&self.project_notes) = notes;
+        Ok(())
+    }
+
+    /// Returns the creation time of the current project
+    ///
+    /// The first call for a project that has never been saved or loaded
+    /// records the current time, so every later save reports the same value.
+    ///
+    /// # Returns
+    /// * `Ok(DateTime<Utc>)` - When the project was created
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn project_created_at(&self) -> Result<DateTime<Utc>> {
+        Ok(*lock(&self.project_created_at).get_or_insert_with(Utc::now))
+    }
+
+    /// Sets the creation time of the current project, e.g. from a loaded file
+    ///
+    /// # Arguments
+    /// * `created_at` - Original creation time
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the update succeeds
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn set_project_created_at(&self, created_at: DateTime<Utc>) -> Result<()> {
+        *lock(&self.project_created_at) = Some(created_at);
+        Ok(())
+    }
+
     /// Creates a new node in the database
     ///
+    /// Every node creator goes through here, so this is where the
+    /// configured auto-tag rules are applied (see [`Settings::apply_auto_tags`]).
+    ///
     /// # Arguments
     /// * `node` - The node to store
     ///
     /// # Returns
     /// * `Ok(Uuid)` - The UUID of the created node
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn create_node(&self, node: Node) -> Result<Uuid> {
+    pub fn create_node(&self, mut node: Node) -> Result<Uuid> {
+        lock(&self.settings).apply_auto_tags(&mut node);
         let node_id = node.id;
-        let mut nodes = self.nodes.lock().unwrap();
+        let mut nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        node.degree = relationships
+            .iter()
+            .map(|rel| (rel.source_id == node_id) as usize + (rel.target_id == node_id) as usize)
+            .sum();
         nodes.insert(node_id, node);
+        self.record_audit(AuditOperation::Create, AuditEntityType::Node, node_id, Vec::new());
         Ok(node_id)
     }
 
@@ -89,7 +985,7 @@ impl Database {
     /// * `Ok(None)` - If no node exists with the given ID
     /// * `Err(anyhow::Error)` - If the operation fails
     pub fn get_node(&self, id: Uuid) -> Result<Option<Node>> {
-        let nodes = self.nodes.lock().unwrap();
+        let nodes = lock(&self.nodes);
         Ok(nodes.get(&id).cloned())
     }
 
@@ -99,10 +995,43 @@ impl Database {
     /// * `Ok(Vec<Node>)` - Vector containing all nodes
     /// * `Err(anyhow::Error)` - If the operation fails
     pub fn get_all_nodes(&self) -> Result<Vec<Node>> {
-        let nodes = self.nodes.lock().unwrap();
+        let nodes = lock(&self.nodes);
         Ok(nodes.values().cloned().collect())
     }
 
+    /// Retrieves a page of nodes ordered by several sort keys
+    ///
+    /// Keys are applied in order, each breaking ties left by the previous
+    /// ones; remaining ties are broken by node ID so pages are stable.
+    ///
+    /// # Arguments
+    /// * `sort_keys` - Fields to sort by, each with a descending flag
+    /// * `offset` - Number of sorted nodes to skip
+    /// * `limit` - Maximum number of nodes to return; all remaining if `None`
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Node>)` - The requested page
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_nodes_sorted(
+        &self,
+        sort_keys: &[(NodeSortField, bool)],
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Result<Vec<Node>> {
+        let mut nodes = self.get_all_nodes()?;
+        nodes.sort_by(|a, b| {
+            sort_keys
+                .iter()
+                .map(|&(field, descending)| {
+                    let ordering = field.compare(a, b);
+                    if descending { ordering.reverse() } else { ordering }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| a.id.cmp(&b.id))
+        });
+        Ok(nodes.into_iter().skip(offset).take(limit.unwrap_or(usize::MAX)).collect())
+    }
+
     /// Searches for nodes matching a query string
     ///
     /// Performs case-insensitive search across node labels, descriptions, and tags
@@ -114,7 +1043,7 @@ impl Database {
     /// * `Ok(Vec<Node>)` - Vector of nodes matching the query
     /// * `Err(anyhow::Error)` - If the operation fails
     pub fn search_nodes(&self, query: &str) -> Result<Vec<Node>> {
-        let nodes = self.nodes.lock().unwrap();
+        let nodes = lock(&self.nodes);
         let query_lower = query.to_lowercase();
         
         let results: Vec<Node> = nodes
@@ -130,142 +1059,3921 @@ impl Database {
         Ok(results)
     }
 
-    /// Updates an existing node in the database
+    /// Suggests nodes whose labels start with or contain a prefix
     ///
-    /// Replaces the existing node with the same UUID
+    /// Matching is case-insensitive. Labels starting with the prefix come
+    /// first, then labels containing it elsewhere; each group is ordered
+    /// alphabetically. Lighter than search because only labels are examined.
     ///
     /// # Arguments
-    /// * `node` - The updated node data
+    /// * `prefix` - Text typed so far
+    /// * `limit` - Maximum number of suggestions
     ///
     /// # Returns
-    /// * `Ok(())` - If the update succeeds
+    /// * `Ok(Vec<LabelSuggestion>)` - Up to `limit` suggestions, best first
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn update_node(&self, node: Node) -> Result<()> {
-        let mut nodes = self.nodes.lock().unwrap();
-        nodes.insert(node.id, node);
-        Ok(())
+    pub fn autocomplete_labels(&self, prefix: &str, limit: usize) -> Result<Vec<LabelSuggestion>> {
+        let nodes = lock(&self.nodes);
+        let prefix = prefix.to_lowercase();
+
+        let mut candidates: Vec<(bool, String, &Node)> = nodes
+            .values()
+            .filter_map(|node| {
+                let label = node.label.to_lowercase();
+                if label.starts_with(&prefix) {
+                    Some((false, label, node))
+                } else if label.contains(&prefix) {
+                    Some((true, label, node))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)).then(a.2.id.cmp(&b.2.id)));
+
+        Ok(candidates
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, node)| LabelSuggestion { id: node.id, label: node.label.clone(), node_type: node.node_type.clone() })
+            .collect())
     }
 
-    /// Deletes a node and all its relationships
+    /// Searches for nodes and returns them with their direct relationships and neighbors
     ///
-    /// Removes the node from storage and cleans up any relationships
-    /// that reference this node to prevent orphaned references
+    /// Matching follows [`Database::search_nodes`]. Every relationship with a
+    /// match at either end is included, and the node on the other end is
+    /// listed once in `neighbors`, so the result can be drawn as a small graph
+    /// without further lookups.
     ///
     /// # Arguments
-    /// * `id` - UUID of the node to delete
+    /// * `query` - Search query string
     ///
     /// # Returns
-    /// * `Ok(true)` - If the node was found and deleted
-    /// * `Ok(false)` - If no node existed with the given ID
+    /// * `Ok(SearchContext)` - Matches, touching relationships, and neighbor nodes
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn delete_node(&self, id: Uuid) -> Result<bool> {
-        let mut nodes = self.nodes.lock().unwrap();
-        let node_existed = nodes.remove(&id).is_some();
-        
-        if node_existed {
-            // Also remove all relationships involving this node to prevent orphaned references
-            let mut relationships = self.relationships.lock().unwrap();
-            relationships.retain(|rel| rel.source_id != id && rel.target_id != id);
-        }
-        
-        Ok(node_existed)
+    pub fn search_with_context(&self, query: &str) -> Result<SearchContext> {
+        let mut matches = self.search_nodes(query)?;
+        matches.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+        let match_ids: HashSet<Uuid> = matches.iter().map(|node| node.id).collect();
+
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let touching: Vec<Relationship> = relationships
+            .iter()
+            .filter(|rel| match_ids.contains(&rel.source_id) || match_ids.contains(&rel.target_id))
+            .cloned()
+            .collect();
+        let neighbor_ids: HashSet<Uuid> = touching
+            .iter()
+            .flat_map(|rel| [rel.source_id, rel.target_id])
+            .filter(|id| !match_ids.contains(id))
+            .collect();
+        let mut neighbors: Vec<Node> = neighbor_ids.iter().filter_map(|id| nodes.get(id)).cloned().collect();
+        neighbors.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+
+        Ok(SearchContext { matches, relationships: touching, neighbors })
     }
 
-    /// Creates a new relationship in the database
+    /// Searches for nodes and ranks them by relevance
+    ///
+    /// Text relevance depends on where the query matched: an exact label
+    /// match scores 1.0, a label prefix 0.8, anywhere in the label 0.6, an
+    /// exact tag 0.5, part of a tag 0.4, and the description 0.3. When
+    /// `weight_by_confidence` is set, the score is multiplied by
+    /// `0.5 + 0.5 * confidence`, so verified entities outrank speculative ones
+    /// with the same text match. Each result lists every match location so
+    /// the UI can highlight them.
     ///
     /// # Arguments
-    /// * `relationship` - The relationship to store
+    /// * `query` - Search query string (case-insensitive)
+    /// * `weight_by_confidence` - Whether to factor node confidence into the score
     ///
     /// # Returns
-    /// * `Ok(Uuid)` - The UUID of the created relationship
+    /// * `Ok(Vec<SearchResult>)` - Matching nodes, best first
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn create_relationship(&self, relationship: Relationship) -> Result<Uuid> {
-        let relationship_id = relationship.id;
-        let mut relationships = self.relationships.lock().unwrap();
-        relationships.push(relationship);
-        Ok(relationship_id)
+    pub fn search_nodes_ranked(&self, query: &str, weight_by_confidence: bool) -> Result<Vec<SearchResult>> {
+        let nodes = lock(&self.nodes);
+        let query_lower = query.to_lowercase();
+
+        let mut results: Vec<SearchResult> = nodes
+            .values()
+            .filter_map(|node| {
+                let text_score = text_relevance(node, &query_lower)?;
+                let score = if weight_by_confidence {
+                    let confidence = if node.confidence.is_finite() { node.confidence.clamp(0.0, 1.0) } else { 0.0 };
+                    text_score * (0.5 + 0.5 * confidence as f64)
+                } else {
+                    text_score
+                };
+                Some(SearchResult { node: node.clone(), score, matches: match_locations(node, &query_lower) })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.node.label.cmp(&b.node.label))
+                .then_with(|| a.node.id.cmp(&b.node.id))
+        });
+        Ok(results)
     }
 
-    /// Retrieves all relationships from the database
+    /// Searches only the nodes within a given distance of a center node
+    ///
+    /// The subgraph is found by breadth-first search ignoring edge direction;
+    /// the center itself is at depth 0 and is included. Matching uses the
+    /// same fields as [`Database::search_nodes`].
+    ///
+    /// # Arguments
+    /// * `center_id` - Node the subgraph is centered on
+    /// * `depth` - Maximum number of hops from the center
+    /// * `query` - Search query string (case-insensitive)
     ///
     /// # Returns
-    /// * `Ok(Vec<Relationship>)` - Vector containing all relationships
+    /// * `Ok(Some(Vec<Node>))` - Matching nodes inside the subgraph, sorted by label
+    /// * `Ok(None)` - If the center node doesn't exist
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn get_relationships(&self) -> Result<Vec<Relationship>> {
-        let relationships = self.relationships.lock().unwrap();
-        Ok(relationships.clone())
+    pub fn search_within_subgraph(&self, center_id: Uuid, depth: usize, query: &str) -> Result<Option<Vec<Node>>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, false, &[]);
+        if !nodes.contains_key(&center_id) {
+            return Ok(None);
+        }
+
+        let mut distances: HashMap<Uuid, usize> = HashMap::from([(center_id, 0)]);
+        let mut queue = VecDeque::from([center_id]);
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[&current];
+            if distance == depth {
+                continue;
+            }
+            for &neighbor in adjacency.get(&current).into_iter().flat_map(|neighbors| neighbors.keys()) {
+                if let std::collections::hash_map::Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let query_lower = query.to_lowercase();
+        let mut results: Vec<Node> = distances
+            .keys()
+            .filter_map(|id| nodes.get(id))
+            .filter(|node| text_relevance(node, &query_lower).is_some())
+            .cloned()
+            .collect();
+        results.sort_by(|a, b| a.label.cmp(&b.label).then_with(|| a.id.cmp(&b.id)));
+        Ok(Some(results))
     }
 
-    /// Retrieves all relationships involving a specific node
+    /// Finds all nodes whose label exactly matches the given string
     ///
-    /// Returns relationships where the node is either source or target
+    /// # Arguments
+    /// * `label` - Label to match (compared after trimming whitespace)
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Node>)` - All nodes carrying this label
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn find_nodes_by_label(&self, label: &str) -> Result<Vec<Node>> {
+        let nodes = lock(&self.nodes);
+        let label = label.trim();
+        Ok(nodes.values().filter(|node| node.label.trim() == label).cloned().collect())
+    }
+
+    /// Returns all nodes of the given type
     ///
     /// # Arguments
-    /// * `node_id` - UUID of the node to find relationships for
+    /// * `node_type` - Type of node to return
     ///
     /// # Returns
-    /// * `Ok(Vec<Relationship>)` - Vector of relationships involving the node
+    /// * `Ok(Vec<Node>)` - All nodes of this type
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn get_node_relationships(&self, node_id: Uuid) -> Result<Vec<Relationship>> {
-        let relationships = self.relationships.lock().unwrap();
-        let results: Vec<Relationship> = relationships
-            .iter()
-            .filter(|rel| rel.source_id == node_id || rel.target_id == node_id)
+    pub fn get_nodes_by_type(&self, node_type: &NodeType) -> Result<Vec<Node>> {
+        let nodes = lock(&self.nodes);
+        Ok(nodes.values().filter(|node| &node.node_type == node_type).cloned().collect())
+    }
+
+    /// Lists each distinct node source with the number of nodes citing it
+    ///
+    /// Nodes without a source, or with a blank one, are not counted.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<SourceCount>)` - Sources, most frequent first, ties by name
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_all_node_sources(&self) -> Result<Vec<SourceCount>> {
+        let nodes = lock(&self.nodes);
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for source in nodes.values().filter_map(|node| node.source.as_deref()) {
+            if !source.trim().is_empty() {
+                *counts.entry(source).or_insert(0) += 1;
+            }
+        }
+        let mut sources: Vec<SourceCount> = counts
+            .into_iter()
+            .map(|(source, count)| SourceCount { source: source.to_string(), count })
+            .collect();
+        sources.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.source.cmp(&b.source)));
+        Ok(sources)
+    }
+
+    /// Groups Email and Domain nodes that normalize to the same identifier
+    ///
+    /// Catches near-duplicates such as `User@X.COM` and `user@x.com` that
+    /// differ only by case or trailing dots. Normalization is recomputed from
+    /// labels so nodes loaded from older files are included.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<IdentifierDuplicateGroup>)` - Groups with at least two nodes
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn find_identifier_duplicates(&self) -> Result<Vec<IdentifierDuplicateGroup>> {
+        let nodes = lock(&self.nodes);
+
+        // Keyed by type as well so emails and domains never group together
+        let mut groups: HashMap<(String, String), Vec<Uuid>> = HashMap::new();
+        for node in nodes.values() {
+            if let Some(normalized) = normalize_identifier(&node.node_type, &node.label) {
+                let key = (format!("{:?}", node.node_type), normalized);
+                groups.entry(key).or_default().push(node.id);
+            }
+        }
+
+        let mut results: Vec<IdentifierDuplicateGroup> = groups
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|((_, normalized), mut node_ids)| {
+                node_ids.sort();
+                IdentifierDuplicateGroup { normalized, node_ids }
+            })
+            .collect();
+        results.sort_by(|a, b| a.normalized.cmp(&b.normalized));
+        Ok(results)
+    }
+
+    /// Flags nodes whose source is not backed by any incident relationship
+    ///
+    /// A node is flagged when it has a source, has at least one relationship,
+    /// and none of its relationships carry the same source (compared
+    /// case-insensitively after trimming). Nodes without relationships have
+    /// nothing to cross-check and are skipped. The result is advisory.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<SourceMismatch>)` - Flagged nodes, ordered by node ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn cross_check_sources(&self) -> Result<Vec<SourceMismatch>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+
+        let mut incident_sources: HashMap<Uuid, Vec<Option<&str>>> = HashMap::new();
+        for rel in relationships.iter() {
+            let source = rel.source.as_deref().map(str::trim).filter(|s| !s.is_empty());
+            incident_sources.entry(rel.source_id).or_default().push(source);
+            if rel.target_id != rel.source_id {
+                incident_sources.entry(rel.target_id).or_default().push(source);
+            }
+        }
+
+        let mut mismatches: Vec<SourceMismatch> = nodes
+            .values()
+            .filter_map(|node| {
+                let node_source = node.source.as_deref().map(str::trim).filter(|s| !s.is_empty())?;
+                let sources = incident_sources.get(&node.id)?;
+                let corroborated = sources
+                    .iter()
+                    .flatten()
+                    .any(|source| source.eq_ignore_ascii_case(node_source));
+                if corroborated {
+                    return None;
+                }
+
+                let mut relationship_sources: Vec<String> =
+                    sources.iter().flatten().map(|source| source.to_string()).collect();
+                relationship_sources.sort();
+                relationship_sources.dedup();
+                Some(SourceMismatch {
+                    node_id: node.id,
+                    node_source: node_source.to_string(),
+                    relationship_sources,
+                })
+            })
+            .collect();
+        mismatches.sort_by_key(|mismatch| mismatch.node_id);
+        Ok(mismatches)
+    }
+
+    /// Finds nodes whose metadata matches a key/value condition
+    ///
+    /// Uses the same key paths and comparators as
+    /// [`Database::query_relationship_metadata`].
+    ///
+    /// # Arguments
+    /// * `key` - Metadata key or dot-path to inspect
+    /// * `value` - Value to compare against
+    /// * `comparator` - How to compare the stored value with `value`
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Node>)` - Nodes satisfying the condition
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn query_node_metadata(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+        comparator: MetadataComparator,
+    ) -> Result<Vec<Node>> {
+        let nodes = lock(&self.nodes);
+        let results: Vec<Node> = nodes
+            .values()
+            .filter(|node| metadata_matches(&node.metadata, key, value, comparator))
             .cloned()
             .collect();
         Ok(results)
     }
 
-    /// Updates an existing relationship in the database
+    /// Updates an existing node in the database
     ///
-    /// Finds the relationship by UUID and replaces it with new data
+    /// Replaces the existing node with the same UUID. The cached degree is
+    /// owned by the database and is kept from the stored node.
     ///
     /// # Arguments
-    /// * `relationship` - The updated relationship data
+    /// * `node` - The updated node data
     ///
     /// # Returns
     /// * `Ok(())` - If the update succeeds
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn update_relationship(&self, relationship: Relationship) -> Result<()> {
-        let mut relationships = self.relationships.lock().unwrap();
-        if let Some(pos) = relationships.iter().position(|r| r.id == relationship.id) {
-            relationships[pos] = relationship;
-        }
+    pub fn update_node(&self, mut node: Node) -> Result<()> {
+        let mut nodes = lock(&self.nodes);
+        let changed = match nodes.get(&node.id) {
+            Some(existing) => {
+                node.degree = existing.degree;
+                changed_fields(existing, &node)
+            }
+            None => Vec::new(),
+        };
+        let node_id = node.id;
+        nodes.insert(node_id, node);
+        self.record_audit(AuditOperation::Update, AuditEntityType::Node, node_id, changed);
         Ok(())
     }
 
-    /// Deletes a relationship from the database
+    /// Reports what `delete_node` would remove for a node
     ///
     /// # Arguments
-    /// * `id` - UUID of the relationship to delete
+    /// * `id` - UUID of the node
     ///
     /// # Returns
-    /// * `Ok(true)` - If the relationship was found and deleted
-    /// * `Ok(false)` - If no relationship existed with the given ID
+    /// * `Ok(Some(NodeDeletionPreview))` - The node and its relationships
+    /// * `Ok(None)` - If no node exists with the given ID
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn delete_relationship(&self, id: Uuid) -> Result<bool> {
-        let mut relationships = self.relationships.lock().unwrap();
-        if let Some(pos) = relationships.iter().position(|r| r.id == id) {
-            relationships.remove(pos);
-            Ok(true)
-        } else {
-            Ok(false)
+    pub fn preview_node_deletion(&self, id: Uuid) -> Result<Option<NodeDeletionPreview>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        Ok(nodes.get(&id).map(|node| NodeDeletionPreview {
+            node: node.clone(),
+            relationships: relationships
+                .iter()
+                .filter(|rel| rel.source_id == id || rel.target_id == id)
+                .cloned()
+                .collect(),
+        }))
+    }
+
+    /// Deletes a node and all its relationships
+    ///
+    /// Removes the node from storage and cleans up any relationships
+    /// that reference this node to prevent orphaned references
+    ///
+    /// # Arguments
+    /// * `id` - UUID of the node to delete
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the node was found and deleted
+    /// * `Ok(false)` - If no node existed with the given ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn delete_node(&self, id: Uuid) -> Result<bool> {
+        let mut nodes = lock(&self.nodes);
+        let node_existed = nodes.remove(&id).is_some();
+        
+        if node_existed {
+            // Also remove all relationships involving this node to prevent orphaned references
+            let mut relationships = lock(&self.relationships);
+            let mut removed = Vec::new();
+            relationships.retain(|rel| {
+                let involved = rel.source_id == id || rel.target_id == id;
+                if involved {
+                    decrement_degrees(&mut nodes, rel);
+                    removed.push(rel.id);
+                }
+                !involved
+            });
+
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Node, id, Vec::new());
+            for rel_id in removed {
+                self.record_audit(AuditOperation::Delete, AuditEntityType::Relationship, rel_id, Vec::new());
+            }
         }
+        
+        Ok(node_existed)
     }
 
-    /// Clears all data from the database
+    /// Records that a node was just refreshed by an enrichment operation
     ///
-    /// Removes all nodes and relationships, effectively resetting
-    /// the database to an empty state
+    /// # Arguments
+    /// * `id` - UUID of the enriched node
+    /// * `at` - When the enrichment happened
     ///
     /// # Returns
-    /// * `Ok(())` - If the clear operation succeeds
+    /// * `Ok(true)` - If the node was found and updated
+    /// * `Ok(false)` - If no node existed with the given ID
     /// * `Err(anyhow::Error)` - If the operation fails
-    pub fn clear_all(&self) -> Result<()> {
-        let mut nodes = self.nodes.lock().unwrap();
-        let mut relationships = self.relationships.lock().unwrap();
-        nodes.clear();
-        relationships.clear();
-        Ok(())
+    pub fn mark_node_enriched(&self, id: Uuid, at: DateTime<Utc>) -> Result<bool> {
+        let mut nodes = lock(&self.nodes);
+        match nodes.get_mut(&id) {
+            Some(node) => {
+                let change = FieldChange::new("last_enriched", node.last_enriched, Some(at));
+                node.last_enriched = Some(at);
+                self.record_audit(AuditOperation::Update, AuditEntityType::Node, id, vec![change]);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Sets the confidence of several nodes under a single lock
+    ///
+    /// Duplicate IDs are applied once. Every updated node gets a fresh
+    /// `updated_at`.
+    ///
+    /// # Arguments
+    /// * `ids` - UUIDs of the nodes to update
+    /// * `confidence` - New confidence, expected to be within 0.0-1.0
+    ///
+    /// # Returns
+    /// * `Ok(BulkConfidenceUpdate)` - How many nodes changed and which IDs were not found
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn set_confidence_bulk(&self, ids: &[Uuid], confidence: f32) -> Result<BulkConfidenceUpdate> {
+        let mut nodes = lock(&self.nodes);
+        let now = Utc::now();
+        let mut seen = HashSet::new();
+        let mut report = BulkConfidenceUpdate { updated: 0, missing: Vec::new() };
+
+        for &id in ids.iter().filter(|id| seen.insert(**id)) {
+            match nodes.get_mut(&id) {
+                Some(node) => {
+                    let change = FieldChange::new("confidence", node.confidence, confidence);
+                    node.confidence = confidence;
+                    node.updated_at = now;
+                    report.updated += 1;
+                    self.record_audit(AuditOperation::Update, AuditEntityType::Node, id, vec![change]);
+                }
+                None => report.missing.push(id),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Finds enrichable nodes that have not been enriched since a cutoff
+    ///
+    /// Nodes that were never enriched are always included.
+    ///
+    /// # Arguments
+    /// * `cutoff` - Nodes last enriched before this instant are stale
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Node>)` - Stale nodes, least recently enriched first
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_stale_nodes(&self, cutoff: DateTime<Utc>) -> Result<Vec<Node>> {
+        let nodes = lock(&self.nodes);
+        let mut stale: Vec<Node> = nodes
+            .values()
+            .filter(|node| node.node_type.is_enrichable())
+            .filter(|node| node.last_enriched.is_none_or(|at| at < cutoff))
+            .cloned()
+            .collect();
+        stale.sort_by(|a, b| a.last_enriched.cmp(&b.last_enriched).then(a.id.cmp(&b.id)));
+        Ok(stale)
+    }
+
+    /// Sets or clears the pinned flag on a node
+    ///
+    /// # Arguments
+    /// * `id` - UUID of the node to update
+    /// * `pinned` - Whether the node should be protected from bulk removal
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the node was found and updated
+    /// * `Ok(false)` - If no node existed with the given ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn set_node_pinned(&self, id: Uuid, pinned: bool) -> Result<bool> {
+        let mut nodes = lock(&self.nodes);
+        match nodes.get_mut(&id) {
+            Some(node) => {
+                if node.pinned != pinned {
+                    let change = FieldChange::new("pinned", node.pinned, pinned);
+                    node.pinned = pinned;
+                    self.record_audit(AuditOperation::Update, AuditEntityType::Node, id, vec![change]);
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Repeatedly removes leaf nodes (exactly one distinct neighbor)
+    ///
+    /// Each round removes every current leaf at once, so one round on a star
+    /// graph removes all spokes and leaves the center. Direction and parallel
+    /// relationships are ignored when counting neighbors. Pinned nodes are
+    /// never removed; each pinned leaf is counted once in the report.
+    ///
+    /// # Arguments
+    /// * `iterations` - Number of rounds to run; 0 runs until no leaves remain
+    /// * `dry_run` - When true, only report what would be removed
+    ///
+    /// # Returns
+    /// * `Ok(BulkRemovalReport)` - IDs of the removed (or removable) nodes, in removal order, and pinned leaves kept
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn trim_leaves(&self, iterations: usize, dry_run: bool) -> Result<BulkRemovalReport> {
+        let (mut adjacency, pinned) = {
+            let nodes = lock(&self.nodes);
+            let relationships = lock(&self.relationships);
+            let adjacency: HashMap<Uuid, HashSet<Uuid>> = collapsed_adjacency(&nodes, &relationships, false, &[])
+                .into_iter()
+                .map(|(id, neighbors)| (id, neighbors.into_keys().collect()))
+                .collect();
+            let pinned: HashSet<Uuid> = nodes.values().filter(|node| node.pinned).map(|node| node.id).collect();
+            (adjacency, pinned)
+        };
+
+        let mut removed = Vec::new();
+        let mut pinned_leaves = HashSet::new();
+        let mut round = 0;
+        while iterations == 0 || round < iterations {
+            let (kept, mut leaves): (Vec<Uuid>, Vec<Uuid>) = adjacency
+                .iter()
+                .filter(|(_, neighbors)| neighbors.len() == 1)
+                .map(|(id, _)| *id)
+                .partition(|id| pinned.contains(id));
+            pinned_leaves.extend(kept);
+            if leaves.is_empty() {
+                break;
+            }
+            leaves.sort();
+
+            for leaf in &leaves {
+                for neighbor in adjacency.remove(leaf).unwrap_or_default() {
+                    if let Some(neighbors) = adjacency.get_mut(&neighbor) {
+                        neighbors.remove(leaf);
+                    }
+                }
+            }
+            removed.extend(leaves);
+            round += 1;
+        }
+
+        if !dry_run {
+            for id in &removed {
+                self.delete_node(*id)?;
+            }
+        }
+        Ok(BulkRemovalReport { deleted: removed, skipped_pinned: pinned_leaves.len() })
+    }
+
+    /// Returns every node whose expiry time has passed
+    ///
+    /// # Arguments
+    /// * `now` - Nodes expiring at or before this instant are expired
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Node>)` - Expired nodes, earliest expiry first
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_expired_nodes(&self, now: DateTime<Utc>) -> Result<Vec<Node>> {
+        let nodes = lock(&self.nodes);
+        let mut expired: Vec<Node> = nodes.values().filter(|node| node.is_expired(now)).cloned().collect();
+        expired.sort_by(|a, b| a.expires_at.cmp(&b.expires_at).then(a.id.cmp(&b.id)));
+        Ok(expired)
+    }
+
+    /// Deletes every expired node together with its relationships
+    ///
+    /// Pinned nodes are kept even when expired, and counted in the report.
+    ///
+    /// # Arguments
+    /// * `now` - Nodes expiring at or before this instant are purged
+    ///
+    /// # Returns
+    /// * `Ok(BulkRemovalReport)` - Deleted IDs and number of expired pinned nodes kept
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn purge_expired(&self, now: DateTime<Utc>) -> Result<BulkRemovalReport> {
+        let mut report = BulkRemovalReport { deleted: Vec::new(), skipped_pinned: 0 };
+        for node in lock(&self.nodes).values().filter(|node| node.is_expired(now)) {
+            if node.pinned {
+                report.skipped_pinned += 1;
+            } else {
+                report.deleted.push(node.id);
+            }
+        }
+        report.deleted.sort();
+        for id in &report.deleted {
+            self.delete_node(*id)?;
+        }
+        Ok(report)
+    }
+
+    /// Deletes every node that has no relationships
+    ///
+    /// Pinned nodes are skipped and counted in the returned report
+    ///
+    /// # Returns
+    /// * `Ok(BulkRemovalReport)` - Deleted IDs and number of pinned nodes skipped
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn delete_orphan_nodes(&self) -> Result<BulkRemovalReport> {
+        let mut nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+
+        let connected: HashSet<Uuid> = relationships
+            .iter()
+            .flat_map(|rel| [rel.source_id, rel.target_id])
+            .collect();
+
+        let mut report = BulkRemovalReport { deleted: Vec::new(), skipped_pinned: 0 };
+        for node in nodes.values() {
+            if connected.contains(&node.id) {
+                continue;
+            }
+            if node.pinned {
+                report.skipped_pinned += 1;
+            } else {
+                report.deleted.push(node.id);
+            }
+        }
+
+        for id in &report.deleted {
+            nodes.remove(id);
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Node, *id, Vec::new());
+        }
+
+        Ok(report)
+    }
+
+    /// Creates a new relationship in the database
+    ///
+    /// # Arguments
+    /// * `relationship` - The relationship to store
+    ///
+    /// # Returns
+    /// * `Ok(Uuid)` - The UUID of the created relationship
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn create_relationship(&self, relationship: Relationship) -> Result<Uuid> {
+        let relationship_id = relationship.id;
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        increment_degrees(&mut nodes, &relationship);
+        relationships.push(relationship);
+        self.record_audit(AuditOperation::Create, AuditEntityType::Relationship, relationship_id, Vec::new());
+        Ok(relationship_id)
+    }
+
+    /// Creates a relationship unless one with the same idempotency key exists
+    ///
+    /// The key is stored in `metadata.idempotency_key`. Lookup and insertion
+    /// happen under the same lock, so concurrent retries cannot both create.
+    ///
+    /// # Arguments
+    /// * `relationship` - The relationship to store
+    /// * `idempotency_key` - Caller-chosen key identifying this creation
+    ///
+    /// # Returns
+    /// * `Ok(Uuid)` - The UUID of the existing or newly created relationship
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn create_relationship_idempotent(&self, mut relationship: Relationship, idempotency_key: &str) -> Result<Uuid> {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+
+        let existing = relationships.iter().find(|rel| {
+            rel.metadata.get("idempotency_key").and_then(|key| key.as_str()) == Some(idempotency_key)
+        });
+        if let Some(existing) = existing {
+            return Ok(existing.id);
+        }
+
+        if !relationship.metadata.is_object() {
+            relationship.metadata = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let Some(metadata) = relationship.metadata.as_object_mut() {
+            metadata.insert("idempotency_key".to_string(), serde_json::Value::String(idempotency_key.to_string()));
+        }
+
+        let relationship_id = relationship.id;
+        increment_degrees(&mut nodes, &relationship);
+        relationships.push(relationship);
+        self.record_audit(AuditOperation::Create, AuditEntityType::Relationship, relationship_id, Vec::new());
+        Ok(relationship_id)
+    }
+
+    /// Creates a relationship, or merges it into an existing duplicate
+    ///
+    /// A duplicate is a relationship with the same source, target, and type.
+    /// Its confidence and source are combined with the incoming ones under
+    /// the configured [`MergePolicy`]; other fields of the duplicate are
+    /// kept. Regardless of policy, every distinct source is preserved in
+    /// `sources` (see [`Relationship::absorb_sources`]). Lookup and update
+    /// happen under the same lock.
+    ///
+    /// # Arguments
+    /// * `relationship` - The relationship to store or merge
+    ///
+    /// # Returns
+    /// * `Ok(Uuid)` - The UUID of the merged-into or newly created relationship
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn create_or_merge_relationship(&self, relationship: Relationship) -> Result<Uuid> {
+        let policy = lock(&self.settings).merge_policy;
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+
+        let existing = relationships.iter_mut().find(|rel| {
+            rel.source_id == relationship.source_id
+                && rel.target_id == relationship.target_id
+                && rel.relation_type == relationship.relation_type
+        });
+        if let Some(existing) = existing {
+            let before = existing.clone();
+            existing.confidence = policy.confidence.combine(existing.confidence, relationship.confidence);
+            merge_relationship_sources(existing, &relationship, policy.sources);
+            existing.updated_at = Utc::now();
+            let existing_id = existing.id;
+            let changes = changed_fields(&before, &*existing);
+            self.record_audit(AuditOperation::Update, AuditEntityType::Relationship, existing_id, changes);
+            return Ok(existing_id);
+        }
+
+        let relationship_id = relationship.id;
+        increment_degrees(&mut nodes, &relationship);
+        relationships.push(relationship);
+        self.record_audit(AuditOperation::Create, AuditEntityType::Relationship, relationship_id, Vec::new());
+        Ok(relationship_id)
     }
-}
\ No newline at end of file
+
+    /// Retrieves all relationships with their endpoint labels and types resolved
+    ///
+    /// # Returns
+    /// * `Ok(Vec<RelationshipDetail>)` - One entry per relationship, in storage order
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_relationships_detailed(&self) -> Result<Vec<RelationshipDetail>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let endpoint = |id: &Uuid| match nodes.get(id) {
+            Some(node) => (node.label.clone(), Some(node.node_type.clone())),
+            None => (MISSING_NODE_LABEL.to_string(), None),
+        };
+
+        Ok(relationships
+            .iter()
+            .map(|rel| {
+                let (source_label, source_type) = endpoint(&rel.source_id);
+                let (target_label, target_type) = endpoint(&rel.target_id);
+                RelationshipDetail {
+                    relationship: rel.clone(),
+                    source_label,
+                    source_type,
+                    target_label,
+                    target_type,
+                }
+            })
+            .collect())
+    }
+
+    /// Retrieves all relationships from the database
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Relationship>)` - Vector containing all relationships
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_relationships(&self) -> Result<Vec<Relationship>> {
+        let relationships = lock(&self.relationships);
+        Ok(relationships.clone())
+    }
+
+    /// Retrieves a single relationship by its UUID
+    ///
+    /// # Arguments
+    /// * `id` - The UUID of the relationship to retrieve
+    ///
+    /// # Returns
+    /// * `Ok(Some(Relationship))` - The relationship if found
+    /// * `Ok(None)` - If no relationship exists with the given ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_relationship(&self, id: Uuid) -> Result<Option<Relationship>> {
+        let relationships = lock(&self.relationships);
+        Ok(relationships.iter().find(|rel| rel.id == id).cloned())
+    }
+
+    /// Builds the provenance record for a relationship
+    ///
+    /// # Arguments
+    /// * `id` - The UUID of the relationship
+    ///
+    /// # Returns
+    /// * `Ok(Some(Provenance))` - The provenance record if the relationship exists
+    /// * `Ok(None)` - If no relationship exists with the given ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_relationship_provenance(&self, id: Uuid) -> Result<Option<Provenance>> {
+        let Some(rel) = self.get_relationship(id)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Provenance {
+            sources: rel.source_list(),
+            relationship_id: rel.id,
+            source_id: rel.source_id,
+            target_id: rel.target_id,
+            relation_type: rel.relation_type,
+            confidence: rel.confidence,
+            created_at: rel.created_at,
+            updated_at: rel.updated_at,
+            audit_entries: self.get_audit_log_for(id)?,
+        }))
+    }
+
+    /// Collects the nodes and relationships changed since a point in time
+    ///
+    /// Changed entities are those whose `updated_at` is after `since`.
+    /// Deletions come from the audit log; an ID that was deleted and later
+    /// restored is reported as changed, not deleted.
+    ///
+    /// # Arguments
+    /// * `since` - Exclusive cutoff
+    ///
+    /// # Returns
+    /// * `Ok(Delta)` - Changed entities and deleted IDs
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn changes_since(&self, since: DateTime<Utc>) -> Result<Delta> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let audit_log = lock(&self.audit_log);
+
+        let changed_nodes: Vec<Node> = nodes.values().filter(|node| node.updated_at > since).cloned().collect();
+        let changed_relationships: Vec<Relationship> =
+            relationships.iter().filter(|rel| rel.updated_at > since).cloned().collect();
+
+        let mut deleted_nodes = Vec::new();
+        let mut deleted_relationships = Vec::new();
+        let mut seen = HashSet::new();
+        for entry in audit_log.iter() {
+            if entry.timestamp <= since || entry.operation != AuditOperation::Delete || !seen.insert(entry.entity_id) {
+                continue;
+            }
+            match entry.entity_type {
+                AuditEntityType::Node if !nodes.contains_key(&entry.entity_id) => deleted_nodes.push(entry.entity_id),
+                AuditEntityType::Relationship if !relationships.iter().any(|rel| rel.id == entry.entity_id) => {
+                    deleted_relationships.push(entry.entity_id)
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Delta {
+            nodes: changed_nodes,
+            relationships: changed_relationships,
+            deleted_nodes,
+            deleted_relationships,
+        })
+    }
+
+    /// Reconstructs the graph as it existed at a point in time
+    ///
+    /// Keeps nodes created at or before `as_of`, and relationships created at
+    /// or before `as_of` whose endpoints were both kept. Deleted entities are
+    /// gone from the store and cannot be restored.
+    ///
+    /// # Arguments
+    /// * `as_of` - Snapshot time
+    ///
+    /// # Returns
+    /// * `Ok((Vec<Node>, Vec<Relationship>))` - Nodes and relationships in the snapshot
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn snapshot_as_of(&self, as_of: DateTime<Utc>) -> Result<(Vec<Node>, Vec<Relationship>)> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+
+        let snapshot_nodes: Vec<Node> = nodes
+            .values()
+            .filter(|node| node.created_at <= as_of)
+            .cloned()
+            .collect();
+        let kept: HashSet<Uuid> = snapshot_nodes.iter().map(|node| node.id).collect();
+        let snapshot_relationships: Vec<Relationship> = relationships
+            .iter()
+            .filter(|rel| {
+                rel.created_at <= as_of && kept.contains(&rel.source_id) && kept.contains(&rel.target_id)
+            })
+            .cloned()
+            .collect();
+
+        Ok((snapshot_nodes, snapshot_relationships))
+    }
+
+    /// Retrieves all relationships involving a specific node
+    ///
+    /// Returns relationships where the node is either source or target
+    ///
+    /// # Arguments
+    /// * `node_id` - UUID of the node to find relationships for
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Relationship>)` - Vector of relationships involving the node
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_node_relationships(&self, node_id: Uuid) -> Result<Vec<Relationship>> {
+        let relationships = lock(&self.relationships);
+        let results: Vec<Relationship> = relationships
+            .iter()
+            .filter(|rel| rel.source_id == node_id || rel.target_id == node_id)
+            .cloned()
+            .collect();
+        Ok(results)
+    }
+
+    /// Counts a node's relationships by type and direction
+    ///
+    /// A self-loop counts as both incoming and outgoing.
+    ///
+    /// # Arguments
+    /// * `node_id` - UUID of the node
+    ///
+    /// # Returns
+    /// * `Ok(BTreeMap<String, DirectionCounts>)` - Counts keyed by relation type name;
+    ///   types the node has no relationships of are omitted
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_node_relationship_summary(&self, node_id: Uuid) -> Result<BTreeMap<String, DirectionCounts>> {
+        let relationships = lock(&self.relationships);
+        let mut summary: BTreeMap<String, DirectionCounts> = BTreeMap::new();
+        for rel in relationships.iter() {
+            if rel.source_id != node_id && rel.target_id != node_id {
+                continue;
+            }
+            let counts = summary.entry(format!("{:?}", rel.relation_type)).or_default();
+            if rel.source_id == node_id {
+                counts.outgoing += 1;
+            }
+            if rel.target_id == node_id {
+                counts.incoming += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Retrieves all relationships connecting two specific nodes
+    ///
+    /// # Arguments
+    /// * `node_a` - UUID of the first node
+    /// * `node_b` - UUID of the second node
+    /// * `directed` - When true, only relationships from `node_a` to `node_b` are returned
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Relationship>)` - Relationships between the two nodes
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_relationships_between(&self, node_a: Uuid, node_b: Uuid, directed: bool) -> Result<Vec<Relationship>> {
+        let relationships = lock(&self.relationships);
+        let results: Vec<Relationship> = relationships
+            .iter()
+            .filter(|rel| {
+                (rel.source_id == node_a && rel.target_id == node_b)
+                    || (!directed && rel.source_id == node_b && rel.target_id == node_a)
+            })
+            .cloned()
+            .collect();
+        Ok(results)
+    }
+
+    /// Summarizes when two nodes first and last interacted
+    ///
+    /// Each relationship between the nodes (in either direction) is dated by
+    /// `metadata.valid_from` when it holds an RFC 3339 timestamp, and by
+    /// `created_at` otherwise.
+    ///
+    /// # Arguments
+    /// * `node_a` - UUID of the first node
+    /// * `node_b` - UUID of the second node
+    ///
+    /// # Returns
+    /// * `Ok(PairInteractionSummary)` - Relationship count and first/last timestamps
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_pair_interaction_summary(&self, node_a: Uuid, node_b: Uuid) -> Result<PairInteractionSummary> {
+        let between = self.get_relationships_between(node_a, node_b, false)?;
+        let timestamps: Vec<DateTime<Utc>> = between
+            .iter()
+            .map(|rel| {
+                rel.metadata
+                    .get("valid_from")
+                    .and_then(|value| value.as_str())
+                    .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                    .map(|value| value.with_timezone(&Utc))
+                    .unwrap_or(rel.created_at)
+            })
+            .collect();
+
+        Ok(PairInteractionSummary {
+            count: between.len(),
+            first: timestamps.iter().min().copied(),
+            last: timestamps.iter().max().copied(),
+        })
+    }
+
+    /// Summarizes relationship confidence per relationship type
+    ///
+    /// Only types that have at least one relationship are reported.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<RelationConfidenceStats>)` - One entry per type in use, ordered by type name
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_confidence_by_relation_type(&self) -> Result<Vec<RelationConfidenceStats>> {
+        let relationships = lock(&self.relationships);
+        let mut by_type: HashMap<&RelationType, Vec<f32>> = HashMap::new();
+        for rel in relationships.iter() {
+            by_type.entry(&rel.relation_type).or_default().push(rel.confidence);
+        }
+
+        let mut stats: Vec<RelationConfidenceStats> = by_type
+            .into_iter()
+            .map(|(relation_type, confidences)| {
+                let sum: f64 = confidences.iter().map(|&c| c as f64).sum();
+                RelationConfidenceStats {
+                    relation_type: relation_type.clone(),
+                    count: confidences.len(),
+                    mean: (sum / confidences.len() as f64) as f32,
+                    min: confidences.iter().copied().fold(f32::INFINITY, f32::min),
+                    max: confidences.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+                }
+            })
+            .collect();
+        stats.sort_by_key(|entry| format!("{:?}", entry.relation_type));
+        Ok(stats)
+    }
+
+    /// Counts low-confidence and unsourced nodes and relationships
+    ///
+    /// A blank `source` counts as missing.
+    ///
+    /// # Arguments
+    /// * `threshold` - Entities with confidence strictly below this are low confidence
+    ///
+    /// # Returns
+    /// * `Ok(QualityReport)` - Counts and percentages
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_quality_report(&self, threshold: f32) -> Result<QualityReport> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let unsourced = |source: &Option<String>| source.as_deref().is_none_or(|source| source.trim().is_empty());
+        let percent = |part: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                part as f64 * 100.0 / total as f64
+            }
+        };
+
+        let low_confidence_nodes = nodes.values().filter(|node| node.confidence < threshold).count();
+        let low_confidence_relationships = relationships.iter().filter(|rel| rel.confidence < threshold).count();
+
+        Ok(QualityReport {
+            threshold,
+            total_nodes: nodes.len(),
+            low_confidence_nodes,
+            low_confidence_node_percent: percent(low_confidence_nodes, nodes.len()),
+            unsourced_nodes: nodes.values().filter(|node| unsourced(&node.source)).count(),
+            total_relationships: relationships.len(),
+            low_confidence_relationships,
+            low_confidence_relationship_percent: percent(low_confidence_relationships, relationships.len()),
+            unsourced_relationships: relationships.iter().filter(|rel| unsourced(&rel.source)).count(),
+        })
+    }
+
+    /// Counts how the graph grew over time
+    ///
+    /// Nodes and relationships are bucketed by the UTC date of `created_at`.
+    /// Every bucket from the earliest to the latest creation is returned,
+    /// including empty ones, which carry the cumulative totals forward.
+    ///
+    /// # Arguments
+    /// * `bucket` - Calendar period of each bucket
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GrowthPoint>)` - Cumulative counts per bucket, oldest first; empty for an empty graph
+    /// * `Err(anyhow::Error)` - If the range spans more than [`MAX_GROWTH_BUCKETS`] buckets
+    pub fn get_growth_timeline(&self, bucket: TimeBucket) -> Result<Vec<GrowthPoint>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+
+        let mut created: BTreeMap<NaiveDate, (usize, usize)> = BTreeMap::new();
+        for node in nodes.values() {
+            created.entry(bucket.start_of(node.created_at.date_naive())).or_default().0 += 1;
+        }
+        for rel in relationships.iter() {
+            created.entry(bucket.start_of(rel.created_at.date_naive())).or_default().1 += 1;
+        }
+
+        let (Some(&first), Some(&last)) = (created.keys().next(), created.keys().next_back()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut timeline = Vec::new();
+        let (mut node_total, mut relationship_total) = (0, 0);
+        let mut next = Some(first);
+        while let Some(start) = next.filter(|start| *start <= last) {
+            if timeline.len() == MAX_GROWTH_BUCKETS {
+                anyhow::bail!(
+                    "Timeline from {} to {} spans more than {} buckets; use a larger bucket size",
+                    first,
+                    last,
+                    MAX_GROWTH_BUCKETS
+                );
+            }
+            if let Some((node_count, relationship_count)) = created.get(&start) {
+                node_total += node_count;
+                relationship_total += relationship_count;
+            }
+            timeline.push(GrowthPoint { bucket_start: start, nodes: node_total, relationships: relationship_total });
+            next = bucket.next(start);
+        }
+        Ok(timeline)
+    }
+
+    /// Buckets relationship weights into equal-width ranges
+    ///
+    /// The range spans the smallest to the largest weight in use. When every
+    /// weight is equal there is no range to divide, so a single bucket holding
+    /// all relationships is returned.
+    ///
+    /// # Arguments
+    /// * `buckets` - Number of buckets to divide the weight range into (at least 1)
+    ///
+    /// # Returns
+    /// * `Ok(Vec<HistogramBucket>)` - Buckets in ascending order; empty if there are no relationships
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_weight_histogram(&self, buckets: usize) -> Result<Vec<HistogramBucket>> {
+        let relationships = lock(&self.relationships);
+        let weights: Vec<f64> = relationships.iter().map(|rel| rel.weight as f64).collect();
+        if weights.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let min = weights.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if min == max {
+            return Ok(vec![HistogramBucket { min, max, count: weights.len() }]);
+        }
+
+        let buckets = buckets.max(1);
+        let width = (max - min) / buckets as f64;
+        let mut histogram: Vec<HistogramBucket> = (0..buckets)
+            .map(|i| HistogramBucket {
+                min: min + width * i as f64,
+                max: if i + 1 == buckets { max } else { min + width * (i + 1) as f64 },
+                count: 0,
+            })
+            .collect();
+        for weight in weights {
+            let index = (((weight - min) / width) as usize).min(buckets - 1);
+            histogram[index].count += 1;
+        }
+        Ok(histogram)
+    }
+
+    /// Retrieves all relationships tagged with the given hyperedge group
+    ///
+    /// # Arguments
+    /// * `group_id` - Value of `metadata.hyperedge_group` to match
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Relationship>)` - Relationships belonging to the group
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_hyperedge(&self, group_id: &str) -> Result<Vec<Relationship>> {
+        let relationships = lock(&self.relationships);
+        let results: Vec<Relationship> = relationships
+            .iter()
+            .filter(|rel| rel.metadata.get("hyperedge_group").and_then(|v| v.as_str()) == Some(group_id))
+            .cloned()
+            .collect();
+        Ok(results)
+    }
+
+    /// Finds relationships whose metadata matches a key/value condition
+    ///
+    /// Keys may be dot-separated paths into nested objects (e.g.
+    /// `payment.amount`); a leading `metadata.` prefix is ignored.
+    ///
+    /// # Arguments
+    /// * `key` - Metadata key or dot-path to inspect
+    /// * `value` - Value to compare against
+    /// * `comparator` - How to compare the stored value with `value`
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Relationship>)` - Relationships satisfying the condition
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn query_relationship_metadata(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+        comparator: MetadataComparator,
+    ) -> Result<Vec<Relationship>> {
+        let relationships = lock(&self.relationships);
+        let results: Vec<Relationship> = relationships
+            .iter()
+            .filter(|rel| metadata_matches(&rel.metadata, key, value, comparator))
+            .cloned()
+            .collect();
+        Ok(results)
+    }
+
+    /// Updates an existing relationship in the database
+    ///
+    /// Finds the relationship by UUID and replaces it with new data
+    ///
+    /// # Arguments
+    /// * `relationship` - The updated relationship data
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the update succeeds
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn update_relationship(&self, relationship: Relationship) -> Result<()> {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        if let Some(pos) = relationships.iter().position(|r| r.id == relationship.id) {
+            decrement_degrees(&mut nodes, &relationships[pos]);
+            increment_degrees(&mut nodes, &relationship);
+            let changed = changed_fields(&relationships[pos], &relationship);
+            let relationship_id = relationship.id;
+            relationships[pos] = relationship;
+            self.record_audit(AuditOperation::Update, AuditEntityType::Relationship, relationship_id, changed);
+        }
+        Ok(())
+    }
+
+    /// Deletes a relationship from the database
+    ///
+    /// # Arguments
+    /// * `id` - UUID of the relationship to delete
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the relationship was found and deleted
+    /// * `Ok(false)` - If no relationship existed with the given ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn delete_relationship(&self, id: Uuid) -> Result<bool> {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        if let Some(pos) = relationships.iter().position(|r| r.id == id) {
+            let removed = relationships.remove(pos);
+            decrement_degrees(&mut nodes, &removed);
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Relationship, id, Vec::new());
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Deletes every relationship of a given type
+    ///
+    /// # Arguments
+    /// * `relation_type` - Type of relationship to delete
+    /// * `below_confidence` - When set, only relationships with a confidence
+    ///   strictly below this value are deleted
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of relationships deleted
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn delete_relationships_by_type(&self, relation_type: &RelationType, below_confidence: Option<f32>) -> Result<usize> {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+
+        let (removed, kept): (Vec<Relationship>, Vec<Relationship>) =
+            relationships.drain(..).partition(|rel| {
+                &rel.relation_type == relation_type
+                    && below_confidence.is_none_or(|threshold| rel.confidence < threshold)
+            });
+        *relationships = kept;
+
+        for rel in &removed {
+            decrement_degrees(&mut nodes, rel);
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Relationship, rel.id, Vec::new());
+        }
+        Ok(removed.len())
+    }
+
+    /// Finds node pairs where each node is claimed to be the other's parent
+    ///
+    /// `A ParentOf B` and `B ChildOf A` make the same claim; a conflict is a
+    /// pair with claims in both directions. With `auto_fix`, the direction
+    /// backed by the single most confident relationship wins (the earlier
+    /// created one on a tie) and every relationship claiming the opposite
+    /// direction is deleted.
+    ///
+    /// # Arguments
+    /// * `auto_fix` - Whether to delete the losing side of each conflict
+    ///
+    /// # Returns
+    /// * `Ok(HierarchyValidation)` - Conflicts found and relationships removed
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn validate_hierarchy(&self, auto_fix: bool) -> Result<HierarchyValidation> {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+
+        // Claims that the smaller or the larger ID is the parent, keyed by the unordered pair
+        type PairClaims<'a> = (Vec<&'a Relationship>, Vec<&'a Relationship>);
+        let mut claims: BTreeMap<(Uuid, Uuid), PairClaims> = BTreeMap::new();
+        for rel in relationships.iter() {
+            let (parent, child) = match rel.relation_type {
+                RelationType::ParentOf => (rel.source_id, rel.target_id),
+                RelationType::ChildOf => (rel.target_id, rel.source_id),
+                _ => continue,
+            };
+            if parent == child {
+                continue;
+            }
+            let entry = claims.entry((parent.min(child), parent.max(child))).or_default();
+            if parent < child {
+                entry.0.push(rel);
+            } else {
+                entry.1.push(rel);
+            }
+        }
+
+        let strongest = |claims: &[&Relationship]| {
+            claims
+                .iter()
+                .map(|rel| (rel.confidence, std::cmp::Reverse(rel.created_at)))
+                .max_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)))
+        };
+
+        let mut conflicts = Vec::new();
+        let mut to_remove: HashSet<Uuid> = HashSet::new();
+        for ((node_a, node_b), (a_parent, b_parent)) in &claims {
+            if a_parent.is_empty() || b_parent.is_empty() {
+                continue;
+            }
+            if auto_fix {
+                let a_wins = match (strongest(a_parent), strongest(b_parent)) {
+                    (Some(a), Some(b)) => a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)).is_ge(),
+                    _ => true,
+                };
+                let losers = if a_wins { b_parent } else { a_parent };
+                to_remove.extend(losers.iter().map(|rel| rel.id));
+            }
+            conflicts.push(HierarchyConflict {
+                node_a: *node_a,
+                node_b: *node_b,
+                a_parent_of_b: a_parent.iter().map(|rel| rel.id).collect(),
+                b_parent_of_a: b_parent.iter().map(|rel| rel.id).collect(),
+            });
+        }
+
+        let mut removed = Vec::new();
+        relationships.retain(|rel| {
+            if !to_remove.contains(&rel.id) {
+                return true;
+            }
+            decrement_degrees(&mut nodes, rel);
+            removed.push(rel.id);
+            false
+        });
+        for id in &removed {
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Relationship, *id, Vec::new());
+        }
+
+        Ok(HierarchyValidation { conflicts, removed })
+    }
+
+    /// Repairs invalid confidence and weight values
+    ///
+    /// NaN or infinite confidences are reset to the default for new entities
+    /// and finite ones are clamped to 0.0-1.0. Relationship weights that are
+    /// NaN, infinite, or negative are reset to the default weight, and weights
+    /// above [`MAX_RELATIONSHIP_WEIGHT`] are clamped to it. Each repaired
+    /// entity gets a fresh `updated_at` and an audit entry.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of nodes and relationships that were repaired
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn sanitize_numeric_fields(&self) -> Result<usize> {
+        fn sanitize_confidence(confidence: f32, default: f32) -> f32 {
+            if confidence.is_finite() { confidence.clamp(0.0, 1.0) } else { default }
+        }
+
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        let now = Utc::now();
+        let mut repaired = 0;
+
+        for node in nodes.values_mut() {
+            let confidence = sanitize_confidence(node.confidence, DEFAULT_NODE_CONFIDENCE);
+            // Compare bit patterns so NaN counts as changed
+            if confidence.to_bits() != node.confidence.to_bits() {
+                let change = FieldChange::new("confidence", node.confidence, confidence);
+                node.confidence = confidence;
+                node.updated_at = now;
+                repaired += 1;
+                self.record_audit(AuditOperation::Update, AuditEntityType::Node, node.id, vec![change]);
+            }
+        }
+
+        for rel in relationships.iter_mut() {
+            let mut changes = Vec::new();
+            let confidence = sanitize_confidence(rel.confidence, DEFAULT_RELATIONSHIP_CONFIDENCE);
+            if confidence.to_bits() != rel.confidence.to_bits() {
+                changes.push(FieldChange::new("confidence", rel.confidence, confidence));
+                rel.confidence = confidence;
+            }
+            let weight = if !rel.weight.is_finite() || rel.weight < 0.0 {
+                DEFAULT_RELATIONSHIP_WEIGHT
+            } else {
+                rel.weight.min(MAX_RELATIONSHIP_WEIGHT)
+            };
+            if weight.to_bits() != rel.weight.to_bits() {
+                changes.push(FieldChange::new("weight", rel.weight, weight));
+                rel.weight = weight;
+            }
+            if !changes.is_empty() {
+                rel.updated_at = now;
+                repaired += 1;
+                self.record_audit(AuditOperation::Update, AuditEntityType::Relationship, rel.id, changes);
+            }
+        }
+
+        Ok(repaired)
+    }
+
+    /// Lowers relationship confidences that exceed their endpoints' confidences
+    ///
+    /// A relationship can't be more reliable than the entities it connects,
+    /// so each confidence is capped at the smaller of its two endpoint node
+    /// confidences. Relationships with a missing endpoint are left alone.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of relationships whose confidence was lowered
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn cap_relationship_confidence_by_endpoints(&self) -> Result<usize> {
+        let nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        let now = Utc::now();
+        let mut capped = 0;
+
+        for rel in relationships.iter_mut() {
+            let (Some(source), Some(target)) = (nodes.get(&rel.source_id), nodes.get(&rel.target_id)) else {
+                continue;
+            };
+            let cap = source.confidence.min(target.confidence);
+            if rel.confidence > cap {
+                let change = FieldChange::new("confidence", rel.confidence, cap);
+                rel.confidence = cap;
+                rel.updated_at = now;
+                capped += 1;
+                self.record_audit(AuditOperation::Update, AuditEntityType::Relationship, rel.id, vec![change]);
+            }
+        }
+
+        Ok(capped)
+    }
+
+    /// Compacts storage for long-lived sessions
+    ///
+    /// Permanently drops relationships whose endpoints no longer exist,
+    /// shrinks node and relationship storage to fit, and rebuilds the cached
+    /// node degrees. Records are hard-deleted everywhere else, so dangling
+    /// relationships are the only dead records that can accumulate.
+    ///
+    /// # Returns
+    /// * `Ok(CompactionReport)` - What was removed and how much capacity was freed
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn compact(&self) -> Result<CompactionReport> {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+
+        let mut dangling = Vec::new();
+        relationships.retain(|rel| {
+            let keep = nodes.contains_key(&rel.source_id) && nodes.contains_key(&rel.target_id);
+            if !keep {
+                dangling.push(rel.id);
+            }
+            keep
+        });
+
+        let relationship_capacity = relationships.capacity();
+        relationships.shrink_to_fit();
+        let node_capacity = nodes.capacity();
+        nodes.shrink_to_fit();
+
+        for node in nodes.values_mut() {
+            node.degree = 0;
+        }
+        for rel in relationships.iter() {
+            increment_degrees(&mut nodes, rel);
+        }
+
+        for id in &dangling {
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Relationship, *id, Vec::new());
+        }
+
+        Ok(CompactionReport {
+            dangling_relationships_removed: dangling.len(),
+            relationship_capacity_freed: relationship_capacity - relationships.capacity(),
+            node_capacity_freed: node_capacity - nodes.capacity(),
+        })
+    }
+
+    /// Recomputes every node's cached degree from the relationship list
+    ///
+    /// Degrees are kept in sync incrementally; this is a full rebuild for
+    /// recovering from any drift.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the recomputation succeeds
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn refresh_degrees(&self) -> Result<()> {
+        let mut nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        for node in nodes.values_mut() {
+            node.degree = 0;
+        }
+        for rel in relationships.iter() {
+            increment_degrees(&mut nodes, rel);
+        }
+        Ok(())
+    }
+
+    /// Clears all data from the database
+    ///
+    /// Removes all nodes and relationships and resets the project notes.
+    /// The audit history is kept, with a `Delete` entry for every removed
+    /// entity, so `changes_since` still reports the removals.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the clear operation succeeds
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn clear_all(&self) -> Result<()> {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        for rel in relationships.drain(..) {
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Relationship, rel.id, Vec::new());
+        }
+        for id in nodes.drain().map(|(id, _)| id) {
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Node, id, Vec::new());
+        }
+        *lock(&self.project_notes) = ProjectNotes::default();
+        *lock(&self.project_created_at) = None;
+        Ok(())
+    }
+
+    /// Replaces the whole database with a saved project
+    ///
+    /// Used when loading a project file: nodes and relationships are
+    /// inserted without creating audit entries, and the audit log is
+    /// replaced by the one saved with the project, so loading doesn't
+    /// pretend every entity was just created. Node degrees are recomputed
+    /// and the project notes and creation time are reset.
+    ///
+    /// # Arguments
+    /// * `nodes` - Nodes of the project
+    /// * `relationships` - Relationships of the project
+    /// * `audit_log` - Audit history saved with the project
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the restore succeeds
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn restore_project(
+        &self,
+        nodes: Vec<Node>,
+        relationships: Vec<Relationship>,
+        audit_log: Vec<AuditEntry>,
+    ) -> Result<()> {
+        let mut stored_nodes = lock(&self.nodes);
+        let mut stored_relationships = lock(&self.relationships);
+        *stored_nodes = nodes
+            .into_iter()
+            .map(|mut node| {
+                node.degree = 0;
+                (node.id, node)
+            })
+            .collect();
+        for rel in &relationships {
+            increment_degrees(&mut stored_nodes, rel);
+        }
+        *stored_relationships = relationships;
+        *lock(&self.audit_log) = audit_log;
+        *lock(&self.project_notes) = ProjectNotes::default();
+        *lock(&self.project_created_at) = None;
+        Ok(())
+    }
+
+    /// Copies the nodes, relationships, and audit log
+    ///
+    /// # Returns
+    /// A snapshot that [`Database::restore_snapshot`] can put back
+    pub fn snapshot(&self) -> GraphSnapshot {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        GraphSnapshot {
+            nodes: nodes.clone(),
+            relationships: relationships.clone(),
+            audit_log: lock(&self.audit_log).clone(),
+        }
+    }
+
+    /// Puts back the nodes, relationships, and audit log from a snapshot
+    ///
+    /// Everything changed since the snapshot was taken is discarded. Project
+    /// notes and settings are not part of a snapshot and are left alone.
+    ///
+    /// # Arguments
+    /// * `snapshot` - Snapshot taken with [`Database::snapshot`]
+    pub fn restore_snapshot(&self, snapshot: GraphSnapshot) {
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        *nodes = snapshot.nodes;
+        *relationships = snapshot.relationships;
+        *lock(&self.audit_log) = snapshot.audit_log;
+    }
+
+    /// Appends an entry to the audit log
+    ///
+    /// Callers may hold the node and relationship locks; the audit lock is
+    /// always taken last.
+    fn record_audit(
+        &self,
+        operation: AuditOperation,
+        entity_type: AuditEntityType,
+        entity_id: Uuid,
+        changes: Vec<FieldChange>,
+    ) {
+        lock(&self.audit_log).push(AuditEntry {
+            timestamp: Utc::now(),
+            operation,
+            entity_type,
+            entity_id,
+            changed_fields: changes.iter().map(|change| change.field.clone()).collect(),
+            changes,
+        });
+    }
+
+    /// Retrieves audit log entries, optionally limited to a time range
+    ///
+    /// # Arguments
+    /// * `from` - Only include entries at or after this time
+    /// * `to` - Only include entries at or before this time
+    ///
+    /// # Returns
+    /// * `Ok(Vec<AuditEntry>)` - Matching entries in chronological order
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_audit_log(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<Vec<AuditEntry>> {
+        let audit_log = lock(&self.audit_log);
+        let mut entries: Vec<AuditEntry> = audit_log
+            .iter()
+            .filter(|entry| from.is_none_or(|from| entry.timestamp >= from))
+            .filter(|entry| to.is_none_or(|to| entry.timestamp <= to))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|entry| entry.timestamp);
+        Ok(entries)
+    }
+
+    /// Retrieves the audit log entries for a single entity
+    ///
+    /// # Arguments
+    /// * `entity_id` - ID of the node or relationship
+    ///
+    /// # Returns
+    /// * `Ok(Vec<AuditEntry>)` - Entries for the entity in chronological order
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_audit_log_for(&self, entity_id: Uuid) -> Result<Vec<AuditEntry>> {
+        let audit_log = lock(&self.audit_log);
+        let mut entries: Vec<AuditEntry> = audit_log
+            .iter()
+            .filter(|entry| entry.entity_id == entity_id)
+            .cloned()
+            .collect();
+        entries.sort_by_key(|entry| entry.timestamp);
+        Ok(entries)
+    }
+
+    /// Scales relationship weights down toward leaf nodes for display
+    ///
+    /// Each edge's weight is multiplied by `1 - LEAF_EDGE_DECAY / n`, where
+    /// `n` is the number of distinct neighbors of its less-connected
+    /// endpoint, so spokes to leaves fade while edges between hubs keep
+    /// nearly their full weight. Stored weights are not changed.
+    ///
+    /// # Returns
+    /// * `Ok(HashMap<Uuid, f64>)` - Adjusted weight per relationship ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn compute_visual_edge_weights(&self) -> Result<HashMap<Uuid, f64>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, false, &[]);
+        let neighbor_count = |id: &Uuid| adjacency.get(id).map_or(0, |neighbors| neighbors.len());
+
+        Ok(relationships
+            .iter()
+            .map(|rel| {
+                let periphery = neighbor_count(&rel.source_id).min(neighbor_count(&rel.target_id)).max(1);
+                let factor = 1.0 - LEAF_EDGE_DECAY / periphery as f64;
+                (rel.id, rel.weight as f64 * factor)
+            })
+            .collect())
+    }
+
+    /// Computes degree centrality for every node
+    ///
+    /// When `directed` is true, in-degree and out-degree are counted separately
+    /// and `centrality` is based on the total; symmetric relationship types,
+    /// which directed traversals follow both ways, count once per endpoint
+    /// in their stored direction. When false, each neighbor counts once
+    /// regardless of direction. Parallel relationships are consolidated (see
+    /// [`collapsed_adjacency`]): they count as a single neighbor, and their
+    /// weights add up in `strength`.
+    ///
+    /// With a recency half-life, each node also gets a `recency_weighted`
+    /// score in which edges decay with the age of their latest update, and
+    /// results are ranked by that score so currently active hubs come first.
+    ///
+    /// # Arguments
+    /// * `directed` - Whether to distinguish incoming from outgoing relationships
+    /// * `recency_half_life_days` - Age in days at which an edge counts half; `None` disables recency weighting
+    ///
+    /// # Returns
+    /// * `Ok(Vec<DegreeCentrality>)` - Scores sorted by descending degree, or by
+    ///   descending recency-weighted score when recency weighting is enabled
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn compute_degree_centrality(
+        &self,
+        directed: bool,
+        recency_half_life_days: Option<f64>,
+    ) -> Result<Vec<DegreeCentrality>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, directed, &[]);
+        let now = Utc::now();
+        let recency_factor = |updated_at: DateTime<Utc>, half_life_days: f64| {
+            let age_days = (now - updated_at).num_seconds().max(0) as f64 / 86_400.0;
+            0.5_f64.powf(age_days / half_life_days)
+        };
+
+        let mut stored: HashSet<(Uuid, Uuid)> = HashSet::new();
+        let mut strengths: HashMap<Uuid, f64> = HashMap::new();
+        for rel in relationships.iter() {
+            if rel.source_id == rel.target_id
+                || !nodes.contains_key(&rel.source_id)
+                || !nodes.contains_key(&rel.target_id)
+            {
+                continue;
+            }
+            stored.insert((rel.source_id, rel.target_id));
+            for id in [rel.source_id, rel.target_id] {
+                *strengths.entry(id).or_insert(0.0) += rel.weight as f64;
+            }
+        }
+
+        let mut out_degrees: HashMap<Uuid, usize> = HashMap::new();
+        let mut in_degrees: HashMap<Uuid, usize> = HashMap::new();
+        let mut recency: HashMap<Uuid, f64> = HashMap::new();
+        for (from, neighbors) in &adjacency {
+            for (to, edge) in neighbors {
+                // Skip the reverse entries that only mirror a symmetric relationship
+                if directed && !stored.contains(&(*from, *to)) {
+                    continue;
+                }
+                *out_degrees.entry(*from).or_insert(0) += 1;
+                *in_degrees.entry(*to).or_insert(0) += 1;
+                if let Some(half_life_days) = recency_half_life_days {
+                    let factor = recency_factor(edge.updated_at, half_life_days);
+                    *recency.entry(*from).or_insert(0.0) += factor;
+                    if directed {
+                        *recency.entry(*to).or_insert(0.0) += factor;
+                    }
+                }
+            }
+        }
+
+        let max_degree = nodes.len().saturating_sub(1).max(1) as f64;
+        let mut results: Vec<DegreeCentrality> = nodes
+            .keys()
+            .map(|id| {
+                let out_degree = out_degrees.get(id).copied().unwrap_or(0);
+                let in_degree = in_degrees.get(id).copied().unwrap_or(0);
+                // Undirected adjacency already lists every neighbor on both sides
+                let degree = if directed { in_degree + out_degree } else { out_degree };
+                DegreeCentrality {
+                    node_id: *id,
+                    in_degree: directed.then_some(in_degree),
+                    out_degree: directed.then_some(out_degree),
+                    degree,
+                    strength: strengths.get(id).copied().unwrap_or(0.0),
+                    centrality: degree as f64 / max_degree,
+                    recency_weighted: recency_half_life_days.map(|_| recency.get(id).copied().unwrap_or(0.0)),
+                }
+            })
+            .collect();
+
+        if recency_half_life_days.is_some() {
+            results.sort_by(|a, b| {
+                b.recency_weighted
+                    .partial_cmp(&a.recency_weighted)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.node_id.cmp(&b.node_id))
+            });
+        } else {
+            results.sort_by(|a, b| b.degree.cmp(&a.degree).then(a.node_id.cmp(&b.node_id)));
+        }
+        Ok(results)
+    }
+
+    /// Computes the diameter and average shortest path length of the graph
+    ///
+    /// Edge direction is ignored. When the graph is disconnected, only the
+    /// largest connected component is measured (ties go to the component
+    /// containing the smallest node ID), because distances between
+    /// components are undefined. Runs a breadth-first search from every node
+    /// of that component.
+    ///
+    /// # Returns
+    /// * `Ok(PathLengthStats)` - Diameter, average path length, and a pair achieving the diameter
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_path_length_stats(&self) -> Result<PathLengthStats> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, false, &[]);
+        let mut node_ids: Vec<Uuid> = nodes.keys().copied().collect();
+        node_ids.sort();
+
+        let neighbors_of = |id: &Uuid| adjacency.get(id).into_iter().flat_map(|neighbors| neighbors.keys().copied());
+
+        let mut seen: HashSet<Uuid> = HashSet::new();
+        let mut largest: Vec<Uuid> = Vec::new();
+        for &start in &node_ids {
+            if !seen.insert(start) {
+                continue;
+            }
+            let mut component = vec![start];
+            let mut queue = VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                for neighbor in neighbors_of(&current) {
+                    if seen.insert(neighbor) {
+                        component.push(neighbor);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            if component.len() > largest.len() {
+                largest = component;
+            }
+        }
+        largest.sort();
+
+        let mut diameter = 0;
+        let mut diameter_endpoints = None;
+        let mut total_length = 0usize;
+        let mut pair_count = 0usize;
+        for &start in &largest {
+            let mut distances: HashMap<Uuid, usize> = HashMap::from([(start, 0)]);
+            let mut queue = VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                let distance = distances[&current];
+                for neighbor in neighbors_of(&current) {
+                    if let std::collections::hash_map::Entry::Vacant(entry) = distances.entry(neighbor) {
+                        entry.insert(distance + 1);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            for (&end, &distance) in &distances {
+                if end == start {
+                    continue;
+                }
+                total_length += distance;
+                pair_count += 1;
+                // Every pair is seen from both ends; keep the smallest pair among ties
+                let pair = (start.min(end), start.max(end));
+                if distance > diameter || (distance == diameter && diameter_endpoints.is_some_and(|best| pair < best)) {
+                    diameter = distance;
+                    diameter_endpoints = Some(pair);
+                }
+            }
+        }
+
+        Ok(PathLengthStats {
+            component_size: largest.len(),
+            diameter,
+            average_path_length: if pair_count == 0 { 0.0 } else { total_length as f64 / pair_count as f64 },
+            diameter_endpoints,
+        })
+    }
+
+    /// Finds the shortest path between two nodes
+    ///
+    /// Uses breadth-first search over the consolidated adjacency, so the path
+    /// with the fewest hops wins. Edge direction is ignored unless
+    /// `constraints.directed` is set, in which case only source-to-target
+    /// edges are followed, plus symmetric relationships in either direction.
+    /// Intermediate nodes whose type is listed in `avoid_node_types` are never
+    /// visited; the two endpoints themselves are always allowed. When
+    /// `allowed_relation_types` is non-empty, only relationships of those
+    /// types are traversed.
+    ///
+    /// # Arguments
+    /// * `source_id` - Node to start from
+    /// * `target_id` - Node to reach
+    /// * `constraints` - Node types to avoid, relationship types to follow, and direction handling
+    ///
+    /// # Returns
+    /// * `Ok(Some(Vec<Uuid>))` - Node IDs along the path, including both endpoints
+    /// * `Ok(None)` - If either node is missing or no path satisfies the constraints
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn find_shortest_path(
+        &self,
+        source_id: Uuid,
+        target_id: Uuid,
+        constraints: &PathConstraints,
+    ) -> Result<Option<Vec<Uuid>>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency =
+            collapsed_adjacency(&nodes, &relationships, constraints.directed, &constraints.allowed_relation_types);
+
+        if !nodes.contains_key(&source_id) || !nodes.contains_key(&target_id) {
+            return Ok(None);
+        }
+
+        let mut previous: HashMap<Uuid, Uuid> = HashMap::new();
+        let mut visited: HashSet<Uuid> = HashSet::from([source_id]);
+        let mut queue: VecDeque<Uuid> = VecDeque::from([source_id]);
+
+        while let Some(current) = queue.pop_front() {
+            if current == target_id {
+                let mut path = vec![target_id];
+                let mut step = target_id;
+                while let Some(&prev) = previous.get(&step) {
+                    path.push(prev);
+                    step = prev;
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+
+            for &neighbor in adjacency.get(&current).into_iter().flat_map(|neighbors| neighbors.keys()) {
+                if visited.contains(&neighbor) {
+                    continue;
+                }
+                let avoided = neighbor != target_id
+                    && nodes.get(&neighbor).is_some_and(|n| constraints.avoid_node_types.contains(&n.node_type));
+                if avoided {
+                    continue;
+                }
+                visited.insert(neighbor);
+                previous.insert(neighbor, current);
+                queue.push_back(neighbor);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Computes clusters of nodes connected through `SameAs` relationships
+    ///
+    /// If `A SameAs B` and `B SameAs C`, all three end up in one cluster
+    /// regardless of edge direction. Optionally creates the direct `SameAs`
+    /// edges that are missing inside each cluster; inferred edges take the
+    /// lowest confidence found among the cluster's existing `SameAs` edges
+    /// and are marked with `metadata.inferred`.
+    ///
+    /// # Arguments
+    /// * `create_missing` - Whether to add the missing direct edges
+    ///
+    /// # Returns
+    /// * `Ok(SameAsInference)` - Clusters (two or more nodes each) and created edge IDs
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn infer_same_as_transitivity(&self, create_missing: bool) -> Result<SameAsInference> {
+        let (clusters, missing) = {
+            let nodes = lock(&self.nodes);
+            let relationships = lock(&self.relationships);
+
+            let same_as: Vec<&Relationship> = relationships
+                .iter()
+                .filter(|rel| rel.relation_type == RelationType::SameAs)
+                .filter(|rel| nodes.contains_key(&rel.source_id) && nodes.contains_key(&rel.target_id))
+                .collect();
+
+            let clusters = same_as_clusters(&same_as);
+
+            let linked: HashSet<(Uuid, Uuid)> = same_as
+                .iter()
+                .flat_map(|rel| [(rel.source_id, rel.target_id), (rel.target_id, rel.source_id)])
+                .collect();
+
+            let mut missing: Vec<(Uuid, Uuid, f32)> = Vec::new();
+            for cluster in &clusters {
+                let members: HashSet<&Uuid> = cluster.iter().collect();
+                let confidence = same_as
+                    .iter()
+                    .filter(|rel| members.contains(&rel.source_id))
+                    .map(|rel| rel.confidence)
+                    .fold(1.0_f32, f32::min);
+                for (i, a) in cluster.iter().enumerate() {
+                    for b in &cluster[i + 1..] {
+                        if !linked.contains(&(*a, *b)) {
+                            missing.push((*a, *b, confidence));
+                        }
+                    }
+                }
+            }
+
+            (clusters, missing)
+        };
+
+        let mut created = Vec::new();
+        if create_missing {
+            for (source_id, target_id, confidence) in missing {
+                let mut relationship = Relationship::new(source_id, target_id, RelationType::SameAs)
+                    .with_confidence(confidence)
+                    .with_source("Inferred (SameAs transitivity)".to_string());
+                relationship.metadata = serde_json::json!({ "inferred": "same_as_transitivity" });
+                created.push(self.create_relationship(relationship)?);
+            }
+        }
+
+        Ok(SameAsInference { clusters, created })
+    }
+
+    /// Folds duplicate nodes into a single node
+    ///
+    /// The kept node gains the merged nodes' tags, the highest confidence,
+    /// and any description, source, or metadata keys it lacks; the merged
+    /// labels are appended to `metadata.merged_labels`, together with the
+    /// labels the merged nodes had absorbed in earlier merges. Relationships
+    /// of the merged nodes are re-pointed at the kept node and get a fresh
+    /// `updated_at`. Any that would become self-loops (such as the `SameAs`
+    /// edges between duplicates) are dropped, and any that now duplicate
+    /// another relationship of the kept node (same source, target, and type)
+    /// are folded into it, which combines the confidences and the source
+    /// references under the configured [`MergePolicy`] and collects every
+    /// source in `sources`. Merge IDs that don't exist or equal `keep_id` are ignored.
+    /// Pinned nodes are never folded in: they are left in place and listed
+    /// in `skipped_pinned`, so a pinned node only survives a merge as the kept node.
+    ///
+    /// # Arguments
+    /// * `keep_id` - Node that survives the merge
+    /// * `merge_ids` - Nodes to fold into it and remove
+    ///
+    /// # Returns
+    /// * `Ok(Some(NodeMerge))` - What was merged and moved
+    /// * `Ok(None)` - If the kept node doesn't exist
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn merge_nodes(&self, keep_id: Uuid, merge_ids: &[Uuid]) -> Result<Option<NodeMerge>> {
+        let policy = lock(&self.settings).merge_policy;
+        let mut nodes = lock(&self.nodes);
+        let mut relationships = lock(&self.relationships);
+        if !nodes.contains_key(&keep_id) {
+            return Ok(None);
+        }
+
+        let mut absorbed: Vec<Node> = Vec::new();
+        let mut skipped_pinned = Vec::new();
+        for id in merge_ids {
+            if *id == keep_id {
+                continue;
+            }
+            if nodes.get(id).is_some_and(|node| node.pinned) {
+                skipped_pinned.push(*id);
+            } else if let Some(node) = nodes.remove(id) {
+                absorbed.push(node);
+            }
+        }
+        let merged: Vec<Uuid> = absorbed.iter().map(|node| node.id).collect();
+
+        let now = Utc::now();
+        let keeper_before = nodes.get(&keep_id).cloned();
+        if let Some(keeper) = nodes.get_mut(&keep_id) {
+            let mut merged_labels: Vec<serde_json::Value> = keeper
+                .metadata
+                .get("merged_labels")
+                .and_then(|labels| labels.as_array())
+                .cloned()
+                .unwrap_or_default();
+            for node in absorbed {
+                if let Some(history) = node.metadata.get("merged_labels").and_then(|labels| labels.as_array()) {
+                    for label in history {
+                        if !merged_labels.contains(label) && label.as_str() != Some(keeper.label.as_str()) {
+                            merged_labels.push(label.clone());
+                        }
+                    }
+                }
+                for tag in node.tags {
+                    if !keeper.tags.contains(&tag) {
+                        keeper.tags.push(tag);
+                    }
+                }
+                keeper.confidence = keeper.confidence.max(node.confidence);
+                if keeper.description.is_none() {
+                    keeper.description = node.description;
+                }
+                if keeper.source.is_none() {
+                    keeper.source = node.source;
+                }
+                let mut metadata = node.metadata;
+                if metadata.is_object() && keeper.metadata.is_object() {
+                    merge_metadata(&mut metadata, keeper.metadata.clone());
+                    keeper.metadata = metadata;
+                }
+                let label = serde_json::Value::String(node.label);
+                if label.as_str() != Some(keeper.label.as_str()) && !merged_labels.contains(&label) {
+                    merged_labels.push(label);
+                }
+            }
+            if !merged_labels.is_empty() {
+                if let Some(metadata) = keeper.metadata.as_object_mut() {
+                    metadata.insert("merged_labels".to_string(), serde_json::Value::Array(merged_labels));
+                }
+            }
+            keeper.updated_at = now;
+        }
+
+        let merged_set: HashSet<Uuid> = merged.iter().copied().collect();
+        let mut moved = Vec::new();
+        let mut removed = Vec::new();
+        relationships.retain_mut(|rel| {
+            let touches = merged_set.contains(&rel.source_id) || merged_set.contains(&rel.target_id);
+            if !touches {
+                return true;
+            }
+            let before = rel.clone();
+            if merged_set.contains(&rel.source_id) {
+                rel.source_id = keep_id;
+            }
+            if merged_set.contains(&rel.target_id) {
+                rel.target_id = keep_id;
+            }
+            if rel.source_id == rel.target_id {
+                removed.push(rel.id);
+                false
+            } else {
+                rel.updated_at = now;
+                moved.push((rel.id, changed_fields(&before, &*rel)));
+                true
+            }
+        });
+
+        // Fold re-pointed relationships into an existing one with the same
+        // endpoints and type, preferring a relationship that wasn't moved
+        let moved_ids: HashSet<Uuid> = moved.iter().map(|(id, _)| *id).collect();
+        let mut survivors: HashMap<(Uuid, Uuid, RelationType), usize> = HashMap::new();
+        let mut folds: Vec<(usize, usize)> = Vec::new();
+        for pass_moved in [false, true] {
+            for (index, rel) in relationships.iter().enumerate() {
+                let touches_keeper = rel.source_id == keep_id || rel.target_id == keep_id;
+                if !touches_keeper || moved_ids.contains(&rel.id) != pass_moved {
+                    continue;
+                }
+                let key = (rel.source_id, rel.target_id, rel.relation_type.clone());
+                match survivors.get(&key) {
+                    Some(&survivor) if pass_moved => folds.push((index, survivor)),
+                    Some(_) => {}
+                    None => {
+                        survivors.insert(key, index);
+                    }
+                }
+            }
+        }
+        let mut survivor_changes: HashMap<Uuid, (Relationship, Vec<FieldChange>)> = HashMap::new();
+        let mut deduplicated = HashSet::new();
+        for (index, survivor) in folds {
+            let duplicate = relationships[index].clone();
+            let target = &mut relationships[survivor];
+            let before = survivor_changes
+                .remove(&target.id)
+                .map(|(before, _)| before)
+                .unwrap_or_else(|| target.clone());
+            target.confidence = policy.confidence.combine(target.confidence, duplicate.confidence);
+            merge_relationship_sources(target, &duplicate, policy.sources);
+            target.updated_at = now;
+            let changes = changed_fields(&before, &*target);
+            survivor_changes.insert(target.id, (before, changes));
+            deduplicated.insert(duplicate.id);
+        }
+        relationships.retain(|rel| !deduplicated.contains(&rel.id));
+        moved.retain(|(id, _)| !deduplicated.contains(id));
+
+        for node in nodes.values_mut() {
+            node.degree = 0;
+        }
+        for rel in relationships.iter() {
+            increment_degrees(&mut nodes, rel);
+        }
+
+        if let (Some(before), Some(after)) = (&keeper_before, nodes.get(&keep_id)) {
+            self.record_audit(AuditOperation::Update, AuditEntityType::Node, keep_id, changed_fields(before, after));
+        }
+        for id in &merged {
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Node, *id, Vec::new());
+        }
+        for (id, changes) in &moved {
+            self.record_audit(AuditOperation::Update, AuditEntityType::Relationship, *id, changes.clone());
+        }
+        for (id, (_, changes)) in survivor_changes {
+            if !changes.is_empty() {
+                self.record_audit(AuditOperation::Update, AuditEntityType::Relationship, id, changes);
+            }
+        }
+        for id in removed.iter().chain(&deduplicated) {
+            self.record_audit(AuditOperation::Delete, AuditEntityType::Relationship, *id, Vec::new());
+        }
+
+        Ok(Some(NodeMerge {
+            kept: keep_id,
+            merged,
+            relationships_moved: moved.len(),
+            relationships_removed: removed.len(),
+            relationships_deduplicated: deduplicated.len(),
+            skipped_pinned,
+        }))
+    }
+
+    /// Merges every `SameAs` cluster whose links are all confident enough
+    ///
+    /// A cluster qualifies only if every `SameAs` relationship inside it has
+    /// at least `min_confidence`; clusters with any weaker link are left for
+    /// manual review. Each qualifying cluster is merged (see
+    /// [`Database::merge_nodes`]) into its pinned node if it has one,
+    /// otherwise into its highest-confidence node, with the earliest created
+    /// node winning ties. Any further pinned nodes in the cluster are left in
+    /// place and listed in the merge's `skipped_pinned`.
+    ///
+    /// # Arguments
+    /// * `min_confidence` - Minimum confidence every linking edge must have
+    ///
+    /// # Returns
+    /// * `Ok(SameAsMergeReport)` - Merges performed and clusters skipped
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn auto_merge_same_as(&self, min_confidence: f32) -> Result<SameAsMergeReport> {
+        let (qualifying, skipped) = {
+            let nodes = lock(&self.nodes);
+            let relationships = lock(&self.relationships);
+
+            let same_as: Vec<&Relationship> = relationships
+                .iter()
+                .filter(|rel| rel.relation_type == RelationType::SameAs)
+                .filter(|rel| nodes.contains_key(&rel.source_id) && nodes.contains_key(&rel.target_id))
+                .collect();
+
+            let mut qualifying: Vec<(Uuid, Vec<Uuid>)> = Vec::new();
+            let mut skipped: Vec<Vec<Uuid>> = Vec::new();
+            for cluster in same_as_clusters(&same_as) {
+                let members: HashSet<&Uuid> = cluster.iter().collect();
+                let confident = same_as
+                    .iter()
+                    .filter(|rel| members.contains(&rel.source_id))
+                    .all(|rel| rel.confidence >= min_confidence);
+                if !confident {
+                    skipped.push(cluster);
+                    continue;
+                }
+                let keep_id = cluster
+                    .iter()
+                    .filter_map(|id| nodes.get(id))
+                    .min_by(|a, b| {
+                        b.pinned
+                            .cmp(&a.pinned)
+                            .then_with(|| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal))
+                            .then_with(|| a.created_at.cmp(&b.created_at))
+                            .then_with(|| a.id.cmp(&b.id))
+                    })
+                    .map(|node| node.id);
+                if let Some(keep_id) = keep_id {
+                    let others = cluster.into_iter().filter(|id| *id != keep_id).collect();
+                    qualifying.push((keep_id, others));
+                }
+            }
+            (qualifying, skipped)
+        };
+
+        let mut merges = Vec::new();
+        for (keep_id, others) in qualifying {
+            if let Some(merge) = self.merge_nodes(keep_id, &others)? {
+                merges.push(merge);
+            }
+        }
+        Ok(SameAsMergeReport { merges, skipped })
+    }
+
+    /// Summarizes confidence across the connected component containing a node
+    ///
+    /// The component is found ignoring edge direction.
+    ///
+    /// # Arguments
+    /// * `node_id` - Any node in the component
+    ///
+    /// # Returns
+    /// * `Ok(Some(ClusterConfidence))` - Statistics for the component
+    /// * `Ok(None)` - If the node doesn't exist
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn get_cluster_confidence(&self, node_id: Uuid) -> Result<Option<ClusterConfidence>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, false, &[]);
+
+        if !nodes.contains_key(&node_id) {
+            return Ok(None);
+        }
+
+        let mut component: HashSet<Uuid> = HashSet::from([node_id]);
+        let mut queue = VecDeque::from([node_id]);
+        while let Some(current) = queue.pop_front() {
+            for &neighbor in adjacency.get(&current).into_iter().flat_map(|neighbors| neighbors.keys()) {
+                if component.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let confidences: Vec<f32> = component.iter().filter_map(|id| nodes.get(id)).map(|node| node.confidence).collect();
+        let mean_node_confidence = confidences.iter().map(|c| *c as f64).sum::<f64>() / confidences.len() as f64;
+        let min_node_confidence = confidences.iter().copied().fold(f32::INFINITY, f32::min);
+        let max_node_confidence = confidences.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        let internal: Vec<f64> = relationships
+            .iter()
+            .filter(|rel| component.contains(&rel.source_id) && component.contains(&rel.target_id))
+            .map(|rel| rel.confidence as f64)
+            .collect();
+        let mean_relationship_confidence =
+            (!internal.is_empty()).then(|| internal.iter().sum::<f64>() / internal.len() as f64);
+
+        let mut node_ids: Vec<Uuid> = component.into_iter().collect();
+        node_ids.sort();
+
+        Ok(Some(ClusterConfidence {
+            node_ids,
+            mean_node_confidence,
+            min_node_confidence,
+            max_node_confidence,
+            relationship_count: internal.len(),
+            mean_relationship_confidence,
+        }))
+    }
+
+    /// Finds the nodes adjacent to both of two node sets
+    ///
+    /// Relationships are treated as undirected. Members of either set are
+    /// not counted as neighbors, so the result only contains outside nodes
+    /// both groups connect to, such as a shared contact or asset.
+    ///
+    /// # Arguments
+    /// * `set_a` - First group of nodes
+    /// * `set_b` - Second group of nodes
+    ///
+    /// # Returns
+    /// * `Ok(NodeSetComparison)` - Common neighbors and overlap counts
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn compare_node_sets(&self, set_a: &[Uuid], set_b: &[Uuid]) -> Result<NodeSetComparison> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, false, &[]);
+        let members: HashSet<Uuid> = set_a.iter().chain(set_b).copied().collect();
+        let neighbors_of = |set: &[Uuid]| -> HashSet<Uuid> {
+            set.iter()
+                .filter_map(|id| adjacency.get(id))
+                .flat_map(|neighbors| neighbors.keys().copied())
+                .filter(|neighbor| !members.contains(neighbor))
+                .collect()
+        };
+        let neighbors_a = neighbors_of(set_a);
+        let neighbors_b = neighbors_of(set_b);
+
+        let mut common_neighbors: Vec<Node> = neighbors_a
+            .intersection(&neighbors_b)
+            .filter_map(|id| nodes.get(id).cloned())
+            .collect();
+        common_neighbors.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+
+        Ok(NodeSetComparison {
+            overlap: common_neighbors.len(),
+            common_neighbors,
+            neighbors_a: neighbors_a.len(),
+            neighbors_b: neighbors_b.len(),
+        })
+    }
+
+    /// Ranks nodes by how similar their neighborhoods are to a given node
+    ///
+    /// Uses weighted Jaccard similarity over the consolidated, undirected
+    /// adjacency: the sum of the smaller edge weight to each neighbor divided
+    /// by the sum of the larger one. With unit weights this is the plain
+    /// Jaccard index of the two neighbor sets. Nodes sharing no neighbor are
+    /// left out.
+    ///
+    /// # Arguments
+    /// * `node_id` - Reference node
+    /// * `top_n` - Maximum number of results
+    ///
+    /// # Returns
+    /// * `Ok(Vec<SimilarityScore>)` - Most similar nodes first, ties broken by node ID
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn compute_structural_similarity(&self, node_id: Uuid, top_n: usize) -> Result<Vec<SimilarityScore>> {
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, false, &[]);
+        let Some(reference) = adjacency.get(&node_id) else {
+            return Ok(Vec::new());
+        };
+
+        // Only nodes two hops away can share a neighbor with the reference
+        let candidates: HashSet<Uuid> = reference
+            .keys()
+            .filter_map(|neighbor| adjacency.get(neighbor))
+            .flat_map(|neighbors| neighbors.keys().copied())
+            .filter(|candidate| *candidate != node_id)
+            .collect();
+
+        let mut results: Vec<SimilarityScore> = candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                let other = adjacency.get(&candidate)?;
+                let mut intersection = 0.0;
+                let mut union = 0.0;
+                for neighbor in reference.keys().chain(other.keys().filter(|id| !reference.contains_key(id))) {
+                    let a = reference.get(neighbor).map_or(0.0, |edge| edge.weight);
+                    let b = other.get(neighbor).map_or(0.0, |edge| edge.weight);
+                    intersection += a.min(b);
+                    union += a.max(b);
+                }
+                (union > 0.0 && intersection > 0.0).then(|| SimilarityScore {
+                    node_id: candidate,
+                    score: intersection / union,
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.node_id.cmp(&b.node_id)));
+        results.truncate(top_n);
+        Ok(results)
+    }
+
+    /// Groups nodes into communities using label propagation
+    ///
+    /// Every node starts in its own community, then repeatedly adopts the
+    /// community with the highest total edge weight among its neighbors
+    /// (over the consolidated, undirected adjacency) until nothing changes.
+    /// A node only moves when another community is strictly stronger than
+    /// its current one; remaining ties go to the lowest community number.
+    /// Nodes are visited in ID order, so results are stable between runs. Isolated nodes keep a
+    /// community of their own.
+    ///
+    /// # Returns
+    /// * `Ok(HashMap<Uuid, usize>)` - Community number for every node, numbered from 0
+    /// * `Err(anyhow::Error)` - If the operation fails
+    pub fn detect_communities(&self) -> Result<HashMap<Uuid, usize>> {
+        const MAX_ITERATIONS: usize = 100;
+
+        let nodes = lock(&self.nodes);
+        let relationships = lock(&self.relationships);
+        let adjacency = collapsed_adjacency(&nodes, &relationships, false, &[]);
+        let mut node_ids: Vec<Uuid> = nodes.keys().copied().collect();
+        node_ids.sort();
+
+        let mut labels: HashMap<Uuid, usize> = node_ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        for _ in 0..MAX_ITERATIONS {
+            let mut changed = false;
+            for id in &node_ids {
+                let Some(neighbors) = adjacency.get(id) else {
+                    continue;
+                };
+                let mut scores: HashMap<usize, f64> = HashMap::new();
+                for (neighbor, edge) in neighbors {
+                    *scores.entry(labels[neighbor]).or_insert(0.0) += edge.weight;
+                }
+                let current = labels[id];
+                let current_score = scores.get(&current).copied().unwrap_or(0.0);
+                let best = scores
+                    .into_iter()
+                    .max_by(|(a_label, a_score), (b_label, b_score)| {
+                        a_score.total_cmp(b_score).then_with(|| b_label.cmp(a_label))
+                    });
+                // Only move when another community is strictly stronger
+                if let Some((best, best_score)) = best {
+                    if best != current && best_score > current_score {
+                        labels.insert(*id, best);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // Renumber communities densely in node ID order
+        let mut renumbered: HashMap<usize, usize> = HashMap::new();
+        let mut communities = HashMap::with_capacity(node_ids.len());
+        for id in &node_ids {
+            let next = renumbered.len();
+            let community = *renumbered.entry(labels[id]).or_insert(next);
+            communities.insert(*id, community);
+        }
+        Ok(communities)
+    }
+}
+
+/// Scores how well a node's text matches a lowercased query
+///
+/// # Returns
+/// The best score among the matching fields, or `None` if nothing matches
+fn text_relevance(node: &Node, query_lower: &str) -> Option<f64> {
+    let label = node.label.to_lowercase();
+    let mut best: Option<f64> = None;
+    let mut consider = |score: f64| best = Some(best.map_or(score, |b: f64| b.max(score)));
+
+    if label == query_lower {
+        consider(1.0);
+    } else if label.starts_with(query_lower) {
+        consider(0.8);
+    } else if label.contains(query_lower) {
+        consider(0.6);
+    }
+
+    for tag in &node.tags {
+        let tag = tag.to_lowercase();
+        if tag == query_lower {
+            consider(0.5);
+        } else if tag.contains(query_lower) {
+            consider(0.4);
+        }
+    }
+
+    if node.description.as_ref().is_some_and(|desc| desc.to_lowercase().contains(query_lower)) {
+        consider(0.3);
+    }
+
+    best
+}
+
+/// Lists every non-overlapping, case-insensitive match of a query in a node's text fields
+///
+/// # Returns
+/// Matches in label, tag, description order, with character offsets
+fn match_locations(node: &Node, query_lower: &str) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    let mut add_field = |field: String, text: &str| {
+        for (start, end) in char_match_offsets(text, query_lower) {
+            matches.push(SearchMatch { field: field.clone(), start, end });
+        }
+    };
+
+    add_field("label".to_string(), &node.label);
+    for (i, tag) in node.tags.iter().enumerate() {
+        add_field(format!("tags.{}", i), tag);
+    }
+    if let Some(description) = &node.description {
+        add_field("description".to_string(), description);
+    }
+
+    matches
+}
+
+/// Finds non-overlapping, case-insensitive matches of a lowercased query in `text`
+///
+/// Lowercasing can change the number of characters (e.g. `İ` becomes two),
+/// so matching runs on the lowercased characters while offsets are mapped
+/// back to character positions in the original text.
+///
+/// # Returns
+/// `(start, end)` character offsets into `text`, end exclusive
+fn char_match_offsets(text: &str, query_lower: &str) -> Vec<(usize, usize)> {
+    let query: Vec<char> = query_lower.chars().collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // Each lowercased character paired with the index of the original character it came from
+    let lowered: Vec<(char, usize)> = text
+        .chars()
+        .enumerate()
+        .flat_map(|(i, c)| c.to_lowercase().map(move |lc| (lc, i)))
+        .collect();
+
+    let mut offsets = Vec::new();
+    let mut i = 0;
+    while i + query.len() <= lowered.len() {
+        if lowered[i..i + query.len()].iter().map(|(c, _)| *c).eq(query.iter().copied()) {
+            let start = lowered[i].1;
+            let end = lowered[i + query.len() - 1].1 + 1;
+            offsets.push((start, end));
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+    offsets
+}
+
+/// Resolves a dot-separated path inside a metadata object
+///
+/// A leading `metadata.` segment is skipped. Numeric segments index into arrays.
+fn metadata_lookup<'a>(metadata: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    let path = path.strip_prefix("metadata.").unwrap_or(path);
+    path.split('.').try_fold(metadata, |current, segment| match current {
+        serde_json::Value::Object(map) => map.get(segment),
+        serde_json::Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+/// Checks whether the value at `path` satisfies `comparator` against `expected`
+///
+/// Ordering comparisons work on numbers, and on strings lexically (which
+/// suits ISO 8601 dates). Missing keys never match.
+fn metadata_matches(
+    metadata: &serde_json::Value,
+    path: &str,
+    expected: &serde_json::Value,
+    comparator: MetadataComparator,
+) -> bool {
+    let Some(actual) = metadata_lookup(metadata, path) else {
+        return false;
+    };
+
+    let ordering = match (actual, expected) {
+        (serde_json::Value::Number(a), serde_json::Value::Number(b)) => {
+            a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b))
+        }
+        (serde_json::Value::String(a), serde_json::Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+
+    match comparator {
+        MetadataComparator::Eq => ordering.map_or(actual == expected, |o| o.is_eq()),
+        MetadataComparator::Ne => !ordering.map_or(actual == expected, |o| o.is_eq()),
+        MetadataComparator::Gt => ordering.is_some_and(|o| o.is_gt()),
+        MetadataComparator::Gte => ordering.is_some_and(|o| o.is_ge()),
+        MetadataComparator::Lt => ordering.is_some_and(|o| o.is_lt()),
+        MetadataComparator::Lte => ordering.is_some_and(|o| o.is_le()),
+        MetadataComparator::Contains => match (actual, expected) {
+            (serde_json::Value::String(a), serde_json::Value::String(b)) => a.contains(b.as_str()),
+            (serde_json::Value::Array(items), _) => items.contains(expected),
+            _ => false,
+        },
+    }
+}
+
+/// Lists the top-level fields that differ between two versions of an entity
+///
+/// Bookkeeping fields (`updated_at` and the cached `degree`) are ignored.
+/// Each change carries the field's old and new JSON value, sorted by field name.
+fn changed_fields<T: Serialize>(before: &T, after: &T) -> Vec<FieldChange> {
+    let (Ok(serde_json::Value::Object(mut before)), Ok(serde_json::Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+
+    let mut changed: Vec<FieldChange> = Vec::new();
+    for (key, new) in after {
+        if matches!(key.as_str(), "updated_at" | "degree") || before.get(key.as_str()) == Some(&new) {
+            continue;
+        }
+        let old = before.remove(&key).unwrap_or(serde_json::Value::Null);
+        changed.push(FieldChange { field: key, old, new });
+    }
+    changed.sort_by(|a, b| a.field.cmp(&b.field));
+    changed
+}
+
+/// Acquires a mutex, recovering the guard if a previous holder panicked
+///
+/// Every mutation leaves the collections structurally valid, so continuing
+/// with the inner data is preferable to failing every later operation for
+/// the lifetime of the application.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Groups the endpoints of `SameAs` relationships into connected clusters
+///
+/// # Returns
+/// Clusters of two or more node IDs, each sorted, in sorted order
+fn same_as_clusters(same_as: &[&Relationship]) -> Vec<Vec<Uuid>> {
+    // Union-find over SameAs endpoints, by size and with path compression
+    let mut parent: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut size: HashMap<Uuid, usize> = HashMap::new();
+    fn find(parent: &mut HashMap<Uuid, Uuid>, id: Uuid) -> Uuid {
+        let mut root = *parent.entry(id).or_insert(id);
+        while parent[&root] != root {
+            root = parent[&root];
+        }
+        let mut current = id;
+        while current != root {
+            current = std::mem::replace(parent.get_mut(&current).expect("visited on the way to the root"), root);
+        }
+        root
+    }
+    for rel in same_as {
+        let a = find(&mut parent, rel.source_id);
+        let b = find(&mut parent, rel.target_id);
+        if a == b {
+            continue;
+        }
+        let (small, large) = if size.get(&a).unwrap_or(&1) < size.get(&b).unwrap_or(&1) { (a, b) } else { (b, a) };
+        parent.insert(small, large);
+        let merged = size.get(&small).unwrap_or(&1) + size.get(&large).unwrap_or(&1);
+        size.insert(large, merged);
+    }
+
+    let mut groups: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let members: Vec<Uuid> = parent.keys().copied().collect();
+    for id in members {
+        let root = find(&mut parent, id);
+        groups.entry(root).or_default().push(id);
+    }
+
+    let mut clusters: Vec<Vec<Uuid>> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            members.sort();
+            members
+        })
+        .collect();
+    clusters.sort();
+    clusters
+}
+
+/// Builds an adjacency map with parallel relationships consolidated
+///
+/// Consolidation rule: all relationships between the same ordered pair
+/// (or unordered pair when `directed` is false) become one edge whose
+/// weight is the sum of their weights and whose confidence is the
+/// maximum of their confidences. Self-loops and relationships with a
+/// missing endpoint are ignored.
+///
+/// Callers pass the node and relationship collections from guards they
+/// keep holding while using the result, so every neighbor in the map is
+/// guaranteed to be among `nodes`.
+///
+/// # Arguments
+/// * `nodes` - All nodes, keyed by ID
+/// * `relationships` - All relationships
+/// * `directed` - When false, every edge is recorded in both directions;
+///   symmetric relationship types are recorded both ways regardless
+/// * `allowed_types` - Relationship types to include; empty includes every type
+///
+/// # Returns
+/// Map from node ID to its neighbors and the consolidated edge to each
+fn collapsed_adjacency(
+    nodes: &HashMap<Uuid, Node>,
+    relationships: &[Relationship],
+    directed: bool,
+    allowed_types: &[RelationType],
+) -> HashMap<Uuid, HashMap<Uuid, CollapsedEdge>> {
+    let mut adjacency: HashMap<Uuid, HashMap<Uuid, CollapsedEdge>> = HashMap::new();
+    let mut add = |from: Uuid, to: Uuid, rel: &Relationship| {
+        let edge = adjacency.entry(from).or_default().entry(to).or_insert(CollapsedEdge {
+            weight: 0.0,
+            confidence: 0.0,
+            count: 0,
+            updated_at: rel.updated_at,
+        });
+        edge.weight += rel.weight as f64;
+        edge.confidence = edge.confidence.max(rel.confidence);
+        edge.count += 1;
+        edge.updated_at = edge.updated_at.max(rel.updated_at);
+    };
+
+    for rel in relationships {
+        if rel.source_id == rel.target_id
+            || (!allowed_types.is_empty() && !allowed_types.contains(&rel.relation_type))
+            || !nodes.contains_key(&rel.source_id)
+            || !nodes.contains_key(&rel.target_id)
+        {
+            continue;
+        }
+        add(rel.source_id, rel.target_id, rel);
+        if !directed || rel.relation_type.is_symmetric() {
+            add(rel.target_id, rel.source_id, rel);
+        }
+    }
+
+    adjacency
+}
+
+/// Adds a relationship's contribution to its endpoints' cached degrees
+fn increment_degrees(nodes: &mut HashMap<Uuid, Node>, relationship: &Relationship) {
+    for id in [relationship.source_id, relationship.target_id] {
+        if let Some(node) = nodes.get_mut(&id) {
+            node.degree += 1;
+        }
+    }
+}
+
+/// Removes a relationship's contribution from its endpoints' cached degrees
+fn decrement_degrees(nodes: &mut HashMap<Uuid, Node>, relationship: &Relationship) {
+    for id in [relationship.source_id, relationship.target_id] {
+        if let Some(node) = nodes.get_mut(&id) {
+            node.degree = node.degree.saturating_sub(1);
+        }
+    }
+}
+
+/// Folds a duplicate relationship's sources into the one it merges into
+///
+/// `source` is combined under `policy`, while `sources` collects every
+/// distinct reference from both, whatever the policy.
+fn merge_relationship_sources(target: &mut Relationship, duplicate: &Relationship, policy: SourceMerge) {
+    target.absorb_sources(duplicate);
+    let source = policy.combine(target.source.take(), duplicate.source.clone());
+    target.set_source(source);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_node(db: &Database, node_type: NodeType, label: &str) -> Uuid {
+        db.create_node(Node::new(node_type, label.to_string())).unwrap()
+    }
+
+    fn add_scored_node(db: &Database, node_type: NodeType, label: &str, confidence: f32) -> Uuid {
+        let mut node = Node::new(node_type, label.to_string());
+        node.confidence = confidence;
+        db.create_node(node).unwrap()
+    }
+
+    fn link(db: &Database, source: Uuid, target: Uuid, relation_type: RelationType) -> Uuid {
+        db.create_relationship(Relationship::new(source, target, relation_type)).unwrap()
+    }
+
+    #[test]
+    fn directed_degree_centrality_separates_in_and_out_degree() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Organization, "A");
+        let b = add_node(&db, NodeType::Organization, "B");
+        link(&db, a, b, RelationType::Owns);
+
+        let scores = db.compute_degree_centrality(true, None).unwrap();
+        let score = |id: Uuid| scores.iter().find(|score| score.node_id == id).unwrap();
+        assert_eq!((score(a).out_degree, score(a).in_degree), (Some(1), Some(0)));
+        assert_eq!((score(b).out_degree, score(b).in_degree), (Some(0), Some(1)));
+
+        let undirected = db.compute_degree_centrality(false, None).unwrap();
+        assert!(undirected.iter().all(|score| score.degree == 1 && score.in_degree.is_none()));
+
+        let alias = add_node(&db, NodeType::Organization, "A (alias)");
+        link(&db, a, alias, RelationType::SameAs);
+        let scores = db.compute_degree_centrality(true, None).unwrap();
+        let score = |id: Uuid| scores.iter().find(|score| score.node_id == id).unwrap();
+        assert_eq!((score(a).out_degree, score(a).in_degree, score(a).degree), (Some(2), Some(0), 2));
+        assert_eq!((score(alias).out_degree, score(alias).in_degree, score(alias).degree), (Some(0), Some(1), 1));
+        assert_eq!(score(alias).strength, 1.0);
+    }
+
+    #[test]
+    fn pinned_orphan_survives_orphan_cleanup() {
+        let db = Database::new();
+        let pinned = add_node(&db, NodeType::Person, "Pinned");
+        let orphan = add_node(&db, NodeType::Person, "Orphan");
+        assert!(db.set_node_pinned(pinned, true).unwrap());
+
+        let report = db.delete_orphan_nodes().unwrap();
+        assert_eq!(report.deleted, vec![orphan]);
+        assert_eq!(report.skipped_pinned, 1);
+        assert!(db.get_node(pinned).unwrap().is_some());
+    }
+
+    #[test]
+    fn deleting_a_relationship_decrements_both_degrees() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let c = add_node(&db, NodeType::Person, "C");
+        let ab = link(&db, a, b, RelationType::ConnectedTo);
+        link(&db, b, c, RelationType::ConnectedTo);
+        let degree = |id: Uuid| db.get_node(id).unwrap().unwrap().degree;
+        assert_eq!((degree(a), degree(b), degree(c)), (1, 2, 1));
+
+        assert!(db.delete_relationship(ab).unwrap());
+        assert_eq!((degree(a), degree(b), degree(c)), (0, 1, 1));
+    }
+
+    #[test]
+    fn emails_differing_in_case_are_identifier_duplicates() {
+        let db = Database::new();
+        let upper = add_node(&db, NodeType::Email, "User@X.COM");
+        let lower = add_node(&db, NodeType::Email, "user@x.com");
+        add_node(&db, NodeType::Email, "other@x.com");
+        add_node(&db, NodeType::Domain, "x.com");
+
+        let groups = db.find_identifier_duplicates().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].normalized, "user@x.com");
+        let mut expected = vec![upper, lower];
+        expected.sort();
+        assert_eq!(groups[0].node_ids, expected);
+    }
+
+    #[test]
+    fn poisoned_locks_are_recovered() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+
+        let poisoner = db.clone();
+        let result = std::thread::spawn(move || {
+            let _nodes = poisoner.nodes.lock().unwrap();
+            let _relationships = poisoner.relationships.lock().unwrap();
+            panic!("simulated failure while holding the locks");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(db.nodes.is_poisoned() && db.relationships.is_poisoned());
+
+        let b = add_node(&db, NodeType::Person, "B");
+        link(&db, a, b, RelationType::ConnectedTo);
+        assert_eq!(db.get_all_nodes().unwrap().len(), 2);
+        assert_eq!(db.get_relationships().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn relationship_metadata_supports_dot_paths_and_comparators() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Organization, "A");
+        let b = add_node(&db, NodeType::Organization, "B");
+        let mut amounts = Vec::new();
+        for amount in [50, 500, 5000] {
+            let mut rel = Relationship::new(a, b, RelationType::TransactsWith);
+            rel.metadata = serde_json::json!({ "amount": amount, "payment": { "amount": amount } });
+            amounts.push((db.create_relationship(rel).unwrap(), amount));
+        }
+        let ids_over = |key: &str| {
+            let mut ids: Vec<Uuid> = db
+                .query_relationship_metadata(key, &serde_json::json!(100), MetadataComparator::Gt)
+                .unwrap()
+                .iter()
+                .map(|rel| rel.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        let mut expected: Vec<Uuid> = amounts.iter().filter(|(_, amount)| *amount > 100).map(|(id, _)| *id).collect();
+        expected.sort();
+        assert_eq!(ids_over("metadata.amount"), expected);
+        assert_eq!(ids_over("payment.amount"), expected);
+        let equal = db.query_relationship_metadata("amount", &serde_json::json!(50), MetadataComparator::Eq).unwrap();
+        assert_eq!(equal.len(), 1);
+    }
+
+    #[test]
+    fn avoided_intermediate_type_blocks_the_path() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let hub = add_node(&db, NodeType::Organization, "Hub");
+        let b = add_node(&db, NodeType::Person, "B");
+        link(&db, a, hub, RelationType::MemberOf);
+        link(&db, b, hub, RelationType::MemberOf);
+
+        let open = PathConstraints::default();
+        assert_eq!(db.find_shortest_path(a, b, &open).unwrap(), Some(vec![a, hub, b]));
+
+        let avoiding = PathConstraints { avoid_node_types: vec![NodeType::Organization], ..Default::default() };
+        assert_eq!(db.find_shortest_path(a, b, &avoiding).unwrap(), None);
+        // Endpoints of an avoided type are still allowed
+        assert_eq!(db.find_shortest_path(a, hub, &avoiding).unwrap(), Some(vec![a, hub]));
+    }
+
+    #[test]
+    fn provenance_carries_creation_time_and_source() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let rel = Relationship::new(a, b, RelationType::ConnectedTo).with_source("Bank records".to_string());
+        let created_at = rel.created_at;
+        let id = db.create_relationship(rel).unwrap();
+
+        let provenance = db.get_relationship_provenance(id).unwrap().unwrap();
+        assert_eq!(provenance.created_at, created_at);
+        assert_eq!(provenance.sources, vec!["Bank records".to_string()]);
+        assert_eq!(provenance.audit_entries.len(), 1);
+        assert!(db.get_relationship_provenance(Uuid::new_v4()).unwrap().is_none());
+    }
+
+    #[test]
+    fn confidence_weighting_orders_equal_text_matches() {
+        let db = Database::new();
+        let speculative = add_scored_node(&db, NodeType::Person, "John Smith", 0.2);
+        let verified = add_scored_node(&db, NodeType::Person, "John Smith", 1.0);
+
+        let results = db.search_nodes_ranked("john smith", true).unwrap();
+        let order: Vec<Uuid> = results.iter().map(|result| result.node.id).collect();
+        assert_eq!(order, vec![verified, speculative]);
+        assert!(results[0].score > results[1].score);
+
+        let unweighted = db.search_nodes_ranked("john smith", false).unwrap();
+        assert_eq!(unweighted[0].score, unweighted[1].score);
+    }
+
+    #[test]
+    fn same_as_chain_forms_one_cluster() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let c = add_node(&db, NodeType::Person, "C");
+        add_node(&db, NodeType::Person, "Unrelated");
+        link(&db, a, b, RelationType::SameAs);
+        link(&db, b, c, RelationType::SameAs);
+
+        let inference = db.infer_same_as_transitivity(true).unwrap();
+        let mut expected = vec![a, b, c];
+        expected.sort();
+        assert_eq!(inference.clusters, vec![expected]);
+        assert_eq!(inference.created.len(), 1);
+        assert!(db.infer_same_as_transitivity(true).unwrap().created.is_empty());
+    }
+
+    #[test]
+    fn long_same_as_chain_clusters_without_deep_recursion() {
+        let ids: Vec<Uuid> = (0..200_000).map(|_| Uuid::new_v4()).collect();
+        let chain: Vec<Relationship> = ids
+            .windows(2)
+            .map(|pair| Relationship::new(pair[0], pair[1], RelationType::SameAs))
+            .collect();
+        let same_as: Vec<&Relationship> = chain.iter().collect();
+
+        let clusters = same_as_clusters(&same_as);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].len(), ids.len());
+    }
+
+    #[test]
+    fn parallel_edges_add_strength_but_count_one_neighbor() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        link(&db, a, b, RelationType::ConnectedTo);
+        link(&db, a, b, RelationType::TransactsWith);
+
+        let scores = db.compute_degree_centrality(false, None).unwrap();
+        let score = scores.iter().find(|score| score.node_id == a).unwrap();
+        assert_eq!(score.degree, 1);
+        assert_eq!(score.strength, 2.0);
+    }
+
+    #[test]
+    fn relationships_between_respects_direction() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let c = add_node(&db, NodeType::Person, "C");
+        let forward = link(&db, a, b, RelationType::Owns);
+        link(&db, b, a, RelationType::Controls);
+        link(&db, a, c, RelationType::Owns);
+
+        assert_eq!(db.get_relationships_between(a, b, false).unwrap().len(), 2);
+        let directed = db.get_relationships_between(a, b, true).unwrap();
+        assert_eq!(directed.iter().map(|rel| rel.id).collect::<Vec<_>>(), vec![forward]);
+    }
+
+    #[test]
+    fn snapshot_as_of_drops_later_nodes_and_their_edges() {
+        let db = Database::new();
+        let then = Utc::now() - chrono::Duration::days(2);
+        let early = |label: &str| {
+            let mut node = Node::new(NodeType::Person, label.to_string());
+            node.created_at = then;
+            db.create_node(node).unwrap()
+        };
+        let a = early("A");
+        let b = early("B");
+        let late = add_node(&db, NodeType::Person, "Late");
+        for target in [b, late] {
+            let mut rel = Relationship::new(a, target, RelationType::ConnectedTo);
+            rel.created_at = then;
+            db.create_relationship(rel).unwrap();
+        }
+
+        let (nodes, relationships) = db.snapshot_as_of(then + chrono::Duration::days(1)).unwrap();
+        let mut ids: Vec<Uuid> = nodes.iter().map(|node| node.id).collect();
+        ids.sort();
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert_eq!(relationships.len(), 1);
+        assert_eq!(relationships[0].target_id, b);
+    }
+
+    #[test]
+    fn description_templates_differ_by_type_and_can_be_overridden() {
+        let mut settings = Settings::default();
+        let person = settings.description_template(&NodeType::Person);
+        assert_ne!(person, settings.description_template(&NodeType::Domain));
+        assert!(settings.description_template(&NodeType::CryptoWallet).contains("Chain: __"));
+
+        settings.description_templates.insert(NodeType::Person, "Alias: __".to_string());
+        assert_eq!(settings.description_template(&NodeType::Person), "Alias: __");
+    }
+
+    #[test]
+    fn node_source_uncorroborated_by_relationships_is_flagged() {
+        let db = Database::new();
+        let mut claimed = Node::new(NodeType::Person, "Claimed".to_string());
+        claimed.source = Some("X".to_string());
+        let claimed = db.create_node(claimed).unwrap();
+        let mut corroborated = Node::new(NodeType::Person, "Corroborated".to_string());
+        corroborated.source = Some("Y".to_string());
+        let corroborated = db.create_node(corroborated).unwrap();
+        let other = add_node(&db, NodeType::Person, "Other");
+        for (source, target) in [(claimed, other), (corroborated, other)] {
+            let rel = Relationship::new(source, target, RelationType::ConnectedTo).with_source("Y".to_string());
+            db.create_relationship(rel).unwrap();
+        }
+
+        let mismatches = db.cross_check_sources().unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].node_id, claimed);
+        assert_eq!(mismatches[0].relationship_sources, vec!["Y".to_string()]);
+    }
+
+    #[test]
+    fn cluster_confidence_averages_its_component() {
+        let db = Database::new();
+        let a = add_scored_node(&db, NodeType::Person, "A", 0.5);
+        let b = add_scored_node(&db, NodeType::Person, "B", 1.0);
+        let c = add_scored_node(&db, NodeType::Person, "C", 0.25);
+        add_scored_node(&db, NodeType::Person, "Elsewhere", 0.0);
+        link(&db, a, b, RelationType::ConnectedTo);
+        let bc = Relationship::new(b, c, RelationType::ConnectedTo).with_confidence(0.5);
+        db.create_relationship(bc).unwrap();
+
+        let cluster = db.get_cluster_confidence(c).unwrap().unwrap();
+        assert_eq!(cluster.node_ids.len(), 3);
+        assert!((cluster.mean_node_confidence - 1.75 / 3.0).abs() < 1e-9);
+        assert_eq!((cluster.min_node_confidence, cluster.max_node_confidence), (0.25, 1.0));
+        assert_eq!(cluster.relationship_count, 2);
+        let expected = (DEFAULT_RELATIONSHIP_CONFIDENCE as f64 + 0.5) / 2.0;
+        assert!((cluster.mean_relationship_confidence.unwrap() - expected).abs() < 1e-6);
+        assert!(db.get_cluster_confidence(Uuid::new_v4()).unwrap().is_none());
+    }
+
+    #[test]
+    fn allowed_relation_types_restrict_the_path() {
+        let db = Database::new();
+        let payer = add_node(&db, NodeType::Person, "Payer");
+        let mule = add_node(&db, NodeType::Person, "Mule");
+        let friend = add_node(&db, NodeType::Person, "Friend");
+        let payee = add_node(&db, NodeType::Person, "Payee");
+        link(&db, payer, friend, RelationType::ConnectedTo);
+        link(&db, friend, payee, RelationType::ConnectedTo);
+        link(&db, payer, mule, RelationType::TransactsWith);
+        link(&db, mule, payee, RelationType::TransactsWith);
+        let social = add_node(&db, NodeType::Person, "Acquaintance");
+        link(&db, payer, social, RelationType::ConnectedTo);
+
+        let financial = PathConstraints { allowed_relation_types: vec![RelationType::TransactsWith], ..Default::default() };
+        assert_eq!(db.find_shortest_path(payer, payee, &financial).unwrap(), Some(vec![payer, mule, payee]));
+        assert_eq!(db.find_shortest_path(payer, social, &financial).unwrap(), None);
+        assert!(db.find_shortest_path(payer, social, &PathConstraints::default()).unwrap().is_some());
+    }
+
+    #[test]
+    fn deleting_by_type_removes_only_that_type() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        link(&db, a, b, RelationType::RelatedTo);
+        link(&db, b, a, RelationType::RelatedTo);
+        let kept = link(&db, a, b, RelationType::Owns);
+        let confident = Relationship::new(a, b, RelationType::ConnectedTo).with_confidence(0.9);
+        let confident = db.create_relationship(confident).unwrap();
+        let doubtful = Relationship::new(b, a, RelationType::ConnectedTo).with_confidence(0.1);
+        db.create_relationship(doubtful).unwrap();
+
+        assert_eq!(db.delete_relationships_by_type(&RelationType::RelatedTo, None).unwrap(), 2);
+        assert_eq!(db.delete_relationships_by_type(&RelationType::ConnectedTo, Some(0.5)).unwrap(), 1);
+        let mut remaining: Vec<Uuid> = db.get_relationships().unwrap().iter().map(|rel| rel.id).collect();
+        remaining.sort();
+        let mut expected = vec![kept, confident];
+        expected.sort();
+        assert_eq!(remaining, expected);
+        assert_eq!(db.get_node(a).unwrap().unwrap().degree, 2);
+    }
+
+    #[test]
+    fn shared_neighbors_raise_structural_similarity() {
+        let db = Database::new();
+        let wallet = add_node(&db, NodeType::CryptoWallet, "Wallet");
+        let twin = add_node(&db, NodeType::CryptoWallet, "Twin");
+        let distant = add_node(&db, NodeType::CryptoWallet, "Distant");
+        let users: Vec<Uuid> = (0..4).map(|i| add_node(&db, NodeType::Person, &format!("User {}", i))).collect();
+        for user in &users {
+            link(&db, *user, wallet, RelationType::Owns);
+        }
+        for user in &users[..3] {
+            link(&db, *user, twin, RelationType::Owns);
+        }
+        link(&db, users[0], distant, RelationType::Owns);
+        link(&db, add_node(&db, NodeType::Person, "Stranger"), distant, RelationType::Owns);
+
+        let scores = db.compute_structural_similarity(wallet, 2).unwrap();
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[0].node_id, twin);
+        assert_eq!(scores[1].node_id, distant);
+        assert!((scores[0].score - 0.75).abs() < 1e-9);
+        assert!((scores[1].score - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn deletion_preview_lists_every_incident_relationship() {
+        let db = Database::new();
+        let hub = add_node(&db, NodeType::Organization, "Hub");
+        let mut expected: Vec<Uuid> = (0..3)
+            .map(|i| {
+                let other = add_node(&db, NodeType::Person, &format!("P{}", i));
+                if i == 0 { link(&db, other, hub, RelationType::MemberOf) } else { link(&db, hub, other, RelationType::Owns) }
+            })
+            .collect();
+        let (x, y) = (add_node(&db, NodeType::Person, "X"), add_node(&db, NodeType::Person, "Y"));
+        link(&db, x, y, RelationType::ConnectedTo);
+
+        let preview = db.preview_node_deletion(hub).unwrap().unwrap();
+        assert_eq!(preview.node.id, hub);
+        let mut ids: Vec<Uuid> = preview.relationships.iter().map(|rel| rel.id).collect();
+        ids.sort();
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert_eq!(db.get_relationships().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn ranked_search_reports_character_offsets() {
+        let db = Database::new();
+        add_node(&db, NodeType::Organization, "Café Zürich Holdings");
+        let results = db.search_nodes_ranked("zürich", false).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].matches,
+            vec![SearchMatch { field: "label".to_string(), start: 5, end: 11 }]
+        );
+    }
+
+    #[test]
+    fn pair_interaction_summary_reports_first_and_last_contact() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let earlier = DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let later = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        for (source, target, created_at) in [(b, a, later), (a, b, earlier)] {
+            let mut rel = Relationship::new(source, target, RelationType::TransactsWith);
+            rel.created_at = created_at;
+            db.create_relationship(rel).unwrap();
+        }
+
+        let summary = db.get_pair_interaction_summary(a, b).unwrap();
+        assert_eq!(summary.count, 2);
+        assert_eq!(summary.first, Some(earlier));
+        assert_eq!(summary.last, Some(later));
+    }
+
+    #[test]
+    fn sanitize_repairs_nan_confidence_and_infinite_weight() {
+        let db = Database::new();
+        let stale = DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let mut node = Node::new(NodeType::Person, "Corrupt".to_string());
+        node.confidence = f32::NAN;
+        node.updated_at = stale;
+        let healthy = Node::new(NodeType::Person, "Healthy".to_string());
+        let mut rel = Relationship::new(node.id, healthy.id, RelationType::ConnectedTo);
+        rel.weight = f32::INFINITY;
+        rel.updated_at = stale;
+        let (node_id, rel_id) = (node.id, rel.id);
+        db.restore_project(vec![node, healthy], vec![rel], Vec::new()).unwrap();
+
+        assert_eq!(db.sanitize_numeric_fields().unwrap(), 2);
+        let node = db.get_node(node_id).unwrap().unwrap();
+        assert_eq!(node.confidence, DEFAULT_NODE_CONFIDENCE);
+        assert!(node.updated_at > stale);
+        let rel = db.get_relationship(rel_id).unwrap().unwrap();
+        assert_eq!(rel.weight, DEFAULT_RELATIONSHIP_WEIGHT);
+        assert!(rel.updated_at > stale);
+        assert_eq!(db.sanitize_numeric_fields().unwrap(), 0);
+    }
+
+    #[test]
+    fn confidence_by_relation_type_averages_each_type() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Organization, "B");
+        let c = add_node(&db, NodeType::Organization, "C");
+        for (target, confidence) in [(b, 0.4), (c, 0.8)] {
+            db.create_relationship(Relationship::new(a, target, RelationType::Owns).with_confidence(confidence))
+                .unwrap();
+        }
+        link(&db, b, c, RelationType::ConnectedTo);
+
+        let stats = db.get_confidence_by_relation_type().unwrap();
+        let owns = stats.iter().find(|entry| entry.relation_type == RelationType::Owns).unwrap();
+        assert_eq!(owns.count, 2);
+        assert!((owns.mean - 0.6).abs() < 1e-6);
+        assert_eq!((owns.min, owns.max), (0.4, 0.8));
+        assert_eq!(stats.len(), 2);
+    }
+
+    #[test]
+    fn weight_histogram_buckets_weights_and_handles_equal_weights() {
+        let db = Database::new();
+        let hub = add_node(&db, NodeType::Person, "Hub");
+        for weight in [1.0, 1.0, 2.0, 3.0, 5.0] {
+            let leaf = add_node(&db, NodeType::Person, "Leaf");
+            db.create_relationship(Relationship::new(hub, leaf, RelationType::ConnectedTo).with_weight(weight))
+                .unwrap();
+        }
+
+        let histogram = db.get_weight_histogram(4).unwrap();
+        assert_eq!(histogram.iter().map(|bucket| bucket.count).collect::<Vec<_>>(), vec![2, 1, 1, 1]);
+        assert_eq!(histogram.iter().map(|bucket| bucket.count).sum::<usize>(), 5);
+        assert_eq!((histogram[0].min, histogram[3].max), (1.0, 5.0));
+
+        let flat = Database::new();
+        let a = add_node(&flat, NodeType::Person, "A");
+        let b = add_node(&flat, NodeType::Person, "B");
+        link(&flat, a, b, RelationType::ConnectedTo);
+        link(&flat, b, a, RelationType::ConnectedTo);
+        let histogram = flat.get_weight_histogram(4).unwrap();
+        assert_eq!(histogram.len(), 1);
+        assert_eq!(histogram[0].count, 2);
+    }
+
+    #[test]
+    fn stale_nodes_include_old_and_never_enriched_nodes() {
+        let db = Database::new();
+        let now = Utc::now();
+        let old = add_node(&db, NodeType::Domain, "old.example");
+        let fresh = add_node(&db, NodeType::IpAddress, "10.0.0.1");
+        let never = add_node(&db, NodeType::Domain, "never.example");
+        add_node(&db, NodeType::Person, "Not enrichable");
+        assert!(db.mark_node_enriched(old, now - chrono::Duration::days(40)).unwrap());
+        assert!(db.mark_node_enriched(fresh, now).unwrap());
+
+        let stale = db.get_stale_nodes(now - chrono::Duration::days(30)).unwrap();
+        assert_eq!(stale.iter().map(|node| node.id).collect::<Vec<_>>(), vec![never, old]);
+    }
+
+    #[test]
+    fn compaction_drops_dangling_relationships_and_keeps_queries_correct() {
+        let db = Database::new();
+        let a = Node::new(NodeType::Person, "A".to_string());
+        let b = Node::new(NodeType::Person, "B".to_string());
+        let kept = Relationship::new(a.id, b.id, RelationType::ConnectedTo);
+        let dangling = Relationship::new(a.id, Uuid::new_v4(), RelationType::ConnectedTo);
+        let (a_id, b_id, kept_id) = (a.id, b.id, kept.id);
+        db.restore_project(vec![a, b], vec![kept, dangling], Vec::new()).unwrap();
+        let extra: Vec<Uuid> = (0..50).map(|_| link(&db, a_id, b_id, RelationType::RelatedTo)).collect();
+        for id in extra {
+            assert!(db.delete_relationship(id).unwrap());
+        }
+
+        let report = db.compact().unwrap();
+        assert_eq!(report.dangling_relationships_removed, 1);
+        assert!(report.relationship_capacity_freed > 0);
+
+        let remaining = db.get_node_relationships(a_id).unwrap();
+        assert_eq!(remaining.iter().map(|rel| rel.id).collect::<Vec<_>>(), vec![kept_id]);
+        assert_eq!(db.get_node(a_id).unwrap().unwrap().degree, 1);
+        assert_eq!(db.compact().unwrap().dangling_relationships_removed, 0);
+    }
+
+    #[test]
+    fn node_set_comparison_finds_the_shared_neighbor() {
+        let db = Database::new();
+        let persona_a = add_node(&db, NodeType::SocialAccount, "@persona_a");
+        let persona_b = add_node(&db, NodeType::SocialAccount, "@persona_b");
+        let shared = add_node(&db, NodeType::Phone, "+1 555 0100");
+        let only_a = add_node(&db, NodeType::Email, "a@example.com");
+        let only_b = add_node(&db, NodeType::Email, "b@example.com");
+        link(&db, persona_a, shared, RelationType::ConnectedTo);
+        link(&db, shared, persona_b, RelationType::ConnectedTo);
+        link(&db, persona_a, only_a, RelationType::ConnectedTo);
+        link(&db, persona_b, only_b, RelationType::ConnectedTo);
+
+        let comparison = db.compare_node_sets(&[persona_a], &[persona_b]).unwrap();
+        assert_eq!(comparison.common_neighbors.iter().map(|node| node.id).collect::<Vec<_>>(), vec![shared]);
+        assert_eq!((comparison.neighbors_a, comparison.neighbors_b, comparison.overlap), (2, 2, 1));
+    }
+
+    #[test]
+    fn quality_report_counts_low_confidence_and_unsourced_nodes() {
+        let db = Database::new();
+        for (label, confidence, source) in [
+            ("Verified", 0.9, Some("registry")),
+            ("Rumour", 0.2, Some("forum post")),
+            ("Unsourced", 0.8, None),
+            ("Blank source", 0.7, Some("  ")),
+        ] {
+            let mut node = Node::new(NodeType::Person, label.to_string());
+            node.confidence = confidence;
+            node.source = source.map(str::to_string);
+            db.create_node(node).unwrap();
+        }
+
+        let report = db.get_quality_report(0.5).unwrap();
+        assert_eq!(report.total_nodes, 4);
+        assert_eq!(report.low_confidence_nodes, 1);
+        assert_eq!(report.low_confidence_node_percent, 25.0);
+        assert_eq!(report.unsourced_nodes, 2);
+        assert_eq!((report.total_relationships, report.low_confidence_relationship_percent), (0, 0.0));
+    }
+
+    #[test]
+    fn detailed_relationships_hydrate_endpoints_and_mark_missing_ones() {
+        let db = Database::new();
+        let alice = Node::new(NodeType::Person, "Alice".to_string());
+        let acme = Node::new(NodeType::Organization, "Acme".to_string());
+        let normal = Relationship::new(alice.id, acme.id, RelationType::MemberOf);
+        let dangling = Relationship::new(alice.id, Uuid::new_v4(), RelationType::ConnectedTo);
+        db.restore_project(vec![alice, acme], vec![normal, dangling], Vec::new()).unwrap();
+
+        let details = db.get_relationships_detailed().unwrap();
+        assert_eq!(details.len(), 2);
+        assert_eq!((details[0].source_label.as_str(), details[0].source_type.clone()), ("Alice", Some(NodeType::Person)));
+        assert_eq!((details[0].target_label.as_str(), details[0].target_type.clone()), ("Acme", Some(NodeType::Organization)));
+        assert_eq!(details[1].source_label, "Alice");
+        assert_eq!((details[1].target_label.as_str(), details[1].target_type.clone()), (MISSING_NODE_LABEL, None));
+    }
+
+    #[test]
+    fn same_as_is_traversable_backwards_in_directed_paths() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::SocialAccount, "@alias");
+        let b = add_node(&db, NodeType::Person, "Real name");
+        let c = add_node(&db, NodeType::Organization, "Employer");
+        link(&db, a, b, RelationType::SameAs);
+        link(&db, b, c, RelationType::MemberOf);
+        let directed = PathConstraints { directed: true, ..PathConstraints::default() };
+
+        assert_eq!(db.find_shortest_path(b, a, &directed).unwrap(), Some(vec![b, a]));
+        assert_eq!(db.find_shortest_path(a, c, &directed).unwrap(), Some(vec![a, b, c]));
+        assert_eq!(db.find_shortest_path(c, b, &directed).unwrap(), None);
+    }
+
+    #[test]
+    fn changes_since_includes_only_recent_updates_and_deletions() {
+        let db = Database::new();
+        let long_ago = Utc::now() - chrono::Duration::days(30);
+        let [unchanged, edited, removed] = ["Unchanged", "Edited", "Removed"].map(|label| {
+            let mut node = Node::new(NodeType::Person, label.to_string());
+            node.updated_at = long_ago;
+            node
+        });
+        let (unchanged_id, edited_id, removed_id) = (unchanged.id, edited.id, removed.id);
+        db.restore_project(vec![unchanged, edited.clone(), removed], Vec::new(), Vec::new()).unwrap();
+        let since = Utc::now() - chrono::Duration::hours(1);
+
+        let mut edited = edited;
+        edited.label = "Edited again".to_string();
+        edited.updated_at = Utc::now();
+        db.update_node(edited).unwrap();
+        assert!(db.delete_node(removed_id).unwrap());
+
+        let delta = db.changes_since(since).unwrap();
+        assert_eq!(delta.nodes.iter().map(|node| node.id).collect::<Vec<_>>(), vec![edited_id]);
+        assert_eq!(delta.deleted_nodes, vec![removed_id]);
+        assert!(!delta.nodes.iter().any(|node| node.id == unchanged_id));
+    }
+
+    #[test]
+    fn one_trim_round_removes_star_spokes_but_keeps_pinned_ones() {
+        let db = Database::new();
+        let center = add_node(&db, NodeType::Organization, "Center");
+        let spokes: Vec<Uuid> = (0..4)
+            .map(|i| {
+                let spoke = add_node(&db, NodeType::Person, &format!("Spoke {}", i));
+                link(&db, center, spoke, RelationType::ConnectedTo);
+                spoke
+            })
+            .collect();
+        assert!(db.set_node_pinned(spokes[3], true).unwrap());
+
+        let preview = db.trim_leaves(1, true).unwrap();
+        assert_eq!((preview.deleted.len(), preview.skipped_pinned), (3, 1));
+        assert_eq!(db.get_all_nodes().unwrap().len(), 5);
+
+        let report = db.trim_leaves(1, false).unwrap();
+        assert_eq!(report.skipped_pinned, 1);
+        let mut removed = report.deleted;
+        removed.sort();
+        let mut expected = spokes[..3].to_vec();
+        expected.sort();
+        assert_eq!(removed, expected);
+        assert!(db.get_node(center).unwrap().is_some());
+        assert!(db.get_node(spokes[3]).unwrap().is_some());
+    }
+
+    #[test]
+    fn subgraph_search_excludes_matches_beyond_the_depth() {
+        let db = Database::new();
+        let center = add_node(&db, NodeType::Person, "Center");
+        let near = add_node(&db, NodeType::CryptoWallet, "Wallet near");
+        let hop = add_node(&db, NodeType::Person, "Intermediary");
+        let far = add_node(&db, NodeType::CryptoWallet, "Wallet far");
+        add_node(&db, NodeType::CryptoWallet, "Wallet unconnected");
+        link(&db, near, center, RelationType::Owns);
+        link(&db, center, hop, RelationType::ConnectedTo);
+        link(&db, hop, far, RelationType::Owns);
+
+        let found = db.search_within_subgraph(center, 1, "wallet").unwrap().unwrap();
+        assert_eq!(found.iter().map(|node| node.id).collect::<Vec<_>>(), vec![near]);
+        assert_eq!(db.search_within_subgraph(center, 2, "wallet").unwrap().unwrap().len(), 2);
+        assert!(db.search_within_subgraph(Uuid::new_v4(), 1, "wallet").unwrap().is_none());
+    }
+
+    #[test]
+    fn bulk_confidence_updates_every_node_and_reports_missing_ids() {
+        let db = Database::new();
+        let stale = Utc::now() - chrono::Duration::days(1);
+        let ids: Vec<Uuid> = (0..3)
+            .map(|i| {
+                let mut node = Node::new(NodeType::Person, format!("Verified {}", i));
+                node.confidence = 0.3;
+                node.updated_at = stale;
+                db.create_node(node).unwrap()
+            })
+            .collect();
+        let untouched = add_scored_node(&db, NodeType::Person, "Untouched", 0.3);
+        let missing = Uuid::new_v4();
+
+        let mut request = ids.clone();
+        request.push(missing);
+        request.push(ids[0]);
+        let report = db.set_confidence_bulk(&request, 0.9).unwrap();
+
+        assert_eq!(report.updated, 3);
+        assert_eq!(report.missing, vec![missing]);
+        for id in ids {
+            let node = db.get_node(id).unwrap().unwrap();
+            assert_eq!(node.confidence, 0.9);
+            assert!(node.updated_at > stale);
+        }
+        assert_eq!(db.get_node(untouched).unwrap().unwrap().confidence, 0.3);
+    }
+
+    #[test]
+    fn auto_merge_merges_confident_same_as_pairs_only() {
+        let db = Database::new();
+        let kept = add_scored_node(&db, NodeType::Person, "J. Smith", 0.9);
+        let alias = add_scored_node(&db, NodeType::Person, "John Smith", 0.6);
+        let employer = add_node(&db, NodeType::Organization, "Acme");
+        db.create_relationship(Relationship::new(alias, kept, RelationType::SameAs).with_confidence(0.95)).unwrap();
+        link(&db, alias, employer, RelationType::MemberOf);
+        let doubtful_a = add_node(&db, NodeType::SocialAccount, "@jsmith");
+        let doubtful_b = add_node(&db, NodeType::SocialAccount, "@john_s");
+        db.create_relationship(Relationship::new(doubtful_a, doubtful_b, RelationType::SameAs).with_confidence(0.4))
+            .unwrap();
+
+        let report = db.auto_merge_same_as(0.8).unwrap();
+
+        assert_eq!(report.merges.len(), 1);
+        assert_eq!((report.merges[0].kept, report.merges[0].merged.clone()), (kept, vec![alias]));
+        assert!(db.get_node(alias).unwrap().is_none());
+        let moved = db.get_node_relationships(kept).unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!((moved[0].source_id, moved[0].target_id), (kept, employer));
+        let mut skipped = report.skipped.concat();
+        skipped.sort();
+        let mut expected = vec![doubtful_a, doubtful_b];
+        expected.sort();
+        assert_eq!(skipped, expected);
+        assert!(db.get_node(doubtful_a).unwrap().is_some() && db.get_node(doubtful_b).unwrap().is_some());
+    }
+
+    #[test]
+    fn pinned_nodes_are_kept_or_skipped_by_merges() {
+        let db = Database::new();
+        let strong = add_scored_node(&db, NodeType::Person, "J. Smith", 0.9);
+        let pinned = add_scored_node(&db, NodeType::Person, "John Smith", 0.5);
+        let also_pinned = add_scored_node(&db, NodeType::Person, "Johnny Smith", 0.5);
+        assert!(db.set_node_pinned(pinned, true).unwrap());
+        assert!(db.set_node_pinned(also_pinned, true).unwrap());
+
+        let manual = db.merge_nodes(strong, &[pinned]).unwrap().unwrap();
+        assert!(manual.merged.is_empty());
+        assert_eq!(manual.skipped_pinned, vec![pinned]);
+        assert!(db.get_node(pinned).unwrap().is_some());
+
+        db.create_relationship(Relationship::new(strong, pinned, RelationType::SameAs).with_confidence(0.9)).unwrap();
+        db.create_relationship(Relationship::new(also_pinned, strong, RelationType::SameAs).with_confidence(0.9)).unwrap();
+        let report = db.auto_merge_same_as(0.8).unwrap();
+
+        assert_eq!(report.merges.len(), 1);
+        let merge = &report.merges[0];
+        assert!(merge.kept == pinned || merge.kept == also_pinned);
+        assert_eq!(merge.merged, vec![strong]);
+        assert_eq!(merge.skipped_pinned.len(), 1);
+        assert!(db.get_node(pinned).unwrap().is_some() && db.get_node(also_pinned).unwrap().is_some());
+        assert!(db.get_node(strong).unwrap().is_none());
+    }
+
+    #[test]
+    fn duplicate_merges_follow_the_configured_policy() {
+        let merged = |confidence: ConfidenceAggregation, sources: SourceMerge| {
+            let db = Database::new();
+            db.update_settings(|settings| settings.merge_policy = MergePolicy { confidence, sources }).unwrap();
+            let a = add_node(&db, NodeType::Person, "A");
+            let b = add_node(&db, NodeType::CryptoWallet, "B");
+            let first = Relationship::new(a, b, RelationType::Owns).with_confidence(0.5).with_source("registry".to_string());
+            let second = Relationship::new(a, b, RelationType::Owns).with_confidence(0.5).with_source("leak".to_string());
+            let id = db.create_or_merge_relationship(first).unwrap();
+            assert_eq!(db.create_or_merge_relationship(second).unwrap(), id);
+            assert_eq!(db.get_relationships().unwrap().len(), 1);
+            db.get_relationship(id).unwrap().unwrap()
+        };
+
+        let noisy_or = merged(ConfidenceAggregation::NoisyOr, SourceMerge::Union);
+        assert_eq!(noisy_or.confidence, 0.75);
+        assert_eq!(noisy_or.source.as_deref(), Some("registry; leak"));
+        assert_eq!(merged(ConfidenceAggregation::Max, SourceMerge::Union).confidence, 0.5);
+        assert_eq!(merged(ConfidenceAggregation::Mean, SourceMerge::Union).confidence, 0.5);
+
+        let replaced = merged(ConfidenceAggregation::Max, SourceMerge::Replace);
+        assert_eq!(replaced.source.as_deref(), Some("leak"));
+        assert_eq!(replaced.sources, vec!["registry", "leak"]);
+
+        let db = Database::new();
+        let policy = MergePolicy { confidence: ConfidenceAggregation::NoisyOr, sources: SourceMerge::Union };
+        db.update_settings(|settings| settings.merge_policy = policy).unwrap();
+        let kept = add_node(&db, NodeType::Person, "A");
+        let duplicate = add_node(&db, NodeType::Person, "A (copy)");
+        let wallet = add_node(&db, NodeType::CryptoWallet, "B");
+        db.create_relationship(Relationship::new(kept, wallet, RelationType::Owns).with_confidence(0.5)).unwrap();
+        db.create_relationship(Relationship::new(duplicate, wallet, RelationType::Owns).with_confidence(0.5)).unwrap();
+        let merge = db.merge_nodes(kept, &[duplicate]).unwrap().unwrap();
+        assert_eq!(merge.relationships_deduplicated, 1);
+        assert_eq!(db.get_relationships().unwrap()[0].confidence, 0.75);
+    }
+
+    #[test]
+    fn relationship_summary_splits_types_by_direction() {
+        let db = Database::new();
+        let node = add_node(&db, NodeType::Person, "Subject");
+        let wallet = add_node(&db, NodeType::CryptoWallet, "Wallet");
+        let friend = add_node(&db, NodeType::Person, "Friend");
+        let colleague = add_node(&db, NodeType::Person, "Colleague");
+        link(&db, node, wallet, RelationType::Owns);
+        link(&db, friend, node, RelationType::ConnectedTo);
+        link(&db, colleague, node, RelationType::ConnectedTo);
+        link(&db, friend, colleague, RelationType::ConnectedTo);
+
+        let summary = db.get_node_relationship_summary(node).unwrap();
+        assert_eq!(summary.len(), 2);
+        assert_eq!((summary["Owns"].incoming, summary["Owns"].outgoing), (0, 1));
+        assert_eq!((summary["ConnectedTo"].incoming, summary["ConnectedTo"].outgoing), (2, 0));
+    }
+
+    #[test]
+    fn growth_timeline_carries_totals_through_empty_days() {
+        let db = Database::new();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 1, d).unwrap().and_hms_opt(12, 0, 0).unwrap().and_utc();
+        let mut ids = Vec::new();
+        for d in [1, 1, 3] {
+            let mut node = Node::new(NodeType::Person, format!("Created {}", d));
+            node.created_at = day(d);
+            ids.push(db.create_node(node).unwrap());
+        }
+        let mut rel = Relationship::new(ids[0], ids[2], RelationType::ConnectedTo);
+        rel.created_at = day(4);
+        db.create_relationship(rel).unwrap();
+
+        let timeline = db.get_growth_timeline(TimeBucket::Day).unwrap();
+        let points: Vec<(u32, usize, usize)> =
+            timeline.iter().map(|point| (point.bucket_start.day(), point.nodes, point.relationships)).collect();
+        assert_eq!(points, vec![(1, 2, 0), (2, 2, 0), (3, 3, 0), (4, 3, 1)]);
+        assert!(Database::new().get_growth_timeline(TimeBucket::Day).unwrap().is_empty());
+    }
+
+    #[test]
+    fn growth_timeline_refuses_too_many_buckets() {
+        let db = Database::new();
+        let mut ancient = Node::new(NodeType::Document, "Ancient".to_string());
+        ancient.created_at = NaiveDate::from_ymd_opt(1900, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        db.create_node(ancient).unwrap();
+        add_node(&db, NodeType::Document, "Recent");
+
+        let error = db.get_growth_timeline(TimeBucket::Day).unwrap_err().to_string();
+        assert!(error.contains("larger bucket size"));
+        assert!(db.get_growth_timeline(TimeBucket::Month).is_ok());
+    }
+
+    #[test]
+    fn hierarchy_conflicts_are_detected_and_auto_fix_keeps_the_stronger_edge() {
+        let db = Database::new();
+        let parent = add_node(&db, NodeType::Organization, "Holding");
+        let child = add_node(&db, NodeType::Organization, "Subsidiary");
+        let strong = db
+            .create_relationship(Relationship::new(parent, child, RelationType::ParentOf).with_confidence(0.9))
+            .unwrap();
+        let weak = db
+            .create_relationship(Relationship::new(child, parent, RelationType::ParentOf).with_confidence(0.3))
+            .unwrap();
+        let weak_reverse = db
+            .create_relationship(Relationship::new(parent, child, RelationType::ChildOf).with_confidence(0.2))
+            .unwrap();
+
+        let report = db.validate_hierarchy(false).unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert!(report.removed.is_empty());
+        assert_eq!(db.get_relationships().unwrap().len(), 3);
+
+        let mut report = db.validate_hierarchy(true).unwrap();
+        report.removed.sort();
+        let mut expected = vec![weak, weak_reverse];
+        expected.sort();
+        assert_eq!(report.removed, expected);
+        let remaining: Vec<Uuid> = db.get_relationships().unwrap().iter().map(|rel| rel.id).collect();
+        assert_eq!(remaining, vec![strong]);
+        assert_eq!(db.get_node(child).unwrap().unwrap().degree, 1);
+        assert!(db.validate_hierarchy(false).unwrap().conflicts.is_empty());
+    }
+
+    #[test]
+    fn edges_to_leaves_get_lower_visual_weights() {
+        let db = Database::new();
+        let hub_a = add_node(&db, NodeType::Organization, "Hub A");
+        let hub_b = add_node(&db, NodeType::Organization, "Hub B");
+        let core = link(&db, hub_a, hub_b, RelationType::ConnectedTo);
+        for hub in [hub_a, hub_b] {
+            for i in 0..2 {
+                let extra = add_node(&db, NodeType::Person, &format!("Member {}", i));
+                link(&db, hub, extra, RelationType::MemberOf);
+                link(&db, extra, if hub == hub_a { hub_b } else { hub_a }, RelationType::ConnectedTo);
+            }
+        }
+        let leaf = add_node(&db, NodeType::Person, "Leaf");
+        let spoke = link(&db, hub_a, leaf, RelationType::ConnectedTo);
+
+        let weights = db.compute_visual_edge_weights().unwrap();
+        assert!(weights[&spoke] < weights[&core]);
+        assert!(weights.values().all(|weight| *weight > 0.0 && *weight <= 1.0));
+        assert_eq!(db.get_relationship(spoke).unwrap().unwrap().weight, 1.0);
+    }
+
+    #[test]
+    fn node_sources_are_counted_by_frequency() {
+        let db = Database::new();
+        for (label, source) in [
+            ("A", Some("OSINT")),
+            ("B", Some("OSINT")),
+            ("C", Some("Court records")),
+            ("D", Some("   ")),
+            ("E", None),
+        ] {
+            let mut node = Node::new(NodeType::Person, label.to_string());
+            node.source = source.map(str::to_string);
+            db.create_node(node).unwrap();
+        }
+
+        let sources: Vec<(String, usize)> =
+            db.get_all_node_sources().unwrap().into_iter().map(|entry| (entry.source, entry.count)).collect();
+        assert_eq!(sources, vec![("OSINT".to_string(), 2), ("Court records".to_string(), 1)]);
+    }
+
+    #[test]
+    fn recency_weighting_favors_recently_active_hubs() {
+        let db = Database::new();
+        let active = add_node(&db, NodeType::Person, "Active");
+        let dormant = add_node(&db, NodeType::Person, "Dormant");
+        let long_ago = Utc::now() - chrono::Duration::days(365);
+        for (hub, updated_at) in [(active, Utc::now()), (dormant, long_ago)] {
+            for i in 0..2 {
+                let contact = add_node(&db, NodeType::Person, &format!("Contact {}", i));
+                let mut rel = Relationship::new(hub, contact, RelationType::ConnectedTo);
+                rel.updated_at = updated_at;
+                db.create_relationship(rel).unwrap();
+            }
+        }
+
+        let plain = db.compute_degree_centrality(false, None).unwrap();
+        let score = |scores: &[DegreeCentrality], id: Uuid| scores.iter().find(|score| score.node_id == id).unwrap().clone();
+        assert_eq!(score(&plain, active).degree, score(&plain, dormant).degree);
+        assert!(plain.iter().all(|score| score.recency_weighted.is_none()));
+
+        let weighted = db.compute_degree_centrality(false, Some(30.0)).unwrap();
+        assert_eq!(weighted[0].node_id, active);
+        let (active, dormant) = (score(&weighted, active), score(&weighted, dormant));
+        assert!((active.recency_weighted.unwrap() - 2.0).abs() < 0.01);
+        assert!(dormant.recency_weighted.unwrap() < 0.01);
+    }
+
+    #[test]
+    fn purge_expired_removes_past_expiry_nodes_and_their_edges() {
+        let db = Database::new();
+        let now = Utc::now();
+        let mut transient = Node::new(NodeType::IpAddress, "203.0.113.7".to_string());
+        transient.expires_at = Some(now - chrono::Duration::hours(1));
+        let transient = db.create_node(transient).unwrap();
+        let mut pending = Node::new(NodeType::IpAddress, "203.0.113.8".to_string());
+        pending.expires_at = Some(now + chrono::Duration::hours(1));
+        let pending = db.create_node(pending).unwrap();
+        let host = add_node(&db, NodeType::Domain, "example.com");
+        link(&db, host, transient, RelationType::ConnectedTo);
+
+        let expired: Vec<Uuid> = db.get_expired_nodes(now).unwrap().iter().map(|node| node.id).collect();
+        assert_eq!(expired, vec![transient]);
+
+        let mut pinned = Node::new(NodeType::IpAddress, "203.0.113.9".to_string());
+        pinned.expires_at = Some(now - chrono::Duration::hours(1));
+        pinned.pinned = true;
+        let pinned = db.create_node(pinned).unwrap();
+
+        let report = db.purge_expired(now).unwrap();
+        assert_eq!((report.deleted, report.skipped_pinned), (vec![transient], 1));
+        assert!(db.get_node(transient).unwrap().is_none());
+        assert!(db.get_node(pending).unwrap().is_some());
+        assert!(db.get_node(pinned).unwrap().is_some());
+        assert!(db.get_relationships().unwrap().is_empty());
+    }
+
+    #[test]
+    fn relationship_confidence_is_capped_by_weaker_endpoint() {
+        let db = Database::new();
+        let a = add_scored_node(&db, NodeType::Person, "A", 0.5);
+        let b = add_scored_node(&db, NodeType::Person, "B", 0.5);
+        let c = add_scored_node(&db, NodeType::Person, "C", 0.5);
+        let strong = db.create_relationship(Relationship::new(a, b, RelationType::ConnectedTo).with_confidence(0.9)).unwrap();
+        let weak = db.create_relationship(Relationship::new(b, c, RelationType::ConnectedTo).with_confidence(0.3)).unwrap();
+
+        assert_eq!(db.cap_relationship_confidence_by_endpoints().unwrap(), 1);
+        let confidence = |id: Uuid| db.get_relationships().unwrap().into_iter().find(|rel| rel.id == id).unwrap().confidence;
+        assert_eq!(confidence(strong), 0.5);
+        assert_eq!(confidence(weak), 0.3);
+    }
+
+    #[test]
+    fn search_with_context_returns_match_with_its_neighbor_and_edge() {
+        let db = Database::new();
+        let zelda = add_node(&db, NodeType::Person, "Zelda");
+        let acme = add_node(&db, NodeType::Organization, "Acme");
+        let other = add_node(&db, NodeType::Organization, "Globex");
+        let edge = link(&db, zelda, acme, RelationType::MemberOf);
+        link(&db, acme, other, RelationType::Owns);
+
+        let context = db.search_with_context("zelda").unwrap();
+        assert_eq!(context.matches.iter().map(|node| node.id).collect::<Vec<_>>(), vec![zelda]);
+        assert_eq!(context.relationships.iter().map(|rel| rel.id).collect::<Vec<_>>(), vec![edge]);
+        assert_eq!(context.neighbors.iter().map(|node| node.id).collect::<Vec<_>>(), vec![acme]);
+
+        link(&db, acme, zelda, RelationType::Controls);
+        let context = db.search_with_context("zelda").unwrap();
+        assert_eq!(context.relationships.len(), 2);
+        assert_eq!(context.neighbors.iter().map(|node| node.id).collect::<Vec<_>>(), vec![acme]);
+    }
+
+    #[test]
+    fn autocomplete_ranks_prefix_matches_before_substring_matches() {
+        let db = Database::new();
+        let major = add_node(&db, NodeType::Person, "Major Jones");
+        let john = add_node(&db, NodeType::Person, "John");
+        add_node(&db, NodeType::Person, "Alice");
+
+        let suggestions = db.autocomplete_labels("jo", 10).unwrap();
+        assert_eq!(suggestions.iter().map(|s| s.id).collect::<Vec<_>>(), vec![john, major]);
+        assert_eq!(db.autocomplete_labels("Jo", 1).unwrap()[0].label, "John");
+    }
+
+    #[test]
+    fn path_length_stats_on_four_node_path() {
+        let db = Database::new();
+        let path: Vec<Uuid> = ["A", "B", "C", "D"].iter().map(|label| add_node(&db, NodeType::Person, label)).collect();
+        for pair in path.windows(2) {
+            link(&db, pair[0], pair[1], RelationType::ConnectedTo);
+        }
+        let x = add_node(&db, NodeType::Person, "X");
+        let y = add_node(&db, NodeType::Person, "Y");
+        link(&db, x, y, RelationType::ConnectedTo);
+
+        let stats = db.get_path_length_stats().unwrap();
+        assert_eq!((stats.component_size, stats.diameter), (4, 3));
+        assert!((stats.average_path_length - 10.0 / 6.0).abs() < 1e-9);
+        let (from, to) = stats.diameter_endpoints.unwrap();
+        let mut ends = [from, to];
+        ends.sort();
+        let mut expected = [path[0], path[3]];
+        expected.sort();
+        assert_eq!(ends, expected);
+    }
+
+    #[test]
+    fn merging_relationships_keeps_each_distinct_source_once() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Organization, "B");
+        let sourced = |source: &str| Relationship::new(a, b, RelationType::MemberOf).with_source(source.to_string());
+
+        let id = db.create_or_merge_relationship(sourced("A")).unwrap();
+        db.create_or_merge_relationship(sourced("B")).unwrap();
+        db.create_or_merge_relationship(sourced("A")).unwrap();
+
+        let merged = db.get_relationship(id).unwrap().unwrap();
+        assert_eq!(merged.sources, vec!["A", "B"]);
+        assert_eq!(merged.source_list(), vec!["A", "B"]);
+    }
+}
@@ -16,6 +16,8 @@
 //! - **Phone**: Phone numbers
 //! - **Document**: Files, reports, evidence
 //! - **Event**: Time-based occurrences
+//! - **Custom**: Entities outside the built-in types, including types from
+//!   older project files that are no longer recognized
 //!
 //! ## Relationship Types
 //!
@@ -33,12 +35,24 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+/// Confidence given to new nodes
+pub const DEFAULT_NODE_CONFIDENCE: f32 = 1.0;
+
+/// Confidence given to new relationships
+pub const DEFAULT_RELATIONSHIP_CONFIDENCE: f32 = 0.7;
+
+/// Weight given to new relationships
+pub const DEFAULT_RELATIONSHIP_WEIGHT: f32 = 1.0;
+
+/// Largest relationship weight considered sane; larger values are clamped on repair
+pub const MAX_RELATIONSHIP_WEIGHT: f32 = 1000.0;
+
 /// Types of entities that can be investigated
 ///
 /// Each node type represents a different kind of entity commonly found
 /// in OSINT investigations. The type determines how the entity should
 /// be displayed and what operations are available.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NodeType {
     /// Individual person
     Person,
@@ -60,6 +74,44 @@ pub enum NodeType {
     Document,
     /// Time-based event or occurrence
     Event,
+    /// Any other entity, including legacy types that no longer exist
+    Custom,
+}
+
+impl NodeType {
+    /// Returns the built-in fill-in-the-blanks description template for this type
+    ///
+    /// Used to prefill the description field so structured details are
+    /// entered consistently. Users can override these via settings.
+    pub fn description_template(&self) -> &'static str {
+        match self {
+            NodeType::Person => "Full name: __\nAliases: __\nDate of birth: __\nLocation: __\nOccupation: __",
+            NodeType::Organization => "Legal name: __\nJurisdiction: __\nRegistration number: __\nIndustry: __",
+            NodeType::CryptoWallet => "Chain: __\nFirst seen: __\nExchange: __\nBalance: __",
+            NodeType::SocialAccount => "Platform: __\nHandle: __\nCreated: __\nFollowers: __",
+            NodeType::Domain => "Registrar: __\nRegistered: __\nExpires: __\nName servers: __",
+            NodeType::IpAddress => "ASN: __\nHosting provider: __\nGeolocation: __\nFirst seen: __",
+            NodeType::Email => "Provider: __\nFirst seen: __\nBreaches: __",
+            NodeType::Phone => "Country: __\nCarrier: __\nLine type: __",
+            NodeType::Document => "Author: __\nDate: __\nOrigin: __\nSummary: __",
+            NodeType::Event => "Date: __\nLocation: __\nParticipants: __\nSummary: __",
+            NodeType::Custom => "Summary: __",
+        }
+    }
+
+    /// Whether nodes of this type can be enriched from external lookups
+    /// (WHOIS, DNS, IP geolocation, blockchain explorers, and so on)
+    pub fn is_enrichable(&self) -> bool {
+        matches!(
+            self,
+            NodeType::CryptoWallet
+                | NodeType::SocialAccount
+                | NodeType::Domain
+                | NodeType::IpAddress
+                | NodeType::Email
+                | NodeType::Phone
+        )
+    }
 }
 
 /// Investigation node representing an entity in the graph
@@ -99,13 +151,28 @@ pub struct Node {
     pub tags: Vec<String>,
     /// Optional source reference for where this information came from
     pub source: Option<String>,
+    /// Pinned nodes are never removed by bulk cleanup operations
+    #[serde(default)]
+    pub pinned: bool,
+    /// When the node was last refreshed by an enrichment operation, if ever
+    #[serde(default)]
+    pub last_enriched: Option<DateTime<Utc>>,
+    /// When the node stops being valid, for ephemeral indicators such as temporary IPs
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Cached number of relationships touching this node
+    ///
+    /// Maintained by the database and sent to the frontend for sizing, but
+    /// never trusted from input: it is recomputed whenever a node is stored.
+    #[serde(default, skip_deserializing)]
+    pub degree: usize,
 }
 
 /// Types of relationships between entities
 ///
 /// Relationships define how different entities are connected to each other.
 /// Each type has specific semantic meaning in the context of investigations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RelationType {
     /// Entity owns another entity (property, account, etc.)
     Owns,
@@ -127,6 +194,16 @@ pub enum RelationType {
     ChildOf,
 }
 
+impl RelationType {
+    /// Whether the relationship reads the same in both directions
+    ///
+    /// Symmetric relationships are stored once, as a single directed edge,
+    /// and direction-aware algorithms and exports treat them as two-way.
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, RelationType::SameAs | RelationType::ConnectedTo | RelationType::RelatedTo)
+    }
+}
+
 /// Relationship between two nodes in the investigation graph
 ///
 /// Relationships represent connections between entities. They can have
@@ -170,6 +247,16 @@ pub struct Relationship {
     pub metadata: serde_json::Value,
     /// Optional source reference for where this information came from
     pub source: Option<String>,
+    /// Optional short free-text label shown on the edge (e.g. "wired $5k")
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Every distinct source collected when duplicates were merged into this relationship
+    ///
+    /// Empty until the first merge; `source` alone describes unmerged relationships.
+    /// Once filled, it always includes the references named by `source`
+    /// (see [`Relationship::set_source`]).
+    #[serde(default)]
+    pub sources: Vec<String>,
 }
 
 impl Node {
@@ -191,9 +278,13 @@ impl Node {
             metadata: serde_json::Value::Object(serde_json::Map::new()),
             created_at: now,
             updated_at: now,
-            confidence: 1.0,
+            confidence: DEFAULT_NODE_CONFIDENCE,
             tags: Vec::new(),
             source: None,
+            pinned: false,
+            last_enriched: None,
+            expires_at: None,
+            degree: 0,
         }
     }
 
@@ -211,7 +302,9 @@ impl Node {
 
     /// Sets tags for this node
     ///
-    /// Tags are used for categorization and searching
+    /// Tags are used for categorization and searching. They are trimmed,
+    /// empty tags are dropped, and duplicates are removed case-insensitively
+    /// while keeping the casing of the first occurrence.
     ///
     /// # Arguments
     /// * `tags` - Vector of tag strings
@@ -219,10 +312,204 @@ impl Node {
     /// # Returns
     /// Self for method chaining
     pub fn with_tags(mut self, tags: Vec<String>) -> Self {
-        self.tags = tags;
+        self.tags = normalize_tags(tags, false);
+        self
+    }
+
+    /// Sets the source reference for this node
+    ///
+    /// # Arguments
+    /// * `source` - Source description (e.g., "FOIA request", "Company registry")
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn with_source(mut self, source: String) -> Self {
+        self.source = Some(source);
         self
     }
 
+    /// Whether the node's expiry time is at or before `now`
+    ///
+    /// Nodes without an expiry never expire.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Stores the normalized form of this node's identifier in `metadata.normalized`
+    ///
+    /// Only applies to identifier-like types (see [`normalize_identifier`]).
+    /// For other types any stale `normalized` entry is removed.
+    pub fn refresh_normalized_identifier(&mut self) {
+        if !self.metadata.is_object() {
+            self.metadata = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let Some(metadata) = self.metadata.as_object_mut() {
+            match normalize_identifier(&self.node_type, &self.label) {
+                Some(normalized) => {
+                    metadata.insert("normalized".to_string(), serde_json::Value::String(normalized));
+                }
+                None => {
+                    metadata.remove("normalized");
+                }
+            }
+        }
+    }
+
+}
+
+/// Normalizes an identifier-like label for duplicate detection
+///
+/// - **Email**: trimmed and lowercased, with trailing dots removed from the domain
+/// - **Domain**: trimmed and lowercased, with trailing dots removed
+///
+/// # Arguments
+/// * `node_type` - Type of the node the value belongs to
+/// * `value` - Raw identifier, usually the node label
+///
+/// # Returns
+/// * `Some(String)` - The normalized identifier
+/// * `None` - If the type is not an identifier type or the value is empty
+pub fn normalize_identifier(node_type: &NodeType, value: &str) -> Option<String> {
+    let value = value.trim().to_lowercase();
+    let normalized = match node_type {
+        NodeType::Domain => value.trim_end_matches('.').to_string(),
+        NodeType::Email => match value.rsplit_once('@') {
+            Some((local, domain)) => format!("{}@{}", local, domain.trim_end_matches('.')),
+            None => value,
+        },
+        _ => return None,
+    };
+
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+/// Guesses the node type of a bare indicator such as an IP, domain, or hash
+///
+/// - **IpAddress**: a valid IPv4 or IPv6 address
+/// - **Email**: a local part, `@`, and a domain
+/// - **Document**: a 64-character hex string (SHA-256 hash)
+/// - **Domain**: dot-separated labels of letters, digits, and hyphens,
+///   ending in an alphabetic TLD
+///
+/// # Arguments
+/// * `value` - Indicator text, surrounding whitespace is ignored
+///
+/// # Returns
+/// * `Some(NodeType)` - The inferred type
+/// * `None` - If the value does not look like any known indicator
+pub fn classify_indicator(value: &str) -> Option<NodeType> {
+    let value = value.trim();
+    if value.parse::<std::net::IpAddr>().is_ok() {
+        return Some(NodeType::IpAddress);
+    }
+    if value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(NodeType::Document);
+    }
+    if let Some((local, domain)) = value.split_once('@') {
+        let local_ok = !local.is_empty() && !local.contains(char::is_whitespace);
+        return (local_ok && looks_like_domain(domain)).then_some(NodeType::Email);
+    }
+    looks_like_domain(value).then_some(NodeType::Domain)
+}
+
+/// Checks whether a value has the shape of a DNS name with an alphabetic TLD
+fn looks_like_domain(value: &str) -> bool {
+    let value = value.trim_end_matches('.');
+    let labels: Vec<&str> = value.split('.').collect();
+    if labels.len() < 2 {
+        return false;
+    }
+    let labels_ok = labels.iter().all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    let tld = labels[labels.len() - 1];
+    labels_ok && tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Deep-merges a JSON patch into existing metadata
+///
+/// Objects are merged key by key, recursively, so keys absent from the patch
+/// are preserved. A `null` in the patch removes the key. Any other value,
+/// including arrays, replaces the existing value. This follows JSON Merge
+/// Patch (RFC 7386).
+///
+/// # Arguments
+/// * `target` - Metadata to modify in place
+/// * `patch` - Values to merge in
+pub fn merge_metadata(target: &mut serde_json::Value, patch: serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let Some(existing) = target.as_object_mut() {
+        for (key, value) in patch {
+            if value.is_null() {
+                existing.remove(&key);
+            } else {
+                merge_metadata(existing.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Normalizes a list of tags
+///
+/// Trims whitespace, drops empty tags, and removes duplicates compared
+/// case-insensitively. The first occurrence of each tag wins and order is
+/// otherwise preserved.
+///
+/// # Arguments
+/// * `tags` - Raw tags as entered by the user
+/// * `lowercase` - Whether to store tags lowercased instead of keeping their display casing
+///
+/// # Returns
+/// The normalized tag list
+pub fn normalize_tags(tags: Vec<String>, lowercase: bool) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut normalized = Vec::with_capacity(tags.len());
+
+    for tag in tags {
+        let trimmed = tag.trim();
+        if trimmed.is_empty() || !seen.insert(trimmed.to_lowercase()) {
+            continue;
+        }
+        if lowercase {
+            normalized.push(trimmed.to_lowercase());
+        } else {
+            normalized.push(trimmed.to_string());
+        }
+    }
+
+    normalized
+}
+
+/// Splits a source field into its individual references
+///
+/// Combined sources are joined with `"; "` (see `SourceMerge::Union`);
+/// blank references are skipped.
+///
+/// # Arguments
+/// * `source` - Source field, possibly holding several references
+///
+/// # Returns
+/// The trimmed references, in order
+pub fn source_references(source: Option<&str>) -> impl Iterator<Item = &str> {
+    source
+        .into_iter()
+        .flat_map(|source| source.split(';'))
+        .map(str::trim)
+        .filter(|reference| !reference.is_empty())
 }
 
 impl Relationship {
@@ -243,12 +530,14 @@ impl Relationship {
             target_id,
             relation_type,
             description: None,
-            weight: 1.0,
-            confidence: 0.7, // Default confidence score
+            weight: DEFAULT_RELATIONSHIP_WEIGHT,
+            confidence: DEFAULT_RELATIONSHIP_CONFIDENCE,
             created_at: now,
             updated_at: now,
             metadata: serde_json::Value::Object(serde_json::Map::new()),
             source: None,
+            label: None,
+            sources: Vec::new(),
         }
     }
 
@@ -266,6 +555,20 @@ impl Relationship {
         self
     }
 
+    /// Sets the weight for this relationship
+    ///
+    /// Negative weights are clamped to 0.0
+    ///
+    /// # Arguments
+    /// * `weight` - Weight/strength of the relationship
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight.max(0.0);
+        self
+    }
+
     /// Sets the source reference for this relationship
     ///
     /// # Arguments
@@ -277,4 +580,111 @@ impl Relationship {
         self.source = Some(source);
         self
     }
-}
\ No newline at end of file
+
+    /// Sets the short label for this relationship
+    ///
+    /// # Arguments
+    /// * `label` - Free-text edge label; blank labels are ignored
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn with_label(mut self, label: String) -> Self {
+        let label = label.trim();
+        self.label = (!label.is_empty()).then(|| label.to_string());
+        self
+    }
+
+    /// Returns every distinct source of this relationship, in the order they were first recorded
+    ///
+    /// Falls back to the references in `source` for relationships that were never merged.
+    pub fn source_list(&self) -> Vec<String> {
+        if self.sources.is_empty() {
+            let mut sources = Vec::new();
+            for reference in source_references(self.source.as_deref()) {
+                if !sources.iter().any(|source| source == reference) {
+                    sources.push(reference.to_string());
+                }
+            }
+            sources
+        } else {
+            self.sources.clone()
+        }
+    }
+
+    /// Sets the source reference, keeping `sources` in step
+    ///
+    /// A merged relationship keeps the sources it has collected, and any
+    /// reference in the new source that isn't among them yet is appended.
+    ///
+    /// # Arguments
+    /// * `source` - New source reference; `"; "`-separated references are recorded individually
+    pub fn set_source(&mut self, source: Option<String>) {
+        self.source = source;
+        if self.sources.is_empty() {
+            return;
+        }
+        for reference in source_references(self.source.as_deref()) {
+            if !self.sources.iter().any(|source| source == reference) {
+                self.sources.push(reference.to_string());
+            }
+        }
+    }
+
+    /// Adds the sources of a duplicate relationship to `sources`
+    ///
+    /// Sources already present are skipped, so the list stays distinct and
+    /// keeps its existing order.
+    ///
+    /// # Arguments
+    /// * `other` - The duplicate being merged into this relationship
+    pub fn absorb_sources(&mut self, other: &Relationship) {
+        let mut sources = self.source_list();
+        for source in other.source_list() {
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+        self.sources = sources;
+    }
+
+    /// Returns the text to show on the edge: its label, or the type name when it has none
+    pub fn display_label(&self) -> String {
+        self.label.clone().unwrap_or_else(|| format!("{:?}", self.relation_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn tags_are_trimmed_deduplicated_and_lowercased() {
+        let tags = strings(&["Suspect", " suspect ", ""]);
+        assert_eq!(normalize_tags(tags.clone(), true), strings(&["suspect"]));
+        assert_eq!(normalize_tags(tags, false), strings(&["Suspect"]));
+    }
+
+    #[test]
+    fn metadata_merge_preserves_keys_missing_from_the_patch() {
+        let mut metadata = serde_json::json!({ "kept": 1, "nested": { "a": 1, "b": 2 }, "dropped": true });
+        merge_metadata(&mut metadata, serde_json::json!({ "nested": { "b": 3 }, "added": "x", "dropped": null }));
+        assert_eq!(metadata, serde_json::json!({ "kept": 1, "nested": { "a": 1, "b": 3 }, "added": "x" }));
+    }
+
+    #[test]
+    fn set_source_appends_new_references_to_merged_sources() {
+        let mut rel = Relationship::new(Uuid::new_v4(), Uuid::new_v4(), RelationType::ConnectedTo).with_source("A".to_string());
+        rel.set_source(Some("B".to_string()));
+        assert!(rel.sources.is_empty());
+        assert_eq!(rel.source_list(), strings(&["B"]));
+
+        let duplicate = rel.clone().with_source("C".to_string());
+        rel.absorb_sources(&duplicate);
+        rel.set_source(Some("C; D".to_string()));
+        assert_eq!(rel.sources, strings(&["B", "C", "D"]));
+    }
+}
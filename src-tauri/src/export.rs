@@ -0,0 +1,1242 @@
+//! # Export Rendering
+//!
+//! This module turns investigation data into self-contained documents that
+//! can be shared outside the application.
+//!
+//! ## Formats
+//!
+//! - **CSV**: Node and relationship sections in one file
+//! - **GraphML**: XML graph format for tools like Gephi
+//! - **GEXF**: Gephi's native XML graph format
+//! - **Cypher**: `CREATE` statements for loading into Neo4j
+//! - **networkx JSON**: node-link format loadable with `json_graph.node_link_graph`
+//! - **Turtle**: RDF triples for semantic-web tooling
+//! - **DOT**: Graphviz digraph styled by node type, with an optional legend
+//! - **SVG**: Standalone vector drawing of the graph
+//! - **HTML report**: A single file with inline CSS, summary statistics,
+//!   node and relationship tables, and an embedded SVG of the graph
+//!
+//! All renderers are pure functions over node and relationship slices; the
+//! Tauri commands in `lib.rs` are responsible for fetching data and writing
+//! the result to disk.
+
+use crate::entities::{Node, NodeType, Relationship};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::f64::consts::PI;
+use uuid::Uuid;
+
+/// Width and height of the embedded graph drawing, in SVG user units
+const SVG_SIZE: f64 = 600.0;
+
+/// Minimum relationship confidence drawn as a solid line
+pub const SOLID_EDGE_MIN_CONFIDENCE: f32 = 0.7;
+
+/// Minimum relationship confidence drawn as a dashed line; anything lower is dotted
+pub const DASHED_EDGE_MIN_CONFIDENCE: f32 = 0.4;
+
+/// Line style hint for an edge, derived from relationship confidence
+///
+/// Graph exports carry this alongside the raw confidence so visualization
+/// tools can make uncertain links stand out without their own thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeStyle {
+    /// Confidence of at least [`SOLID_EDGE_MIN_CONFIDENCE`]
+    Solid,
+    /// Confidence between [`DASHED_EDGE_MIN_CONFIDENCE`] and [`SOLID_EDGE_MIN_CONFIDENCE`]
+    Dashed,
+    /// Confidence below [`DASHED_EDGE_MIN_CONFIDENCE`]
+    Dotted,
+}
+
+impl EdgeStyle {
+    /// Maps a relationship confidence to its style band
+    pub fn from_confidence(confidence: f32) -> Self {
+        if confidence >= SOLID_EDGE_MIN_CONFIDENCE {
+            EdgeStyle::Solid
+        } else if confidence >= DASHED_EDGE_MIN_CONFIDENCE {
+            EdgeStyle::Dashed
+        } else {
+            EdgeStyle::Dotted
+        }
+    }
+
+    /// Style name as written to export files
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EdgeStyle::Solid => "solid",
+            EdgeStyle::Dashed => "dashed",
+            EdgeStyle::Dotted => "dotted",
+        }
+    }
+
+    /// SVG `stroke-dasharray` value for this style, if any
+    fn svg_dasharray(self) -> Option<&'static str> {
+        match self {
+            EdgeStyle::Solid => None,
+            EdgeStyle::Dashed => Some("6 4"),
+            EdgeStyle::Dotted => Some("2 3"),
+        }
+    }
+}
+
+/// Escapes a string for safe interpolation into HTML or XML text and attributes
+///
+/// # Arguments
+/// * `value` - Raw string to escape
+///
+/// # Returns
+/// The escaped string
+pub fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Maximum length, in characters, of a sanitized filename stem
+pub const MAX_FILENAME_CHARS: usize = 64;
+
+/// Device names Windows refuses as filenames, with or without an extension
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turns a node label into a filename stem that is safe on every platform
+///
+/// Path separators, characters Windows rejects, and control characters
+/// become `_`; leading and trailing dots and spaces are removed; Windows
+/// device names such as `CON` get a `_` prefix; and the result is cut to
+/// [`MAX_FILENAME_CHARS`]. Names are compared case-insensitively against
+/// `taken`, and a colliding name gets the first 8 characters of `id`
+/// appended, so the output is deterministic for a given export order.
+///
+/// # Arguments
+/// * `label` - Label to derive the name from
+/// * `id` - ID of the entity being written, used to disambiguate collisions
+/// * `taken` - Lowercased stems already used in this export; the result is added
+///
+/// # Returns
+/// The filename stem, without extension
+pub fn sanitize_filename(label: &str, id: Uuid, taken: &mut HashSet<String>) -> String {
+    let replaced: String = label
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let mut stem: String = replaced
+        .trim_matches(|c| c == '.' || c == ' ')
+        .chars()
+        .take(MAX_FILENAME_CHARS)
+        .collect();
+    stem.truncate(stem.trim_end_matches(['.', ' ']).len());
+
+    if stem.is_empty() {
+        stem = "unnamed".to_string();
+    }
+    let device_name = stem.split('.').next().unwrap_or_default().to_uppercase();
+    if WINDOWS_RESERVED_NAMES.contains(&device_name.as_str()) {
+        stem.insert(0, '_');
+    }
+
+    if taken.contains(&stem.to_lowercase()) {
+        stem = format!("{}-{}", stem, &id.simple().to_string()[..8]);
+    }
+    taken.insert(stem.to_lowercase());
+    stem
+}
+
+/// Numbers parallel relationships between the same pair of nodes
+///
+/// The Nth relationship (in slice order) connecting a pair gets index N,
+/// starting from 0, regardless of direction. Renderers can use the index to
+/// curve parallel edges apart instead of drawing them on top of each other.
+///
+/// # Arguments
+/// * `relationships` - Relationships to number
+///
+/// # Returns
+/// Map from relationship ID to its index among the pair's relationships
+pub fn parallel_indices(relationships: &[Relationship]) -> HashMap<Uuid, usize> {
+    let mut counts: HashMap<(Uuid, Uuid), usize> = HashMap::new();
+    relationships
+        .iter()
+        .map(|rel| {
+            let pair = if rel.source_id <= rel.target_id {
+                (rel.source_id, rel.target_id)
+            } else {
+                (rel.target_id, rel.source_id)
+            };
+            let count = counts.entry(pair).or_insert(0);
+            let index = *count;
+            *count += 1;
+            (rel.id, index)
+        })
+        .collect()
+}
+
+/// Computes node positions evenly spaced on a circle
+///
+/// Nodes are ordered by label so the drawing is stable between exports.
+///
+/// # Arguments
+/// * `nodes` - Nodes to lay out
+/// * `size` - Width/height of the square drawing area
+///
+/// # Returns
+/// Map from node ID to (x, y) coordinates
+fn circular_layout(nodes: &[Node], size: f64) -> HashMap<Uuid, (f64, f64)> {
+    let mut ordered: Vec<&Node> = nodes.iter().collect();
+    ordered.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+
+    let center = size / 2.0;
+    let radius = size * 0.4;
+    let count = ordered.len().max(1) as f64;
+
+    ordered
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            if ordered.len() == 1 {
+                return (node.id, (center, center));
+            }
+            let angle = 2.0 * PI * i as f64 / count - PI / 2.0;
+            (node.id, (center + radius * angle.cos(), center + radius * angle.sin()))
+        })
+        .collect()
+}
+
+/// Smallest node radius in SVG drawings, used for isolated nodes
+const SVG_MIN_NODE_RADIUS: f64 = 6.0;
+
+/// Largest node radius in SVG drawings, however connected the node is
+const SVG_MAX_NODE_RADIUS: f64 = 20.0;
+
+/// Renders the graph as an inline SVG element using a circular layout
+///
+/// Nodes are filled by type (see [`node_style`]) and their radius grows
+/// with the square root of their degree among the given relationships.
+fn render_graph_svg(nodes: &[Node], relationships: &[Relationship]) -> String {
+    let positions = circular_layout(nodes, SVG_SIZE);
+    let mut degrees: HashMap<Uuid, usize> = HashMap::new();
+    for rel in relationships {
+        if positions.contains_key(&rel.source_id) && positions.contains_key(&rel.target_id) {
+            *degrees.entry(rel.source_id).or_default() += 1;
+            *degrees.entry(rel.target_id).or_default() += 1;
+        }
+    }
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" width=\"{size}\" height=\"{size}\">\n",
+        size = SVG_SIZE
+    ));
+
+    for rel in relationships {
+        if let (Some((x1, y1)), Some((x2, y2))) = (positions.get(&rel.source_id), positions.get(&rel.target_id)) {
+            let dash = EdgeStyle::from_confidence(rel.confidence)
+                .svg_dasharray()
+                .map(|pattern| format!(" stroke-dasharray=\"{}\"", pattern))
+                .unwrap_or_default();
+            svg.push_str(&format!(
+                "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#888\" stroke-width=\"1\"{}/>\n",
+                x1, y1, x2, y2, dash
+            ));
+        }
+    }
+
+    for node in nodes {
+        if let Some((x, y)) = positions.get(&node.id) {
+            let degree = degrees.get(&node.id).copied().unwrap_or(0) as f64;
+            let radius = (SVG_MIN_NODE_RADIUS + 2.0 * degree.sqrt()).min(SVG_MAX_NODE_RADIUS);
+            svg.push_str(&format!(
+                "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\"/>\n",
+                x,
+                y,
+                radius,
+                node_style(&node.node_type).color
+            ));
+            svg.push_str(&format!(
+                "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"middle\">{}</text>\n",
+                x,
+                y - radius - 4.0,
+                escape_html(&node.label)
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Renders the graph as a standalone SVG image
+///
+/// Uses the same circular layout and styling as the drawing embedded in
+/// HTML reports. Labels are XML-escaped.
+///
+/// # Arguments
+/// * `nodes` - Nodes to draw
+/// * `relationships` - Relationships to draw; those with an endpoint outside `nodes` are skipped
+///
+/// # Returns
+/// The SVG document as a string
+pub fn render_svg(nodes: &[Node], relationships: &[Relationship]) -> String {
+    let mut svg = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    svg.push_str(&render_graph_svg(nodes, relationships));
+    svg
+}
+
+/// Renders a complete, self-contained HTML investigation report
+///
+/// The output has no external assets: styles are inline and the graph is an
+/// embedded SVG, so the file opens offline in any browser. Every
+/// user-supplied string is HTML-escaped.
+///
+/// # Arguments
+/// * `title` - Report title
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+///
+/// # Returns
+/// The HTML document as a string
+pub fn render_html_report(title: &str, nodes: &[Node], relationships: &[Relationship]) -> String {
+    let labels: HashMap<Uuid, &str> = nodes.iter().map(|n| (n.id, n.label.as_str())).collect();
+    let label_of = |id: &Uuid| labels.get(id).copied().unwrap_or("(unknown)");
+
+    let mut type_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for node in nodes {
+        *type_counts.entry(format!("{:?}", node.node_type)).or_insert(0) += 1;
+    }
+    let average_confidence = if nodes.is_empty() {
+        0.0
+    } else {
+        nodes.iter().map(|n| n.confidence as f64).sum::<f64>() / nodes.len() as f64
+    };
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"UTF-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+    html.push_str("<style>\n");
+    html.push_str("body { font-family: sans-serif; margin: 2em; color: #222; }\n");
+    html.push_str("table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }\n");
+    html.push_str("th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }\n");
+    html.push_str("th { background: #f0f0f0; }\n");
+    html.push_str("</style>\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    html.push_str(&format!(
+        "<p>Generated {}</p>\n",
+        escape_html(&chrono::Utc::now().to_rfc3339())
+    ));
+
+    // Summary
+    html.push_str("<h2>Summary</h2>\n<ul>\n");
+    html.push_str(&format!("<li>Nodes: {}</li>\n", nodes.len()));
+    html.push_str(&format!("<li>Relationships: {}</li>\n", relationships.len()));
+    html.push_str(&format!("<li>Average node confidence: {:.0}%</li>\n", average_confidence * 100.0));
+    for (node_type, count) in &type_counts {
+        html.push_str(&format!("<li>{}: {}</li>\n", escape_html(node_type), count));
+    }
+    html.push_str("</ul>\n");
+
+    // Graph
+    html.push_str("<h2>Graph</h2>\n");
+    html.push_str(&render_graph_svg(nodes, relationships));
+
+    // Nodes
+    html.push_str("<h2>Nodes</h2>\n<table>\n");
+    html.push_str("<tr><th>Label</th><th>Type</th><th>Description</th><th>Tags</th><th>Confidence</th><th>Source</th></tr>\n");
+    for node in nodes {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{}</td><td>{:.0}%</td><td>{}</td></tr>\n",
+            escape_html(&node.label),
+            node.node_type,
+            escape_html(node.description.as_deref().unwrap_or("")),
+            escape_html(&node.tags.join(", ")),
+            node.confidence * 100.0,
+            escape_html(node.source.as_deref().unwrap_or(""))
+        ));
+    }
+    html.push_str("</table>\n");
+
+    // Relationships
+    html.push_str("<h2>Relationships</h2>\n<table>\n");
+    html.push_str("<tr><th>Source</th><th>Type</th><th>Label</th><th>Target</th><th>Description</th><th>Confidence</th><th>Evidence Source</th></tr>\n");
+    for rel in relationships {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.0}%</td><td>{}</td></tr>\n",
+            escape_html(label_of(&rel.source_id)),
+            rel.relation_type,
+            escape_html(rel.label.as_deref().unwrap_or("")),
+            escape_html(label_of(&rel.target_id)),
+            escape_html(rel.description.as_deref().unwrap_or("")),
+            rel.confidence * 100.0,
+            escape_html(rel.source.as_deref().unwrap_or(""))
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Renders nodes and relationships as CSV
+///
+/// The file contains a node section followed by a relationship section,
+/// each with its own header row.
+///
+/// # Arguments
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+///
+/// # Returns
+/// The CSV content as a string
+pub fn render_csv(nodes: &[Node], relationships: &[Relationship]) -> String {
+    let mut csv_content = String::new();
+    
+    // Nodes CSV
+    csv_content.push_str("Type,ID,Label,NodeType,Description,Tags,Confidence,CreatedAt\n");
+    for node in nodes {
+        csv_content.push_str(&format!(
+            "Node,{},{},{},{},{},{},{}\n",
+            node.id,
+            node.label.replace(',', ";"),
+            format!("{:?}", node.node_type),
+            node.description.as_ref().unwrap_or(&String::new()).replace(',', ";"),
+            node.tags.join(";"),
+            node.confidence,
+            node.created_at.to_rfc3339()
+        ));
+    }
+    
+    csv_content.push_str("\n");
+    
+    // Relationships CSV
+    csv_content.push_str("Type,ID,SourceID,TargetID,RelationType,Description,Weight,Confidence,Source,CreatedAt\n");
+    for rel in relationships {
+        csv_content.push_str(&format!(
+            "Relationship,{},{},{},{},{},{},{},{},{}\n",
+            rel.id,
+            rel.source_id,
+            rel.target_id,
+            format!("{:?}", rel.relation_type),
+            rel.description.as_ref().unwrap_or(&String::new()).replace(',', ";"),
+            rel.weight,
+            rel.confidence,
+            rel.source.as_ref().unwrap_or(&String::new()).replace(',', ";"),
+            rel.created_at.to_rfc3339()
+        ));
+    }
+    
+    csv_content
+}
+
+/// How fresh a node is, from 1.0 (just updated) down to 0.0 (`max_age_days` old or older)
+///
+/// Freshness falls linearly with the time since `updated_at`. A zero
+/// `max_age_days` makes every node fully stale.
+///
+/// # Arguments
+/// * `updated_at` - When the node was last updated
+/// * `now` - Reference time, normally the export time
+/// * `max_age_days` - Age at which freshness reaches 0.0
+///
+/// # Returns
+/// Freshness between 0.0 and 1.0
+pub fn freshness(updated_at: DateTime<Utc>, now: DateTime<Utc>, max_age_days: u32) -> f64 {
+    if max_age_days == 0 {
+        return 0.0;
+    }
+    let age_days = (now - updated_at).num_seconds().max(0) as f64 / 86_400.0;
+    (1.0 - age_days / max_age_days as f64).clamp(0.0, 1.0)
+}
+
+/// Renders nodes and relationships as GraphML
+///
+/// Each edge carries its raw confidence plus a derived `style` hint (see
+/// [`EdgeStyle`]); symmetric relationship types are marked
+/// `directed="false"`. Each node carries a derived `freshness` (see [`freshness`])
+/// so stale entities can be faded. When communities are given, each node
+/// gets a `community` attribute so tools like Gephi can color groups.
+///
+/// # Arguments
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+/// * `communities` - Optional community number per node
+/// * `freshness_max_age_days` - Age at which node freshness reaches 0.0
+///
+/// # Returns
+/// The GraphML document as a string
+pub fn render_graphml(
+    nodes: &[Node],
+    relationships: &[Relationship],
+    communities: Option<&HashMap<Uuid, usize>>,
+    freshness_max_age_days: u32,
+) -> String {
+    let now = Utc::now();
+    let mut graphml = String::new();
+    graphml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    graphml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n");
+    
+    // Define keys for attributes
+    graphml.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"nodeType\" for=\"node\" attr.name=\"nodeType\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"confidence\" for=\"node\" attr.name=\"confidence\" attr.type=\"double\"/>\n");
+    graphml.push_str("  <key id=\"freshness\" for=\"node\" attr.name=\"freshness\" attr.type=\"double\"/>\n");
+    if communities.is_some() {
+        graphml.push_str("  <key id=\"community\" for=\"node\" attr.name=\"community\" attr.type=\"int\"/>\n");
+    }
+    graphml.push_str("  <key id=\"relationType\" for=\"edge\" attr.name=\"relationType\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"edgeLabel\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n");
+    graphml.push_str("  <key id=\"edgeConfidence\" for=\"edge\" attr.name=\"edgeConfidence\" attr.type=\"double\"/>\n");
+    graphml.push_str("  <key id=\"source\" for=\"edge\" attr.name=\"source\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"style\" for=\"edge\" attr.name=\"style\" attr.type=\"string\"/>\n");
+    
+    graphml.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+    
+    // Add nodes
+    for node in nodes {
+        graphml.push_str(&format!("    <node id=\"{}\">\n", node.id));
+        graphml.push_str(&format!("      <data key=\"label\">{}</data>\n", node.label));
+        graphml.push_str(&format!("      <data key=\"nodeType\">{:?}</data>\n", node.node_type));
+        graphml.push_str(&format!("      <data key=\"confidence\">{}</data>\n", node.confidence));
+        graphml.push_str(&format!(
+            "      <data key=\"freshness\">{:.3}</data>\n",
+            freshness(node.updated_at, now, freshness_max_age_days)
+        ));
+        if let Some(community) = communities.and_then(|communities| communities.get(&node.id)) {
+            graphml.push_str(&format!("      <data key=\"community\">{}</data>\n", community));
+        }
+        graphml.push_str("    </node>\n");
+    }
+    
+    // Add edges
+    for rel in relationships {
+        let direction = if rel.relation_type.is_symmetric() { " directed=\"false\"" } else { "" };
+        graphml.push_str(&format!(
+            "    <edge id=\"{}\" source=\"{}\" target=\"{}\"{}>\n",
+            rel.id, rel.source_id, rel.target_id, direction
+        ));
+        graphml.push_str(&format!("      <data key=\"relationType\">{:?}</data>\n", rel.relation_type));
+        graphml.push_str(&format!("      <data key=\"edgeLabel\">{}</data>\n", escape_html(&rel.display_label())));
+        graphml.push_str(&format!("      <data key=\"weight\">{}</data>\n", rel.weight));
+        graphml.push_str(&format!("      <data key=\"edgeConfidence\">{}</data>\n", rel.confidence));
+        graphml.push_str(&format!(
+            "      <data key=\"style\">{}</data>\n",
+            EdgeStyle::from_confidence(rel.confidence).as_str()
+        ));
+        if let Some(source) = &rel.source {
+            graphml.push_str(&format!("      <data key=\"source\">{}</data>\n", source));
+        }
+        graphml.push_str("    </edge>\n");
+    }
+    
+    graphml.push_str("  </graph>\n");
+    graphml.push_str("</graphml>\n");
+    
+    graphml
+}
+
+/// Renders nodes and relationships in networkx's node-link JSON format
+///
+/// The output loads with `networkx.readwrite.json_graph.node_link_graph`
+/// (pass `edges="links"` on networkx 3.4 and later) as a directed multigraph,
+/// using relationship IDs as edge keys so parallel relationships survive. Relationships whose endpoints are not among `nodes`
+/// are left out so every link references an existing node.
+///
+/// # Arguments
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+///
+/// # Returns
+/// The JSON document as a value
+pub fn render_networkx_json(nodes: &[Node], relationships: &[Relationship]) -> serde_json::Value {
+    let node_ids: HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+    let parallel = parallel_indices(relationships);
+
+    let nodes: Vec<serde_json::Value> = nodes
+        .iter()
+        .map(|node| {
+            serde_json::json!({
+                "id": node.id,
+                "label": node.label,
+                "node_type": node.node_type,
+                "confidence": node.confidence,
+            })
+        })
+        .collect();
+
+    let links: Vec<serde_json::Value> = relationships
+        .iter()
+        .filter(|rel| node_ids.contains(&rel.source_id) && node_ids.contains(&rel.target_id))
+        .map(|rel| {
+            serde_json::json!({
+                "source": rel.source_id,
+                "target": rel.target_id,
+                "key": rel.id,
+                "relation_type": rel.relation_type,
+                "label": rel.display_label(),
+                "weight": rel.weight,
+                "confidence": rel.confidence,
+                "parallel_index": parallel.get(&rel.id).copied().unwrap_or(0),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "directed": true,
+        "multigraph": true,
+        "graph": {},
+        "nodes": nodes,
+        "links": links,
+    })
+}
+
+/// Namespace for the node types and predicates used in Turtle exports
+pub const TURTLE_VOCAB: &str = "urn:osint-studio:vocab#";
+
+/// Escapes a string for use inside a double-quoted Turtle literal
+fn escape_turtle(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Lower-camel-cases a variant name for use as a predicate (`SameAs` -> `sameAs`)
+fn turtle_predicate(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Renders nodes and relationships as RDF in Turtle syntax
+///
+/// Each node becomes a `urn:uuid:` subject with an `rdf:type` from its node
+/// type, an `rdfs:label`, an optional `rdfs:comment`, and an
+/// `osint:confidence`. Each relationship becomes exactly one triple whose
+/// predicate is derived from its relation type (`Owns` -> `osint:owns`).
+/// Relationships whose endpoints are not among `nodes` are left out.
+///
+/// # Arguments
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+///
+/// # Returns
+/// The Turtle document as a string
+pub fn render_turtle(nodes: &[Node], relationships: &[Relationship]) -> String {
+    let node_ids: HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+
+    let mut turtle = String::new();
+    turtle.push_str("@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .\n");
+    turtle.push_str("@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n");
+    turtle.push_str("@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n");
+    turtle.push_str(&format!("@prefix osint: <{}> .\n\n", TURTLE_VOCAB));
+
+    for node in nodes {
+        turtle.push_str(&format!("<urn:uuid:{}> rdf:type osint:{:?} ;\n", node.id, node.node_type));
+        turtle.push_str(&format!("    rdfs:label \"{}\" ;\n", escape_turtle(&node.label)));
+        if let Some(description) = &node.description {
+            turtle.push_str(&format!("    rdfs:comment \"{}\" ;\n", escape_turtle(description)));
+        }
+        turtle.push_str(&format!("    osint:confidence \"{}\"^^xsd:decimal .\n\n", node.confidence));
+    }
+
+    for rel in relationships
+        .iter()
+        .filter(|rel| node_ids.contains(&rel.source_id) && node_ids.contains(&rel.target_id))
+    {
+        turtle.push_str(&format!(
+            "<urn:uuid:{}> osint:{} <urn:uuid:{}> .\n",
+            rel.source_id,
+            turtle_predicate(&format!("{:?}", rel.relation_type)),
+            rel.target_id
+        ));
+    }
+
+    turtle
+}
+
+/// Renders nodes and relationships as GEXF 1.3, Gephi's native format
+///
+/// Carries the same attributes as [`render_graphml`]: node type,
+/// confidence, `freshness`, and the optional `community` on nodes; relation
+/// type, confidence, `style`, and source on edges, with the edge label and
+/// weight as native GEXF attributes. Symmetric relationship types are
+/// written as undirected edges. Relationships whose endpoints are not among
+/// `nodes` are left out, since Gephi rejects them.
+///
+/// # Arguments
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+/// * `communities` - Optional community number per node
+/// * `freshness_max_age_days` - Age at which node freshness reaches 0.0
+///
+/// # Returns
+/// The GEXF document as a string
+pub fn render_gexf(
+    nodes: &[Node],
+    relationships: &[Relationship],
+    communities: Option<&HashMap<Uuid, usize>>,
+    freshness_max_age_days: u32,
+) -> String {
+    let now = Utc::now();
+    let node_ids: HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+
+    let mut gexf = String::new();
+    gexf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    gexf.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+    gexf.push_str(&format!(
+        "  <meta lastmodifieddate=\"{}\">\n    <creator>OSINT Studio</creator>\n  </meta>\n",
+        now.format("%Y-%m-%d")
+    ));
+    gexf.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
+
+    gexf.push_str("    <attributes class=\"node\">\n");
+    gexf.push_str("      <attribute id=\"nodeType\" title=\"nodeType\" type=\"string\"/>\n");
+    gexf.push_str("      <attribute id=\"confidence\" title=\"confidence\" type=\"double\"/>\n");
+    gexf.push_str("      <attribute id=\"freshness\" title=\"freshness\" type=\"double\"/>\n");
+    if communities.is_some() {
+        gexf.push_str("      <attribute id=\"community\" title=\"community\" type=\"integer\"/>\n");
+    }
+    gexf.push_str("    </attributes>\n");
+    gexf.push_str("    <attributes class=\"edge\">\n");
+    gexf.push_str("      <attribute id=\"relationType\" title=\"relationType\" type=\"string\"/>\n");
+    gexf.push_str("      <attribute id=\"edgeConfidence\" title=\"edgeConfidence\" type=\"double\"/>\n");
+    gexf.push_str("      <attribute id=\"style\" title=\"style\" type=\"string\"/>\n");
+    gexf.push_str("      <attribute id=\"source\" title=\"source\" type=\"string\"/>\n");
+    gexf.push_str("    </attributes>\n");
+
+    gexf.push_str("    <nodes>\n");
+    for node in nodes {
+        gexf.push_str(&format!("      <node id=\"{}\" label=\"{}\">\n", node.id, escape_html(&node.label)));
+        gexf.push_str("        <attvalues>\n");
+        gexf.push_str(&format!("          <attvalue for=\"nodeType\" value=\"{:?}\"/>\n", node.node_type));
+        gexf.push_str(&format!("          <attvalue for=\"confidence\" value=\"{}\"/>\n", node.confidence));
+        gexf.push_str(&format!(
+            "          <attvalue for=\"freshness\" value=\"{:.3}\"/>\n",
+            freshness(node.updated_at, now, freshness_max_age_days)
+        ));
+        if let Some(community) = communities.and_then(|communities| communities.get(&node.id)) {
+            gexf.push_str(&format!("          <attvalue for=\"community\" value=\"{}\"/>\n", community));
+        }
+        gexf.push_str("        </attvalues>\n");
+        gexf.push_str("      </node>\n");
+    }
+    gexf.push_str("    </nodes>\n");
+
+    gexf.push_str("    <edges>\n");
+    for rel in relationships
+        .iter()
+        .filter(|rel| node_ids.contains(&rel.source_id) && node_ids.contains(&rel.target_id))
+    {
+        let edge_type = if rel.relation_type.is_symmetric() { "undirected" } else { "directed" };
+        gexf.push_str(&format!(
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\" type=\"{}\" label=\"{}\" weight=\"{}\">\n",
+            rel.id,
+            rel.source_id,
+            rel.target_id,
+            edge_type,
+            escape_html(&rel.display_label()),
+            rel.weight
+        ));
+        gexf.push_str("        <attvalues>\n");
+        gexf.push_str(&format!("          <attvalue for=\"relationType\" value=\"{:?}\"/>\n", rel.relation_type));
+        gexf.push_str(&format!("          <attvalue for=\"edgeConfidence\" value=\"{}\"/>\n", rel.confidence));
+        gexf.push_str(&format!(
+            "          <attvalue for=\"style\" value=\"{}\"/>\n",
+            EdgeStyle::from_confidence(rel.confidence).as_str()
+        ));
+        if let Some(source) = &rel.source {
+            gexf.push_str(&format!("          <attvalue for=\"source\" value=\"{}\"/>\n", escape_html(source)));
+        }
+        gexf.push_str("        </attvalues>\n");
+        gexf.push_str("      </edge>\n");
+    }
+    gexf.push_str("    </edges>\n");
+
+    gexf.push_str("  </graph>\n");
+    gexf.push_str("</gexf>\n");
+    gexf
+}
+
+/// Escapes a string for use inside a single-quoted Cypher string literal
+fn escape_cypher(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Upper-snake-cases a variant name for use as a Cypher relationship type (`SameAs` -> `SAME_AS`)
+fn cypher_relationship_type(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            result.push('_');
+        }
+        result.extend(c.to_uppercase());
+    }
+    result
+}
+
+/// Renders nodes and relationships as a Cypher script for Neo4j and compatible databases
+///
+/// Every node is created with the shared label `Entity` plus its node type
+/// as a second label, and relationships are created by matching their
+/// endpoints on `Entity.id`, so the script can be run with `cypher-shell`
+/// against an empty database. Relationship types are upper snake case
+/// (`TransactsWith` -> `TRANSACTS_WITH`). Relationships whose endpoints are
+/// not among `nodes` are left out.
+///
+/// # Arguments
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+///
+/// # Returns
+/// The Cypher script as a string, one statement per line
+pub fn render_cypher(nodes: &[Node], relationships: &[Relationship]) -> String {
+    let node_ids: HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+    let optional = |name: &str, value: &Option<String>| match value {
+        Some(value) => format!(", {}: '{}'", name, escape_cypher(value)),
+        None => String::new(),
+    };
+
+    let mut cypher = String::new();
+    cypher.push_str("CREATE INDEX entity_id IF NOT EXISTS FOR (n:Entity) ON (n.id);\n");
+
+    for node in nodes {
+        let tags: Vec<String> = node.tags.iter().map(|tag| format!("'{}'", escape_cypher(tag))).collect();
+        cypher.push_str(&format!(
+            "CREATE (:Entity:{:?} {{id: '{}', label: '{}', confidence: {}, tags: [{}]{}{}}});\n",
+            node.node_type,
+            node.id,
+            escape_cypher(&node.label),
+            node.confidence,
+            tags.join(", "),
+            optional("description", &node.description),
+            optional("source", &node.source),
+        ));
+    }
+
+    for rel in relationships
+        .iter()
+        .filter(|rel| node_ids.contains(&rel.source_id) && node_ids.contains(&rel.target_id))
+    {
+        cypher.push_str(&format!(
+            "MATCH (a:Entity {{id: '{}'}}), (b:Entity {{id: '{}'}}) CREATE (a)-[:{} {{id: '{}', weight: {}, confidence: {}{}{}}}]->(b);\n",
+            rel.source_id,
+            rel.target_id,
+            cypher_relationship_type(&format!("{:?}", rel.relation_type)),
+            rel.id,
+            rel.weight,
+            rel.confidence,
+            optional("label", &rel.label),
+            optional("source", &rel.source),
+        ));
+    }
+
+    cypher
+}
+
+/// Fill color and shape used to draw a node type
+///
+/// Colors match the in-app graph view, so exported drawings look familiar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeStyle {
+    /// Fill color as a `#rrggbb` hex string
+    pub color: &'static str,
+    /// Graphviz node shape name
+    pub shape: &'static str,
+}
+
+/// Looks up the drawing style for a node type
+///
+/// This is the single style table shared by graph exports and their legends.
+/// Shapes follow common diagram conventions where one exists: people are
+/// ellipses, organizations boxes, events diamonds, and wallets hexagons.
+pub fn node_style(node_type: &NodeType) -> NodeStyle {
+    let (color, shape) = match node_type {
+        NodeType::Person => ("#3b82f6", "ellipse"),
+        NodeType::Organization => ("#ef4444", "box"),
+        NodeType::CryptoWallet => ("#f59e0b", "hexagon"),
+        NodeType::SocialAccount => ("#8b5cf6", "circle"),
+        NodeType::Domain => ("#10b981", "component"),
+        NodeType::IpAddress => ("#6b7280", "octagon"),
+        NodeType::Email => ("#ec4899", "parallelogram"),
+        NodeType::Phone => ("#06b6d4", "trapezium"),
+        NodeType::Document => ("#84cc16", "note"),
+        NodeType::Event => ("#f97316", "diamond"),
+        NodeType::Custom => ("#9ca3af", "plain"),
+    };
+    NodeStyle { color, shape }
+}
+
+/// Node types present in `nodes`, each listed once and sorted by name
+fn present_node_types(nodes: &[Node]) -> Vec<NodeType> {
+    let mut node_types: Vec<NodeType> = Vec::new();
+    for node in nodes {
+        if !node_types.contains(&node.node_type) {
+            node_types.push(node.node_type.clone());
+        }
+    }
+    node_types.sort_by_key(|node_type| format!("{:?}", node_type));
+    node_types
+}
+
+/// Escapes a string for use inside a double-quoted DOT identifier
+fn escape_dot(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+/// Renders nodes and relationships as a Graphviz DOT digraph
+///
+/// Nodes are filled and shaped by type (see [`node_style`]) and edges use
+/// the confidence [`EdgeStyle`]; symmetric relationship types are drawn
+/// without arrowheads. With `include_legend`, a `cluster_legend` subgraph
+/// lists every node type present alongside its color and shape.
+///
+/// # Arguments
+/// * `nodes` - Nodes to include
+/// * `relationships` - Relationships to include
+/// * `include_legend` - Whether to embed a legend cluster
+///
+/// # Returns
+/// The DOT document as a string
+pub fn render_dot(nodes: &[Node], relationships: &[Relationship], include_legend: bool) -> String {
+    let node_ids: HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+
+    let mut dot = String::new();
+    dot.push_str("digraph investigation {\n");
+    dot.push_str("  node [style=filled, fontcolor=\"#ffffff\"];\n");
+
+    if include_legend {
+        dot.push_str("  subgraph cluster_legend {\n");
+        dot.push_str("    label=\"Legend\";\n");
+        for node_type in present_node_types(nodes) {
+            let style = node_style(&node_type);
+            dot.push_str(&format!(
+                "    \"legend_{name:?}\" [label=\"{name:?}\", fillcolor=\"{}\", shape={}];\n",
+                style.color,
+                style.shape,
+                name = node_type
+            ));
+        }
+        dot.push_str("  }\n");
+    }
+
+    for node in nodes {
+        let style = node_style(&node.node_type);
+        dot.push_str(&format!(
+            "  \"{}\" [label=\"{}\", fillcolor=\"{}\", shape={}];\n",
+            node.id,
+            escape_dot(&node.label),
+            style.color,
+            style.shape
+        ));
+    }
+
+    for rel in relationships
+        .iter()
+        .filter(|rel| node_ids.contains(&rel.source_id) && node_ids.contains(&rel.target_id))
+    {
+        let direction = if rel.relation_type.is_symmetric() { ", dir=none" } else { "" };
+        dot.push_str(&format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\", style={}{}];\n",
+            rel.source_id,
+            rel.target_id,
+            escape_dot(&rel.display_label()),
+            EdgeStyle::from_confidence(rel.confidence).as_str(),
+            direction
+        ));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Renders a legend of the node types present as CSV
+///
+/// Written next to graph exports whose format has no place for styling, so
+/// the type-to-color mapping survives the trip to another tool.
+///
+/// # Arguments
+/// * `nodes` - Nodes whose types should be listed
+///
+/// # Returns
+/// CSV with `NodeType,Color,Shape` columns, one row per type
+pub fn render_legend_csv(nodes: &[Node]) -> String {
+    let mut csv = String::from("NodeType,Color,Shape\n");
+    for node_type in present_node_types(nodes) {
+        let style = node_style(&node_type);
+        csv.push_str(&format!("{:?},{},{}\n", node_type, style.color, style.shape));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::RelationType;
+
+    fn node(node_type: NodeType, label: &str) -> Node {
+        Node::new(node_type, label.to_string())
+    }
+
+    #[test]
+    fn html_report_is_self_contained_and_escaped() {
+        let alice = node(NodeType::Person, "<script>alert(1)</script>");
+        let acme = node(NodeType::Organization, "Acme & Co");
+        let rel = Relationship::new(alice.id, acme.id, RelationType::MemberOf);
+        let html = render_html_report("Case \"42\"", &[alice, acme], &[rel]);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.trim_end().ends_with("</html>"));
+        for header in ["<h2>Summary</h2>", "<h2>Graph</h2>", "<h2>Nodes</h2>", "<h2>Relationships</h2>"] {
+            assert!(html.contains(header), "missing {}", header);
+        }
+        assert!(html.contains("<svg"));
+        assert!(!html.contains("src=") && !html.contains("href=") && !html.contains("<link"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;") && html.contains("Acme &amp; Co"));
+        assert_eq!(html.matches("<table>").count(), html.matches("</table>").count());
+    }
+
+    #[test]
+    fn edge_style_bands_follow_confidence_thresholds() {
+        for (confidence, style) in [
+            (1.0, EdgeStyle::Solid),
+            (SOLID_EDGE_MIN_CONFIDENCE, EdgeStyle::Solid),
+            (0.69, EdgeStyle::Dashed),
+            (DASHED_EDGE_MIN_CONFIDENCE, EdgeStyle::Dashed),
+            (0.39, EdgeStyle::Dotted),
+            (0.0, EdgeStyle::Dotted),
+        ] {
+            assert_eq!(EdgeStyle::from_confidence(confidence), style, "confidence {}", confidence);
+        }
+
+        let a = node(NodeType::Person, "A");
+        let b = node(NodeType::Person, "B");
+        let rel = Relationship::new(a.id, b.id, RelationType::ConnectedTo).with_confidence(0.5);
+        let graphml = render_graphml(&[a, b], &[rel], None, 30);
+        assert!(graphml.contains(">dashed<") && graphml.contains(">0.5<"));
+    }
+
+    #[test]
+    fn networkx_links_reference_listed_nodes() {
+        let a = node(NodeType::Person, "A");
+        let b = node(NodeType::Organization, "B");
+        let rel = Relationship::new(a.id, b.id, RelationType::MemberOf).with_weight(2.0);
+        let json = render_networkx_json(&[a, b], &[rel]);
+
+        let parsed: serde_json::Value = serde_json::from_str(&json.to_string()).unwrap();
+        assert_eq!(parsed["directed"], true);
+        let ids: HashSet<&str> = parsed["nodes"].as_array().unwrap().iter().map(|node| node["id"].as_str().unwrap()).collect();
+        assert_eq!(ids.len(), 2);
+        let links = parsed["links"].as_array().unwrap();
+        assert_eq!(links.len(), 1);
+        for end in ["source", "target"] {
+            assert!(ids.contains(links[0][end].as_str().unwrap()));
+        }
+        assert_eq!(links[0]["weight"], 2.0);
+    }
+
+    #[test]
+    fn parallel_edges_are_numbered_per_pair() {
+        let a = node(NodeType::Person, "A");
+        let b = node(NodeType::Person, "B");
+        let c = node(NodeType::Person, "C");
+        let rels = vec![
+            Relationship::new(a.id, b.id, RelationType::TransactsWith),
+            Relationship::new(b.id, a.id, RelationType::ConnectedTo),
+            Relationship::new(a.id, c.id, RelationType::ConnectedTo),
+            Relationship::new(a.id, b.id, RelationType::RelatedTo),
+        ];
+        let indices = parallel_indices(&rels);
+
+        assert_eq!(indices[&rels[0].id], 0);
+        assert_eq!(indices[&rels[1].id], 1);
+        assert_eq!(indices[&rels[3].id], 2);
+        assert_eq!(indices[&rels[2].id], 0);
+    }
+
+    #[test]
+    fn turtle_has_a_type_per_node_and_a_triple_per_relationship() {
+        let alice = node(NodeType::Person, "Alice \"Al\"\nSmith");
+        let acme = node(NodeType::Organization, "Acme");
+        let wallet = node(NodeType::CryptoWallet, "bc1q");
+        let rels = vec![
+            Relationship::new(alice.id, acme.id, RelationType::MemberOf),
+            Relationship::new(alice.id, wallet.id, RelationType::Owns),
+            Relationship::new(alice.id, Uuid::new_v4(), RelationType::SameAs),
+        ];
+        let turtle = render_turtle(&[alice.clone(), acme.clone(), wallet], &rels);
+
+        assert_eq!(turtle.matches(" rdf:type osint:").count(), 3);
+        assert!(turtle.contains(&format!("<urn:uuid:{}> rdf:type osint:Person ;", alice.id)));
+        assert!(turtle.contains("rdfs:label \"Alice \\\"Al\\\"\\nSmith\" ;"));
+        let relationship_triples: Vec<&str> = turtle
+            .lines()
+            .filter(|line| line.starts_with("<urn:uuid:") && !line.contains("rdf:type"))
+            .collect();
+        assert_eq!(relationship_triples.len(), 2);
+        assert!(relationship_triples.contains(&format!("<urn:uuid:{}> osint:memberOf <urn:uuid:{}> .", alice.id, acme.id).as_str()));
+        assert!(turtle.lines().filter(|line| line.starts_with('@')).all(|line| line.ends_with(" .")));
+    }
+
+    #[test]
+    fn sanitized_filenames_are_safe_and_distinct() {
+        let mut taken = HashSet::new();
+        let traversal = sanitize_filename("../etc/passwd", Uuid::new_v4(), &mut taken);
+        let device = sanitize_filename("CON", Uuid::new_v4(), &mut taken);
+        let device_with_extension = sanitize_filename("nul.txt", Uuid::new_v4(), &mut taken);
+
+        assert_eq!(traversal, "_etc_passwd");
+        assert_eq!(device, "_CON");
+        assert_eq!(device_with_extension, "_nul.txt");
+
+        let id = Uuid::new_v4();
+        let collision = sanitize_filename("con", id, &mut taken);
+        assert_eq!(collision, format!("_con-{}", &id.simple().to_string()[..8]));
+        let long = sanitize_filename(&"x".repeat(500), Uuid::new_v4(), &mut taken);
+        assert_eq!(long.chars().count(), MAX_FILENAME_CHARS);
+    }
+
+    #[test]
+    fn older_nodes_export_lower_freshness() {
+        let now = Utc::now();
+        assert_eq!(freshness(now, now, 365), 1.0);
+        assert_eq!(freshness(now - chrono::Duration::days(73), now, 365), 0.8);
+        assert_eq!(freshness(now - chrono::Duration::days(1000), now, 365), 0.0);
+        assert_eq!(freshness(now, now, 0), 0.0);
+
+        let fresh = node(NodeType::Person, "Fresh");
+        let mut stale = node(NodeType::Person, "Stale");
+        stale.updated_at = now - chrono::Duration::days(300);
+        let graphml = render_graphml(&[fresh, stale], &[], None, 365);
+        let values: Vec<f64> = graphml
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("<data key=\"freshness\">"))
+            .map(|value| value.trim_end_matches("</data>").parse().unwrap())
+            .collect();
+        assert_eq!(values.len(), 2);
+        assert!(values[1] < values[0]);
+        assert!((values[1] - 65.0 / 365.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn dot_legend_lists_each_type_present_once() {
+        let nodes = [
+            node(NodeType::Person, "Alice"),
+            node(NodeType::Person, "Bob"),
+            node(NodeType::CryptoWallet, "bc1q"),
+        ];
+        assert!(!render_dot(&nodes, &[], false).contains("cluster_legend"));
+
+        let dot = render_dot(&nodes, &[], true);
+        let legend_start = dot.find("subgraph cluster_legend {").unwrap();
+        let legend = &dot[legend_start..legend_start + dot[legend_start..].find("  }\n").unwrap()];
+        let entries: Vec<&str> = legend.lines().filter(|line| line.contains("\"legend_")).collect();
+        assert_eq!(
+            entries,
+            vec![
+                "    \"legend_CryptoWallet\" [label=\"CryptoWallet\", fillcolor=\"#f59e0b\", shape=hexagon];",
+                "    \"legend_Person\" [label=\"Person\", fillcolor=\"#3b82f6\", shape=ellipse];",
+            ]
+        );
+        assert_eq!(
+            render_legend_csv(&nodes),
+            "NodeType,Color,Shape\nCryptoWallet,#f59e0b,hexagon\nPerson,#3b82f6,ellipse\n"
+        );
+    }
+
+    #[test]
+    fn edge_labels_fall_back_to_the_relation_type() {
+        let a = node(NodeType::Person, "A");
+        let b = node(NodeType::Person, "B");
+        let labelled = Relationship::new(a.id, b.id, RelationType::TransactsWith).with_label("wired $5k".to_string());
+        let plain = Relationship::new(b.id, a.id, RelationType::TransactsWith);
+        let nodes = [a, b];
+        let rels = [labelled, plain];
+
+        let json = render_networkx_json(&nodes, &rels);
+        let labels: Vec<&str> = json["links"].as_array().unwrap().iter().map(|link| link["label"].as_str().unwrap()).collect();
+        assert_eq!(labels, vec!["wired $5k", "TransactsWith"]);
+
+        let graphml = render_graphml(&nodes, &rels, None, 365);
+        assert!(graphml.contains("<data key=\"edgeLabel\">wired $5k</data>"));
+        assert!(graphml.contains("<data key=\"edgeLabel\">TransactsWith</data>"));
+
+        let html = render_html_report("Case", &nodes, &rels);
+        assert!(html.contains("<td>TransactsWith</td><td>wired $5k</td>"));
+    }
+
+    #[test]
+    fn svg_has_one_circle_per_node_and_escaped_labels() {
+        let a = node(NodeType::Person, "Tom & <Jerry>");
+        let b = node(NodeType::Organization, "Acme");
+        let c = node(NodeType::Domain, "example.com");
+        let rels = [
+            Relationship::new(a.id, b.id, RelationType::MemberOf),
+            Relationship::new(b.id, Uuid::new_v4(), RelationType::Owns),
+        ];
+        let svg = render_svg(&[a, b, c], &rels);
+
+        assert!(svg.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<svg").count(), 1);
+        assert_eq!(svg.matches("<circle ").count(), 3);
+        assert_eq!(svg.matches("<line ").count(), 1);
+        assert_eq!(svg.matches("<text ").count(), svg.matches("</text>").count());
+        assert!(svg.contains(">Tom &amp; &lt;Jerry&gt;</text>"));
+        assert!(!svg.contains("<Jerry>"));
+    }
+
+    #[test]
+    fn dot_nodes_are_shaped_by_type() {
+        let org = node(NodeType::Organization, "Acme");
+        let person = node(NodeType::Person, "Alice");
+        let event = node(NodeType::Event, "Meeting");
+        let dot = render_dot(&[org.clone(), person.clone(), event.clone()], &[], false);
+
+        let line = |node: &Node| dot.lines().find(|line| line.contains(&node.id.to_string())).unwrap().to_string();
+        assert!(line(&org).ends_with("fillcolor=\"#ef4444\", shape=box];"));
+        assert!(line(&person).ends_with("fillcolor=\"#3b82f6\", shape=ellipse];"));
+        assert!(line(&event).ends_with("shape=diamond];"));
+        assert_eq!(node_style(&NodeType::CryptoWallet).shape, "hexagon");
+    }
+}
@@ -0,0 +1,210 @@
+//! # Headless Mode
+//!
+//! Runs OSINT Studio without the GUI so investigations can be scripted from
+//! pipelines and CI jobs. Started with the `--headless` flag.
+//!
+//! ## Protocol
+//!
+//! Requests are read from stdin as newline-delimited JSON, one object per line:
+//!
+//! ```json
+//! {"id": 1, "command": "create_node", "args": {"request": {"node_type": "Person", "label": "Alice", "tags": []}}}
+//! ```
+//!
+//! Each request produces exactly one response line on stdout:
+//!
+//! ```json
+//! {"id": 1, "ok": true, "result": "3f2c...", "error": null}
+//! ```
+//!
+//! `id` is optional and echoed back unchanged. `args` uses the same argument
+//! names as the Tauri commands, in snake_case. Blank lines are ignored, and
+//! a line that is not valid JSON gets an error response with a null `id`.
+//!
+//! ## Commands
+//!
+//! `create_node`, `get_node`, `get_all_nodes`, `search_nodes`, `delete_node`,
+//! `create_relationship`, `get_relationships`, `get_node_relationships`,
+//! `save_project`, `load_project`, `export`, `clear_all_data`
+
+use crate::database::Database;
+use crate::{CreateNodeRequest, CreateRelationshipRequest};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, Write};
+use uuid::Uuid;
+
+/// A single command read from stdin
+#[derive(Debug, Deserialize)]
+pub struct HeadlessRequest {
+    /// Caller-chosen request identifier, echoed in the response
+    #[serde(default)]
+    pub id: Value,
+    /// Name of the command to run
+    pub command: String,
+    /// Command arguments keyed by parameter name
+    #[serde(default)]
+    pub args: Value,
+}
+
+/// The response written to stdout for each request
+#[derive(Debug, Serialize)]
+pub struct HeadlessResponse {
+    /// Identifier of the request this answers
+    pub id: Value,
+    /// Whether the command succeeded
+    pub ok: bool,
+    /// Command result on success
+    pub result: Option<Value>,
+    /// Error message on failure
+    pub error: Option<String>,
+}
+
+impl HeadlessResponse {
+    fn from_result(id: Value, result: Result<Value, String>) -> Self {
+        match result {
+            Ok(value) => HeadlessResponse { id, ok: true, result: Some(value), error: None },
+            Err(error) => HeadlessResponse { id, ok: false, result: None, error: Some(error) },
+        }
+    }
+}
+
+/// Reads requests from `input` until EOF and writes one response per request to `output`
+///
+/// # Arguments
+/// * `state` - Database the commands operate on
+/// * `input` - Source of newline-delimited JSON requests
+/// * `output` - Destination for newline-delimited JSON responses
+///
+/// # Returns
+/// * `Ok(())` - Input was exhausted
+/// * `Err(std::io::Error)` - Reading or writing failed
+pub fn serve<R: BufRead, W: Write>(state: &Database, input: R, mut output: W) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<HeadlessRequest>(&line) {
+            Ok(request) => {
+                let result = dispatch(state, &request.command, request.args);
+                HeadlessResponse::from_result(request.id, result)
+            }
+            Err(e) => HeadlessResponse::from_result(Value::Null, Err(format!("Invalid request: {}", e))),
+        };
+
+        serde_json::to_writer(&mut output, &response)?;
+        output.write_all(b"\n")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Runs a single command against the database
+fn dispatch(state: &Database, command: &str, args: Value) -> Result<Value, String> {
+    match command {
+        "create_node" => {
+            let request: CreateNodeRequest = arg(&args, "request")?;
+            to_value(crate::insert_node(state, request)?)
+        }
+        "get_node" => {
+            let id = parse_id(&args, "id")?;
+            to_value(state.get_node(id).map_err(|e| e.to_string())?)
+        }
+        "get_all_nodes" => to_value(state.get_all_nodes().map_err(|e| e.to_string())?),
+        "search_nodes" => {
+            let query: String = arg(&args, "query")?;
+            to_value(state.search_nodes(&query).map_err(|e| e.to_string())?)
+        }
+        "delete_node" => {
+            let id = parse_id(&args, "id")?;
+            to_value(state.delete_node(id).map_err(|e| e.to_string())?)
+        }
+        "create_relationship" => {
+            let request: CreateRelationshipRequest = arg(&args, "request")?;
+            to_value(crate::insert_relationship(state, request)?)
+        }
+        "get_relationships" => to_value(state.get_relationships().map_err(|e| e.to_string())?),
+        "get_node_relationships" => {
+            let node_id = parse_id(&args, "node_id")?;
+            to_value(state.get_node_relationships(node_id).map_err(|e| e.to_string())?)
+        }
+        "save_project" => {
+            let file_path: String = arg(&args, "file_path")?;
+            let project_name: String = arg(&args, "project_name")?;
+            let include_attachments: Option<bool> = arg(&args, "include_attachments")?;
+            to_value(crate::write_project(state, &file_path, project_name, include_attachments.unwrap_or(false))?)
+        }
+        "load_project" => {
+            let file_path: String = arg(&args, "file_path")?;
+            let dry_run: Option<bool> = arg(&args, "dry_run")?;
+            let create_stubs: Option<bool> = arg(&args, "create_stubs")?;
+            to_value(crate::read_project(state, &file_path, dry_run.unwrap_or(false), create_stubs.unwrap_or(false))?)
+        }
+        "export" => {
+            let file_path: String = arg(&args, "file_path")?;
+            let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+            let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+            to_value(crate::write_export(state, nodes, relationships, &file_path)?)
+        }
+        "clear_all_data" => to_value(state.clear_all().map_err(|e| e.to_string())?),
+        _ => Err(format!("Unknown command \"{}\"", command)),
+    }
+}
+
+/// Deserializes a named argument, treating a missing argument as JSON null
+fn arg<T: DeserializeOwned>(args: &Value, name: &str) -> Result<T, String> {
+    let value = args.get(name).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value).map_err(|e| format!("Invalid argument \"{}\": {}", name, e))
+}
+
+/// Reads a named argument as a UUID
+fn parse_id(args: &Value, name: &str) -> Result<Uuid, String> {
+    let id: String = arg(args, name)?;
+    Uuid::parse_str(&id).map_err(|e| e.to_string())
+}
+
+/// Serializes a command result for the response envelope
+fn to_value<T: Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `input` through the headless loop and parses each response line
+    fn run(state: &Database, input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        serve(state, input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn create_node_over_stdin_returns_a_uuid() {
+        let db = Database::new();
+        let input = concat!(
+            r#"{"id": 1, "command": "create_node", "args": {"request": {"node_type": "Person", "label": "Alice", "tags": []}}}"#,
+            "\n\n",
+            "not json\n",
+            r#"{"id": "x", "command": "nope"}"#,
+            "\n",
+        );
+        let responses = run(&db, input);
+        assert_eq!(responses.len(), 3);
+
+        assert_eq!((&responses[0]["id"], &responses[0]["ok"]), (&serde_json::json!(1), &Value::Bool(true)));
+        let id = Uuid::parse_str(responses[0]["result"].as_str().unwrap()).unwrap();
+        assert_eq!(db.get_node(id).unwrap().unwrap().label, "Alice");
+
+        assert_eq!((&responses[1]["id"], &responses[1]["ok"]), (&Value::Null, &Value::Bool(false)));
+        assert_eq!(responses[2]["id"], "x");
+        assert!(responses[2]["error"].as_str().unwrap().contains("Unknown command"));
+    }
+}
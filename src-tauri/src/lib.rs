@@ -34,6 +34,7 @@
 //! - Phone
 //! - Document
 //! - Event
+//! - Custom
 //!
 //! ## Relationship Types
 //!
@@ -50,13 +51,29 @@
 
 mod entities;
 mod database;
+mod export;
+mod crypto;
+mod query;
+mod headless;
 
-use database::Database;
-use entities::{Node, NodeType, Relationship, RelationType};
+use database::{
+    AuditEntry, BulkConfidenceUpdate, BulkRemovalReport, ClusterConfidence, CompactionReport, ConfidenceAggregation,
+    Database, DegreeCentrality, DirectionCounts, GrowthPoint, HierarchyValidation, HistogramBucket,
+    IdentifierDuplicateGroup, LabelSuggestion, MergePolicy, MetadataComparator, NodeDeletionPreview,
+    NodeMerge, NodeSetComparison, NodeSortField, PairInteractionSummary, PathConstraints,
+    PathLengthStats, ProjectNotes, Provenance, QualityReport, RelationConfidenceStats, RelationshipDetail,
+    SameAsInference, SameAsMergeReport, SearchContext, SearchResult, Settings, SimilarityScore, SourceCount,
+    SourceMerge, SourceMismatch, TimeBucket,
+};
+use entities::{
+    classify_indicator, merge_metadata, normalize_tags, Node, NodeType, Relationship, RelationType,
+    MAX_RELATIONSHIP_WEIGHT,
+};
 use std::sync::Arc;
 use tauri::State;
 use uuid::Uuid;
 use base64::prelude::*;
+use sha2::{Digest, Sha256};
 
 /// Shared application state containing the database instance
 type AppState = Arc<Database>;
@@ -72,6 +89,23 @@ struct ProjectData {
     relationships: Vec<Relationship>,
     /// Project metadata and information
     metadata: ProjectMetadata,
+    /// Evidence files attached to nodes (absent in files saved before attachments were tracked)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<ProjectAttachment>,
+    /// Change history of the project (absent in files saved before it was persisted)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    audit_log: Vec<AuditEntry>,
+}
+
+/// An attachment entry in a project file
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ProjectAttachment {
+    /// Which node the attachment belongs to, and how to verify it
+    #[serde(flatten)]
+    meta: AttachmentMeta,
+    /// Base64-encoded file content, present only when saved with content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_base64: Option<String>,
 }
 
 /// Metadata for investigation projects
@@ -85,6 +119,46 @@ struct ProjectMetadata {
     updated_at: String,
     /// Project format version
     version: String,
+    /// Free-form case notes (empty in files saved before notes existed)
+    #[serde(default)]
+    notes: String,
+    /// Short case summary
+    #[serde(default)]
+    case_summary: String,
+}
+
+/// Record of a node whose type was migrated while loading
+#[derive(serde::Serialize, serde::Deserialize)]
+struct NodeTypeMigration {
+    /// ID of the migrated node (as found in the file)
+    node_id: String,
+    /// Node type string found in the file
+    original_type: String,
+    /// Node type the node was loaded as
+    migrated_to: String,
+}
+
+/// Outcome of loading (or validating) a project file
+#[derive(serde::Serialize, serde::Deserialize)]
+struct LoadReport {
+    /// Project metadata read from the file
+    metadata: ProjectMetadata,
+    /// Number of nodes loaded, or that would be loaded in a dry run
+    nodes: usize,
+    /// Number of relationships loaded, or that would be loaded in a dry run
+    relationships: usize,
+    /// Problems found in the file that did not prevent loading
+    warnings: Vec<String>,
+    /// Nodes whose unknown or legacy type was mapped to `Custom`
+    type_migrations: Vec<NodeTypeMigration>,
+    /// Number of attachment entries in the file
+    attachments: usize,
+    /// Relationships dropped because an endpoint node was missing from the file
+    dangling_relationships_dropped: usize,
+    /// Placeholder nodes created for missing endpoints instead of dropping relationships
+    stub_nodes_created: usize,
+    /// Whether this was a dry run that left the database untouched
+    dry_run: bool,
 }
 
 /// File attachment data structure
@@ -104,6 +178,98 @@ struct AttachmentData {
     content_base64: String,
 }
 
+/// File attachment metadata without the file content
+///
+/// Returned by `list_attachments_meta` so large evidence files are not read
+/// into memory just to be listed. Use `get_attachment_content` to fetch the
+/// bytes when an attachment is actually viewed.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AttachmentMeta {
+    /// Unique attachment identifier
+    id: String,
+    /// ID of the node this attachment belongs to
+    node_id: String,
+    /// Original filename
+    filename: String,
+    /// File type/extension
+    file_type: String,
+    /// File size in bytes
+    size: u64,
+    /// Hex-encoded SHA-256 hash of the file content
+    sha256: String,
+}
+
+/// A node as returned to the frontend, with derived attachment information
+#[derive(serde::Serialize, serde::Deserialize)]
+struct NodeView {
+    /// The stored node
+    #[serde(flatten)]
+    node: Node,
+    /// Number of files attached to the node, computed from the attachments directory
+    attachment_count: usize,
+}
+
+/// Subgraph stored in a mini-case bundle
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MiniCaseSubgraph {
+    /// The selected nodes
+    nodes: Vec<Node>,
+    /// Relationships whose endpoints are both selected
+    relationships: Vec<Relationship>,
+}
+
+/// Manifest describing the contents of a mini-case bundle
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MiniCaseManifest {
+    /// Bundle format version
+    version: String,
+    /// When the bundle was exported (ISO 8601 format)
+    exported_at: String,
+    /// Number of nodes in the subgraph
+    nodes: usize,
+    /// Number of relationships in the subgraph
+    relationships: usize,
+    /// Stored attachment filenames included under `attachments/`
+    attachments: Vec<String>,
+}
+
+/// Manifest describing the contents of a full project archive
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ArchiveManifest {
+    /// Archive format version
+    version: String,
+    /// When the archive was exported (ISO 8601 format)
+    exported_at: String,
+    /// Number of nodes in `project.json`
+    nodes: usize,
+    /// Number of relationships in `project.json`
+    relationships: usize,
+    /// Attachments stored under `attachments/`, with their SHA-256 hashes
+    attachments: Vec<AttachmentMeta>,
+}
+
+/// Result of importing a mini-case bundle
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MiniCaseImportReport {
+    /// Number of nodes added
+    nodes: usize,
+    /// Number of relationships added
+    relationships: usize,
+    /// Number of attachment files restored
+    attachments: usize,
+    /// Nodes and relationships skipped because their ID already exists, and
+    /// relationships skipped because an endpoint is in neither the bundle nor the investigation
+    skipped: usize,
+    /// Attachments not restored because their node was skipped
+    attachments_skipped: usize,
+    /// Attachment files not restored because an attachment with the same ID already exists
+    attachment_conflicts: Vec<String>,
+    /// How node types in the bundle were mapped; unknown types become `Custom`
+    type_mapping: TypeMappingReport,
+    /// Relationship limit warnings for nodes that went over the configured limit
+    warnings: Vec<String>,
+}
+
 /// Request structure for creating new nodes
 #[derive(serde::Serialize, serde::Deserialize)]
 struct CreateNodeRequest {
@@ -115,6 +281,126 @@ struct CreateNodeRequest {
     description: Option<String>,
     /// Tags for categorization
     tags: Vec<String>,
+    /// Optional data source reference
+    source: Option<String>,
+    /// Optional original creation time, for importing historical records
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Optional original update time; defaults to `created_at` when that is given
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Optional expiry time, after which `purge_expired` removes the node
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Optional confidence score (0.0-1.0); defaults to the node default
+    confidence: Option<f32>,
+    /// Optional initial metadata object
+    metadata: Option<serde_json::Value>,
+}
+
+/// Number of imported records of one source type that became one node type
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TypeMappingCount {
+    /// Type as found in the import source
+    source_type: String,
+    /// Node type the records were imported as, or `None` if they were skipped
+    node_type: Option<String>,
+    /// Number of records mapped this way
+    count: usize,
+}
+
+/// How an import interpreted the types in its source
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+struct TypeMappingReport {
+    /// One entry per (source type, node type) combination, sorted by source type
+    mappings: Vec<TypeMappingCount>,
+    /// Records that fell back to `Custom` or were skipped as unknown
+    fallbacks: usize,
+}
+
+impl TypeMappingReport {
+    /// Counts one imported record
+    ///
+    /// # Arguments
+    /// * `source_type` - Type as found in the source
+    /// * `node_type` - Resulting node type, or `None` if the record was skipped
+    /// * `fallback` - Whether the type was not recognized
+    fn record(&mut self, source_type: &str, node_type: Option<&NodeType>, fallback: bool) {
+        let node_type = node_type.map(|node_type| format!("{:?}", node_type));
+        match self
+            .mappings
+            .iter_mut()
+            .find(|mapping| mapping.source_type == source_type && mapping.node_type == node_type)
+        {
+            Some(mapping) => mapping.count += 1,
+            None => {
+                let position = self.mappings.partition_point(|mapping| {
+                    (mapping.source_type.as_str(), &mapping.node_type) <= (source_type, &node_type)
+                });
+                self.mappings.insert(
+                    position,
+                    TypeMappingCount { source_type: source_type.to_string(), node_type, count: 1 },
+                );
+            }
+        }
+        if fallback {
+            self.fallbacks += 1;
+        }
+    }
+}
+
+/// Result of importing a list of indicators
+#[derive(serde::Serialize, serde::Deserialize)]
+struct IndicatorImportReport {
+    /// IDs of the created nodes, in input order
+    created: Vec<String>,
+    /// Lines that could not be classified and were skipped
+    unclassified: Vec<String>,
+    /// How each kind of indicator was mapped to a node type
+    type_mapping: TypeMappingReport,
+}
+
+/// Declares how fields of flat JSON records become node fields
+///
+/// Field names refer to top-level keys of each record.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FieldMapping {
+    /// Field holding the node label (required in every record)
+    label: String,
+    /// Field holding the node type
+    node_type: Option<String>,
+    /// Translations from raw type values to node type names, e.g. `"company" -> "Organization"`
+    #[serde(default)]
+    type_values: std::collections::HashMap<String, String>,
+    /// Node type used when the type field is absent or unrecognized (default `Custom`)
+    default_node_type: Option<String>,
+    /// Field holding tags, as an array or a comma-separated string
+    tags: Option<String>,
+    /// Field holding the confidence, as a number or numeric string
+    confidence: Option<String>,
+    /// Field holding the description
+    description: Option<String>,
+    /// Field holding the source reference
+    source: Option<String>,
+    /// Fields copied into metadata; when omitted, every unmapped field is copied
+    metadata: Option<Vec<String>>,
+}
+
+/// A record that `import_mapped` could not import
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SkippedRecord {
+    /// Position of the record in the input
+    index: usize,
+    /// Why it was skipped
+    reason: String,
+}
+
+/// Result of importing mapped records
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MappedImportReport {
+    /// IDs of the created nodes, in input order
+    created: Vec<String>,
+    /// Records that were not imported
+    skipped: Vec<SkippedRecord>,
+    /// How raw type values were mapped to node types
+    type_mapping: TypeMappingReport,
 }
 
 /// Request structure for updating existing nodes
@@ -130,6 +416,13 @@ struct UpdateNodeRequest {
     tags: Vec<String>,
     /// Confidence score (0.0 to 1.0)
     confidence: f64,
+    /// New source reference (unchanged if omitted)
+    source: Option<String>,
+    /// New expiry time (unchanged if omitted)
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Removes the node's expiry; takes precedence over `expires_at`
+    #[serde(default)]
+    clear_expiry: bool,
 }
 
 /// Request structure for creating relationships between nodes
@@ -145,8 +438,78 @@ struct CreateRelationshipRequest {
     description: Option<String>,
     /// Optional confidence score (0.0 to 1.0)
     confidence: Option<f32>,
+    /// Optional weight (must be non-negative, defaults to 1.0)
+    weight: Option<f32>,
     /// Optional data source reference
     source: Option<String>,
+    /// Optional short edge label, separate from the description
+    label: Option<String>,
+    /// Optional key making retries safe: a repeated key returns the existing relationship
+    idempotency_key: Option<String>,
+    /// Merge into an existing relationship with the same endpoints and type instead of
+    /// adding a parallel one, using the configured merge policy (default false)
+    merge_duplicates: Option<bool>,
+    /// Allow a relationship from a node to itself, e.g. a self-transaction (default false)
+    allow_self_loop: Option<bool>,
+    /// Optional original creation time, for importing historical records
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Optional original update time; defaults to `created_at` when that is given
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Result of creating an event hyperedge
+#[derive(serde::Serialize, serde::Deserialize)]
+struct HyperedgeResult {
+    /// Group ID shared by every relationship in the hyperedge
+    group_id: String,
+    /// IDs of the created relationships, one per participant
+    relationship_ids: Vec<String>,
+    /// Relationship limit warnings for nodes that went over the configured limit
+    warnings: Vec<String>,
+}
+
+/// Result of creating a single relationship
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RelationshipCreated {
+    /// UUID of the created (or merged-into) relationship
+    id: String,
+    /// Relationship limit warnings for endpoints that went over the configured limit
+    warnings: Vec<String>,
+}
+
+/// How a metadata key that holds node IDs maps to relationships
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MetadataRelationRule {
+    /// Type of relationship to create
+    relation_type: String,
+    /// When true the edge points from the referenced node to the node holding
+    /// the key (e.g. `owner_id` -> owner `Owns` wallet); otherwise the reverse
+    #[serde(default)]
+    reverse: bool,
+}
+
+/// A metadata reference whose target could not be found
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MissingReference {
+    /// Node holding the reference
+    node_id: String,
+    /// Metadata key the reference was found under
+    key: String,
+    /// Referenced value that is not the ID of an existing node
+    value: String,
+}
+
+/// Result of inferring relationships from metadata references
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MetadataInferenceReport {
+    /// IDs of the created relationships
+    created: Vec<String>,
+    /// References that already had a matching relationship
+    existing: usize,
+    /// References whose target node doesn't exist
+    missing: Vec<MissingReference>,
+    /// Relationship limit warnings for nodes that went over the configured limit
+    warnings: Vec<String>,
 }
 
 /// Request structure for updating existing relationships
@@ -158,12 +521,102 @@ struct UpdateRelationshipRequest {
     relation_type: String,
     /// New description
     description: Option<String>,
-    /// New weight value
+    /// New weight value (must be non-negative; values above the maximum are clamped)
     weight: f64,
-    /// New confidence score
+    /// New confidence score, clamped to 0.0-1.0 (unchanged if omitted)
     confidence: Option<f32>,
-    /// New source reference
+    /// New source reference (unchanged if omitted)
     source: Option<String>,
+    /// New edge label (unchanged if omitted; an empty string clears it)
+    label: Option<String>,
+}
+
+/// How far in the future an overridden timestamp may lie, to allow for clock skew
+const FUTURE_TIMESTAMP_TOLERANCE_MINUTES: i64 = 5;
+
+/// A `(created_at, updated_at)` pair
+type TimestampPair = (chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>);
+
+/// Validates optional `created_at`/`updated_at` overrides from a creation request
+///
+/// # Arguments
+/// * `created_at` - Requested creation time
+/// * `updated_at` - Requested update time
+///
+/// # Returns
+/// * `Ok(Some((created_at, updated_at)))` - Timestamps to store, with
+///   `updated_at` defaulting to `created_at` and `created_at` to now
+/// * `Ok(None)` - If neither override was given
+/// * `Err(String)` - Error message if a timestamp is in the future or
+///   `updated_at` precedes `created_at`
+fn resolve_timestamp_overrides(
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Option<TimestampPair>, String> {
+    if created_at.is_none() && updated_at.is_none() {
+        return Ok(None);
+    }
+
+    let now = chrono::Utc::now();
+    let latest_allowed = now + chrono::Duration::minutes(FUTURE_TIMESTAMP_TOLERANCE_MINUTES);
+    let created_at = created_at.unwrap_or(now);
+    let updated_at = updated_at.unwrap_or(created_at);
+
+    if created_at > latest_allowed || updated_at > latest_allowed {
+        return Err("Timestamps must not be in the future".to_string());
+    }
+    if updated_at < created_at {
+        return Err("updated_at must not be earlier than created_at".to_string());
+    }
+    Ok(Some((created_at, updated_at)))
+}
+
+/// Parses a node type name as sent by the frontend
+///
+/// # Arguments
+/// * `value` - Node type name (e.g. "Person", "CryptoWallet")
+///
+/// # Returns
+/// * `Ok(NodeType)` - The matching node type
+/// * `Err(String)` - Error message if the name is not a known type
+fn parse_node_type(value: &str) -> Result<NodeType, String> {
+    match value {
+        "Person" => Ok(NodeType::Person),
+        "Organization" => Ok(NodeType::Organization),
+        "CryptoWallet" => Ok(NodeType::CryptoWallet),
+        "SocialAccount" => Ok(NodeType::SocialAccount),
+        "Domain" => Ok(NodeType::Domain),
+        "IpAddress" => Ok(NodeType::IpAddress),
+        "Email" => Ok(NodeType::Email),
+        "Phone" => Ok(NodeType::Phone),
+        "Document" => Ok(NodeType::Document),
+        "Event" => Ok(NodeType::Event),
+        "Custom" => Ok(NodeType::Custom),
+        _ => Err("Invalid node type".to_string()),
+    }
+}
+
+/// Parses a relationship type name as sent by the frontend
+///
+/// # Arguments
+/// * `value` - Relationship type name (e.g. "Owns", "SameAs")
+///
+/// # Returns
+/// * `Ok(RelationType)` - The matching relationship type
+/// * `Err(String)` - Error message if the name is not a known type
+fn parse_relation_type(value: &str) -> Result<RelationType, String> {
+    match value {
+        "Owns" => Ok(RelationType::Owns),
+        "Controls" => Ok(RelationType::Controls),
+        "TransactsWith" => Ok(RelationType::TransactsWith),
+        "MemberOf" => Ok(RelationType::MemberOf),
+        "ConnectedTo" => Ok(RelationType::ConnectedTo),
+        "SameAs" => Ok(RelationType::SameAs),
+        "RelatedTo" => Ok(RelationType::RelatedTo),
+        "ParentOf" => Ok(RelationType::ParentOf),
+        "ChildOf" => Ok(RelationType::ChildOf),
+        _ => Err("Invalid relationship type".to_string()),
+    }
 }
 
 /// Creates a new investigation node
@@ -177,29 +630,59 @@ struct UpdateRelationshipRequest {
 /// * `Err(String)` - Error message if creation fails
 #[tauri::command]
 fn create_node(state: State<AppState>, request: CreateNodeRequest) -> Result<String, String> {
-    let node_type = match request.node_type.as_str() {
-        "Person" => NodeType::Person,
-        "Organization" => NodeType::Organization,
-        "CryptoWallet" => NodeType::CryptoWallet,
-        "SocialAccount" => NodeType::SocialAccount,
-        "Domain" => NodeType::Domain,
-        "IpAddress" => NodeType::IpAddress,
-        "Email" => NodeType::Email,
-        "Phone" => NodeType::Phone,
-        "Document" => NodeType::Document,
-        "Event" => NodeType::Event,
-        _ => return Err("Invalid node type".to_string()),
-    };
+    insert_node(&state, request)
+}
 
+/// Builds a node from a creation request and stores it
+///
+/// Shared by the `create_node` command and headless mode.
+///
+/// # Arguments
+/// * `state` - Database to insert into
+/// * `request` - Node creation request
+///
+/// # Returns
+/// * `Ok(String)` - The UUID of the created node
+/// * `Err(String)` - Error message if the type is invalid or creation fails
+fn insert_node(state: &Database, request: CreateNodeRequest) -> Result<String, String> {
+    let node_type = parse_node_type(&request.node_type)?;
+    let timestamps = resolve_timestamp_overrides(request.created_at, request.updated_at)?;
+
+    let settings = state.settings().map_err(|e| e.to_string())?;
     let mut node = Node::new(node_type, request.label);
     
     if let Some(desc) = request.description {
         node = node.with_description(desc);
     }
     
-    if !request.tags.is_empty() {
-        node = node.with_tags(request.tags);
+    let tags = normalize_tags(request.tags, settings.lowercase_tags);
+    if !tags.is_empty() {
+        node = node.with_tags(tags);
+    }
+
+    if let Some(source) = request.source {
+        node = node.with_source(source);
+    }
+
+    if let Some((created_at, updated_at)) = timestamps {
+        node.created_at = created_at;
+        node.updated_at = updated_at;
+    }
+    node.expires_at = request.expires_at;
+
+    if let Some(confidence) = request.confidence {
+        if !(0.0..=1.0).contains(&confidence) {
+            return Err("Confidence must be between 0.0 and 1.0".to_string());
+        }
+        node.confidence = confidence;
     }
+    match request.metadata {
+        None => {}
+        Some(metadata @ serde_json::Value::Object(_)) => node.metadata = metadata,
+        Some(_) => return Err("Node metadata must be a JSON object".to_string()),
+    }
+
+    node.refresh_normalized_identifier();
 
     match state.create_node(node) {
         Ok(id) => Ok(id.to_string()),
@@ -207,6 +690,205 @@ fn create_node(state: State<AppState>, request: CreateNodeRequest) -> Result<Str
     }
 }
 
+/// Creates nodes from a newline-delimited list of indicators
+///
+/// Each line is classified with `classify_indicator` (IP, domain, email, or
+/// SHA-256 hash) and created as a node of that type. Blank lines are ignored.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `text` - One indicator per line
+/// * `default_tags` - Tags applied to every created node
+///
+/// # Returns
+/// * `Ok(IndicatorImportReport)` - Created node IDs and the unclassified lines
+/// * `Err(String)` - Error message if creating a node fails
+#[tauri::command]
+fn import_indicators(state: State<AppState>, text: String, default_tags: Vec<String>) -> Result<IndicatorImportReport, String> {
+    insert_indicators(&state, &text, &default_tags)
+}
+
+/// Does the work of `import_indicators`
+fn insert_indicators(state: &Database, text: &str, default_tags: &[String]) -> Result<IndicatorImportReport, String> {
+    let mut report = IndicatorImportReport {
+        created: Vec::new(),
+        unclassified: Vec::new(),
+        type_mapping: TypeMappingReport::default(),
+    };
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let Some(node_type) = classify_indicator(line) else {
+            report.type_mapping.record("unrecognized", None, true);
+            report.unclassified.push(line.to_string());
+            continue;
+        };
+        let indicator_kind = match node_type {
+            NodeType::IpAddress => "IP address",
+            NodeType::Email => "email address",
+            NodeType::Document => "SHA-256 hash",
+            _ => "domain",
+        };
+        report.type_mapping.record(indicator_kind, Some(&node_type), false);
+
+        let id = insert_node(
+            state,
+            CreateNodeRequest {
+                node_type: format!("{:?}", node_type),
+                label: line.to_string(),
+                description: None,
+                tags: default_tags.to_vec(),
+                source: None,
+                created_at: None,
+                updated_at: None,
+                expires_at: None,
+                confidence: None,
+                metadata: None,
+            },
+        )?;
+        report.created.push(id);
+    }
+
+    Ok(report)
+}
+
+/// Renders a JSON value as plain text, without quotes for strings
+fn json_value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Creates nodes from arbitrary flat JSON records using a field mapping
+///
+/// Records that are not objects, lack a non-empty label, or have a
+/// non-numeric confidence are skipped and reported. Type values are looked
+/// up in `type_values` first and then as node type names; anything else
+/// falls back to the default type.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `records` - JSON objects, one per node
+/// * `mapping` - Which record fields become which node fields
+///
+/// # Returns
+/// * `Ok(MappedImportReport)` - Created node IDs, skipped records, and the type mapping used
+/// * `Err(String)` - Error message if the mapping names an unknown node type or creation fails
+#[tauri::command]
+fn import_mapped(
+    state: State<AppState>,
+    records: Vec<serde_json::Value>,
+    mapping: FieldMapping,
+) -> Result<MappedImportReport, String> {
+    insert_mapped_records(&state, &records, &mapping)
+}
+
+/// Does the work of `import_mapped`
+fn insert_mapped_records(
+    state: &Database,
+    records: &[serde_json::Value],
+    mapping: &FieldMapping,
+) -> Result<MappedImportReport, String> {
+    let default_type = parse_node_type(mapping.default_node_type.as_deref().unwrap_or("Custom"))?;
+    let type_values = mapping
+        .type_values
+        .iter()
+        .map(|(raw, name)| Ok((raw.clone(), parse_node_type(name)?)))
+        .collect::<Result<std::collections::HashMap<String, NodeType>, String>>()?;
+    let mapped_fields: Vec<&String> = [
+        Some(&mapping.label),
+        mapping.node_type.as_ref(),
+        mapping.tags.as_ref(),
+        mapping.confidence.as_ref(),
+        mapping.description.as_ref(),
+        mapping.source.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let mut report = MappedImportReport {
+        created: Vec::new(),
+        skipped: Vec::new(),
+        type_mapping: TypeMappingReport::default(),
+    };
+
+    for (index, record) in records.iter().enumerate() {
+        let Some(fields) = record.as_object() else {
+            report.skipped.push(SkippedRecord { index, reason: "Record is not an object".to_string() });
+            continue;
+        };
+        let field = |name: &Option<String>| name.as_ref().and_then(|name| fields.get(name)).filter(|value| !value.is_null());
+
+        let label = fields.get(&mapping.label).map(json_value_text).unwrap_or_default();
+        if label.trim().is_empty() {
+            report.skipped.push(SkippedRecord { index, reason: format!("Missing label field '{}'", mapping.label) });
+            continue;
+        }
+
+        let confidence = match field(&mapping.confidence) {
+            None => None,
+            Some(value) => match value.as_f64().or_else(|| value.as_str().and_then(|text| text.trim().parse().ok())) {
+                Some(confidence) if confidence.is_finite() => Some((confidence as f32).clamp(0.0, 1.0)),
+                _ => {
+                    report.skipped.push(SkippedRecord { index, reason: format!("Invalid confidence {}", value) });
+                    continue;
+                }
+            },
+        };
+
+        let node_type = match field(&mapping.node_type).map(json_value_text) {
+            Some(raw) => match type_values.get(&raw).cloned().or_else(|| parse_node_type(&raw).ok()) {
+                Some(node_type) => {
+                    report.type_mapping.record(&raw, Some(&node_type), false);
+                    node_type
+                }
+                None => {
+                    report.type_mapping.record(&raw, Some(&default_type), true);
+                    default_type.clone()
+                }
+            },
+            None => {
+                let fallback = mapping.node_type.is_some();
+                report.type_mapping.record("(none)", Some(&default_type), fallback);
+                default_type.clone()
+            }
+        };
+
+        let tags: Vec<String> = match field(&mapping.tags) {
+            Some(serde_json::Value::Array(values)) => values.iter().map(json_value_text).collect(),
+            Some(value) => json_value_text(value).split(',').map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+
+        let metadata: serde_json::Map<String, serde_json::Value> = fields
+            .iter()
+            .filter(|(name, _)| match &mapping.metadata {
+                Some(selected) => selected.contains(name),
+                None => !mapped_fields.contains(name),
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
+        let request = CreateNodeRequest {
+            node_type: format!("{:?}", node_type),
+            label: label.trim().to_string(),
+            description: field(&mapping.description).map(json_value_text),
+            tags,
+            source: field(&mapping.source).map(json_value_text),
+            created_at: None,
+            updated_at: None,
+            expires_at: None,
+            confidence,
+            metadata: Some(serde_json::Value::Object(metadata)),
+        };
+        let id = insert_node(state, request)?;
+        report.created.push(id);
+    }
+
+    Ok(report)
+}
+
 /// Retrieves all nodes from the database
 ///
 /// # Arguments
@@ -216,8 +898,89 @@ fn create_node(state: State<AppState>, request: CreateNodeRequest) -> Result<Str
 /// * `Ok(Vec<Node>)` - All nodes in the database
 /// * `Err(String)` - Error message if retrieval fails
 #[tauri::command]
-fn get_all_nodes(state: State<AppState>) -> Result<Vec<Node>, String> {
-    state.get_all_nodes().map_err(|e| e.to_string())
+fn get_all_nodes(state: State<AppState>) -> Result<Vec<NodeView>, String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    Ok(with_attachment_counts(nodes))
+}
+
+/// Pairs each node with the number of attachments stored for it
+fn with_attachment_counts(nodes: Vec<Node>) -> Vec<NodeView> {
+    let counts = attachment_counts();
+    nodes
+        .into_iter()
+        .map(|node| {
+            let attachment_count = counts.get(&node.id.to_string()).copied().unwrap_or(0);
+            NodeView { node, attachment_count }
+        })
+        .collect()
+}
+
+/// Retrieves a page of nodes sorted by several fields
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `sort_keys` - `(field, descending)` pairs applied in order, e.g.
+///   `[["node_type", false], ["confidence", true], ["label", false]]`
+/// * `offset` - Number of sorted nodes to skip (default 0)
+/// * `limit` - Maximum number of nodes to return (default all)
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - The requested page
+/// * `Err(String)` - Error message listing valid fields if a field name is unknown
+#[tauri::command]
+fn get_nodes_sorted(
+    state: State<AppState>,
+    sort_keys: Vec<(String, bool)>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<Node>, String> {
+    let sort_keys = parse_sort_keys(&sort_keys)?;
+    state
+        .get_nodes_sorted(&sort_keys, offset.unwrap_or(0), limit)
+        .map_err(|e| e.to_string())
+}
+
+/// Resolves `(field, descending)` pairs, listing the valid fields if one is unknown
+fn parse_sort_keys(sort_keys: &[(String, bool)]) -> Result<Vec<(NodeSortField, bool)>, String> {
+    sort_keys
+        .iter()
+        .map(|(field, descending)| match NodeSortField::from_name(field) {
+            Some(field) => Ok((field, *descending)),
+            None => Err(format!(
+                "Unknown sort field \"{}\". Valid fields: {}",
+                field,
+                NodeSortField::NAMES.join(", ")
+            )),
+        })
+        .collect()
+}
+
+/// Returns all nodes of the given type
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_type` - Node type name (e.g. "CryptoWallet")
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - All nodes of this type
+/// * `Err(String)` - Error message if the type is invalid or retrieval fails
+#[tauri::command]
+fn get_nodes_by_type(state: State<AppState>, node_type: String) -> Result<Vec<Node>, String> {
+    let node_type = parse_node_type(&node_type)?;
+    state.get_nodes_by_type(&node_type).map_err(|e| e.to_string())
+}
+
+/// Lists the distinct sources cited by nodes, with how many nodes cite each
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(Vec<SourceCount>)` - Sources, most frequent first
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_all_node_sources(state: State<AppState>) -> Result<Vec<SourceCount>, String> {
+    state.get_all_node_sources().map_err(|e| e.to_string())
 }
 
 /// Searches for nodes matching a query string
@@ -236,416 +999,3741 @@ fn search_nodes(state: State<AppState>, query: String) -> Result<Vec<Node>, Stri
     state.search_nodes(&query).map_err(|e| e.to_string())
 }
 
+/// Suggests node labels for type-ahead inputs
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `prefix` - Text typed so far (case-insensitive)
+/// * `limit` - Maximum number of suggestions
+///
+/// # Returns
+/// * `Ok(Vec<LabelSuggestion>)` - Node IDs, labels, and types; prefix matches before other matches
+/// * `Err(String)` - Error message if lookup fails
 #[tauri::command]
-fn get_node(state: State<AppState>, id: String) -> Result<Option<Node>, String> {
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    state.get_node(uuid).map_err(|e| e.to_string())
+fn autocomplete_labels(state: State<AppState>, prefix: String, limit: usize) -> Result<Vec<LabelSuggestion>, String> {
+    state.autocomplete_labels(&prefix, limit).map_err(|e| e.to_string())
 }
 
+/// Searches for nodes and returns them with their direct relationships and neighbors
+///
+/// Gives the UI everything needed to draw a mini-graph of the results.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `query` - Search query string
+///
+/// # Returns
+/// * `Ok(SearchContext)` - Matching nodes, relationships touching them, and the nodes on the other end
+/// * `Err(String)` - Error message if search fails
 #[tauri::command]
-fn update_node(state: State<AppState>, request: UpdateNodeRequest) -> Result<(), String> {
-    let uuid = Uuid::parse_str(&request.id).map_err(|e| e.to_string())?;
-    
-    // Get the existing node
-    let mut node = state.get_node(uuid)
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Node not found".to_string())?;
-    
-    // Update fields
-    node.label = request.label;
-    node.description = request.description;
-    node.tags = request.tags;
-    node.confidence = request.confidence as f32;
-    node.updated_at = chrono::Utc::now();
-    
-    state.update_node(node).map_err(|e| e.to_string())
+fn search_with_context(state: State<AppState>, query: String) -> Result<SearchContext, String> {
+    state.search_with_context(&query).map_err(|e| e.to_string())
 }
 
+/// Searches for nodes and returns them ranked by relevance
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `query` - Search query string
+/// * `weight_by_confidence` - Whether higher-confidence nodes rank above equal text matches (default true)
+///
+/// # Returns
+/// * `Ok(Vec<SearchResult>)` - Matching nodes with scores, best first
+/// * `Err(String)` - Error message if search fails
 #[tauri::command]
-fn delete_node(state: State<AppState>, id: String) -> Result<bool, String> {
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    state.delete_node(uuid).map_err(|e| e.to_string())
+fn search_nodes_ranked(
+    state: State<AppState>,
+    query: String,
+    weight_by_confidence: Option<bool>,
+) -> Result<Vec<SearchResult>, String> {
+    state
+        .search_nodes_ranked(&query, weight_by_confidence.unwrap_or(true))
+        .map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn create_relationship(state: State<AppState>, request: CreateRelationshipRequest) -> Result<String, String> {
-    let source_id = Uuid::parse_str(&request.source_id).map_err(|e| e.to_string())?;
-    let target_id = Uuid::parse_str(&request.target_id).map_err(|e| e.to_string())?;
-    
-    let relation_type = match request.relation_type.as_str() {
-        "Owns" => RelationType::Owns,
-        "Controls" => RelationType::Controls,
-        "TransactsWith" => RelationType::TransactsWith,
-        "MemberOf" => RelationType::MemberOf,
-        "ConnectedTo" => RelationType::ConnectedTo,
-        "SameAs" => RelationType::SameAs,
-        "RelatedTo" => RelationType::RelatedTo,
-        "ParentOf" => RelationType::ParentOf,
-        "ChildOf" => RelationType::ChildOf,
-        _ => return Err("Invalid relationship type".to_string()),
-    };
-
-    let mut relationship = Relationship::new(source_id, target_id, relation_type);
-    
-    // Set confidence if provided
-    if let Some(confidence) = request.confidence {
-        relationship = relationship.with_confidence(confidence);
-    }
-    
-    // Set source if provided
-    if let Some(source) = request.source {
-        relationship = relationship.with_source(source);
-    }
-    
-    // Set description if provided
-    if let Some(description) = request.description {
-        relationship.description = Some(description);
-    }
-    
-    match state.create_relationship(relationship) {
-        Ok(id) => Ok(id.to_string()),
-        Err(e) => Err(e.to_string()),
-    }
+/// Searches only the nodes within a number of hops of a center node
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `center` - UUID of the node the search is centered on
+/// * `depth` - Maximum number of hops from the center (edge direction is ignored)
+/// * `query` - Search query string
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - Matching nodes inside the subgraph, sorted by label
+/// * `Err(String)` - Error message if the center node doesn't exist or search fails
+#[tauri::command]
+fn search_within_subgraph(state: State<AppState>, center: String, depth: usize, query: String) -> Result<Vec<Node>, String> {
+    let center_id = Uuid::parse_str(&center).map_err(|e| e.to_string())?;
+    state
+        .search_within_subgraph(center_id, depth, &query)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Center node not found".to_string())
 }
 
+/// Finds nodes matching a query-language expression
+///
+/// See the `query` module for the syntax, e.g.
+/// `type:CryptoWallet AND tag:exchange AND confidence:>0.8`.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `expr` - Query expression
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - Matching nodes, sorted by label
+/// * `Err(String)` - Parse error with the position where parsing failed
 #[tauri::command]
-fn get_relationships(state: State<AppState>) -> Result<Vec<Relationship>, String> {
-    state.get_relationships().map_err(|e| e.to_string())
+fn query(state: State<AppState>, expr: String) -> Result<Vec<Node>, String> {
+    let query = query::Query::parse(&expr).map_err(|e| e.to_string())?;
+    let mut nodes: Vec<Node> = state
+        .get_all_nodes()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|node| query.matches(node))
+        .collect();
+    nodes.sort_by(|a, b| a.label.cmp(&b.label).then_with(|| a.id.cmp(&b.id)));
+    Ok(nodes)
 }
 
 #[tauri::command]
-fn get_node_relationships(state: State<AppState>, node_id: String) -> Result<Vec<Relationship>, String> {
-    let uuid = Uuid::parse_str(&node_id).map_err(|e| e.to_string())?;
-    state.get_node_relationships(uuid).map_err(|e| e.to_string())
+fn get_node(state: State<AppState>, id: String) -> Result<Option<NodeView>, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let node = state.get_node(uuid).map_err(|e| e.to_string())?;
+    Ok(node.map(|node| {
+        let attachment_count = attachment_counts().get(&uuid.to_string()).copied().unwrap_or(0);
+        NodeView { node, attachment_count }
+    }))
 }
 
 #[tauri::command]
-fn update_relationship(state: State<AppState>, request: UpdateRelationshipRequest) -> Result<(), String> {
+fn update_node(state: State<AppState>, request: UpdateNodeRequest) -> Result<(), String> {
+    apply_node_update(&state, request)
+}
+
+/// Does the work of `update_node`
+fn apply_node_update(state: &Database, request: UpdateNodeRequest) -> Result<(), String> {
     let uuid = Uuid::parse_str(&request.id).map_err(|e| e.to_string())?;
     
-    // Get the existing relationship
-    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
-    let mut relationship = relationships.into_iter()
-        .find(|r| r.id == uuid)
-        .ok_or_else(|| "Relationship not found".to_string())?;
-    
-    // Parse the relation type
-    let relation_type = match request.relation_type.as_str() {
-        "Owns" => RelationType::Owns,
-        "Controls" => RelationType::Controls,
-        "TransactsWith" => RelationType::TransactsWith,
-        "MemberOf" => RelationType::MemberOf,
-        "ConnectedTo" => RelationType::ConnectedTo,
-        "SameAs" => RelationType::SameAs,
-        "RelatedTo" => RelationType::RelatedTo,
-        "ParentOf" => RelationType::ParentOf,
-        "ChildOf" => RelationType::ChildOf,
-        _ => return Err("Invalid relationship type".to_string()),
-    };
+    // Get the existing node
+    let mut node = state.get_node(uuid)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Node not found".to_string())?;
     
+    let settings = state.settings().map_err(|e| e.to_string())?;
+
     // Update fields
-    relationship.relation_type = relation_type;
-    relationship.description = request.description;
-    relationship.weight = request.weight as f32;
-    relationship.updated_at = chrono::Utc::now();
+    node.label = request.label;
+    node.description = request.description;
+    node.tags = normalize_tags(request.tags, settings.lowercase_tags);
+    node.confidence = request.confidence as f32;
+    if let Some(source) = request.source {
+        node.source = Some(source);
+    }
+    if request.clear_expiry {
+        node.expires_at = None;
+    } else if let Some(expires_at) = request.expires_at {
+        node.expires_at = Some(expires_at);
+    }
+    node.updated_at = chrono::Utc::now();
+    node.refresh_normalized_identifier();
     
-    state.update_relationship(relationship).map_err(|e| e.to_string())
+    state.update_node(node).map_err(|e| e.to_string())
 }
 
+/// Updates a node's metadata, either merging a patch or replacing it
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `id` - UUID of the node to update
+/// * `patch` - Metadata values to apply
+/// * `merge` - `true` to deep-merge the patch (keys missing from it are kept,
+///   `null` removes a key), `false` to replace the metadata wholesale
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the node doesn't exist or the update fails
 #[tauri::command]
-fn delete_relationship(state: State<AppState>, id: String) -> Result<bool, String> {
+fn update_node_metadata(state: State<AppState>, id: String, patch: serde_json::Value, merge: bool) -> Result<(), String> {
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    state.delete_relationship(uuid).map_err(|e| e.to_string())
+    let mut node = state.get_node(uuid)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Node not found".to_string())?;
+
+    if merge {
+        merge_metadata(&mut node.metadata, patch);
+    } else {
+        node.metadata = patch;
+    }
+    node.updated_at = chrono::Utc::now();
+    node.refresh_normalized_identifier();
+
+    state.update_node(node).map_err(|e| e.to_string())
 }
 
-/// Saves the current investigation project to a JSON file
+/// Shows what deleting a node would remove, without deleting anything
 ///
-/// Exports all nodes, relationships, and metadata to a JSON file
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `id` - UUID of the node
+///
+/// # Returns
+/// * `Ok(NodeDeletionPreview)` - The node and the relationships that would cascade
+/// * `Err(String)` - Error message if the node doesn't exist
+#[tauri::command]
+fn preview_node_deletion(state: State<AppState>, id: String) -> Result<NodeDeletionPreview, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    state.preview_node_deletion(uuid)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Node not found".to_string())
+}
+
+#[tauri::command]
+fn delete_node(state: State<AppState>, id: String) -> Result<bool, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    state.delete_node(uuid).map_err(|e| e.to_string())
+}
+
+/// Finds Email and Domain nodes that are duplicates after normalization
 ///
 /// # Arguments
 /// * `state` - Application state containing the database
-/// * `file_path` - Path where to save the project file
-/// * `project_name` - Name of the project
 ///
 /// # Returns
-/// * `Ok(())` - Success
-/// * `Err(String)` - Error message if save fails
+/// * `Ok(Vec<IdentifierDuplicateGroup>)` - Groups of node IDs sharing a normalized identifier
+/// * `Err(String)` - Error message if the lookup fails
 #[tauri::command]
-fn save_project(state: State<AppState>, file_path: String, project_name: String) -> Result<(), String> {
-    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
-    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
-    
-    let project_data = ProjectData {
-        nodes,
-        relationships,
-        metadata: ProjectMetadata {
-            name: project_name,
-            created_at: chrono::Utc::now().to_rfc3339(),
-            updated_at: chrono::Utc::now().to_rfc3339(),
-            version: "1.0.0".to_string(),
-        },
-    };
-    
-    let json_data = serde_json::to_string_pretty(&project_data).map_err(|e| e.to_string())?;
-    std::fs::write(&file_path, json_data).map_err(|e| e.to_string())?;
-    
-    Ok(())
+fn find_identifier_duplicates(state: State<AppState>) -> Result<Vec<IdentifierDuplicateGroup>, String> {
+    state.find_identifier_duplicates().map_err(|e| e.to_string())
 }
 
-/// Loads an investigation project from a JSON file
+/// Flags nodes whose source does not appear on any of their relationships
 ///
-/// Clears current data and loads nodes and relationships from file
+/// Advisory only: a flagged node may still be correct, but its provenance
+/// deserves a second look.
 ///
 /// # Arguments
 /// * `state` - Application state containing the database
-/// * `file_path` - Path to the project file to load
 ///
 /// # Returns
-/// * `Ok(ProjectMetadata)` - Loaded project metadata
-/// * `Err(String)` - Error message if load fails
+/// * `Ok(Vec<SourceMismatch>)` - Nodes flagged for review
+/// * `Err(String)` - Error message if the check fails
 #[tauri::command]
-fn load_project(state: State<AppState>, file_path: String) -> Result<ProjectMetadata, String> {
-    let json_data = std::fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
-    let project_data: ProjectData = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
-    
-    // Clear existing data first
-    state.clear_all().map_err(|e| e.to_string())?;
-    
-    // Load nodes
-    for node in project_data.nodes {
-        state.create_node(node).map_err(|e| e.to_string())?;
-    }
-    
-    // Load relationships
-    for relationship in project_data.relationships {
-        state.create_relationship(relationship).map_err(|e| e.to_string())?;
-    }
-    
-    Ok(project_data.metadata)
+fn cross_check_sources(state: State<AppState>) -> Result<Vec<SourceMismatch>, String> {
+    state.cross_check_sources().map_err(|e| e.to_string())
 }
 
-/// Exports investigation data to CSV format
+/// Records that a node was just enriched from an external source
 ///
-/// Creates a CSV file with separate sections for nodes and relationships
+/// Enrichment tooling calls this after refreshing a node so it drops out of
+/// the `get_stale_nodes` work queue.
 ///
 /// # Arguments
 /// * `state` - Application state containing the database
-/// * `file_path` - Path where to save the CSV file
+/// * `id` - UUID of the enriched node
 ///
 /// # Returns
 /// * `Ok(())` - Success
-/// * `Err(String)` - Error message if export fails
+/// * `Err(String)` - Error message if the node doesn't exist
 #[tauri::command]
-fn export_csv(state: State<AppState>, file_path: String) -> Result<(), String> {
-    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
-    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
-    
-    let mut csv_content = String::new();
-    
-    // Nodes CSV
-    csv_content.push_str("Type,ID,Label,NodeType,Description,Tags,Confidence,CreatedAt\n");
-    for node in &nodes {
-        csv_content.push_str(&format!(
-            "Node,{},{},{},{},{},{},{}\n",
-            node.id,
-            node.label.replace(',', ";"),
-            format!("{:?}", node.node_type),
-            node.description.as_ref().unwrap_or(&String::new()).replace(',', ";"),
-            node.tags.join(";"),
-            node.confidence,
-            node.created_at.to_rfc3339()
-        ));
+fn mark_node_enriched(state: State<AppState>, id: String) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    match state.mark_node_enriched(uuid, chrono::Utc::now()).map_err(|e| e.to_string())? {
+        true => Ok(()),
+        false => Err("Node not found".to_string()),
     }
-    
-    csv_content.push_str("\n");
-    
-    // Relationships CSV
-    csv_content.push_str("Type,ID,SourceID,TargetID,RelationType,Description,Weight,Confidence,Source,CreatedAt\n");
-    for rel in &relationships {
-        csv_content.push_str(&format!(
-            "Relationship,{},{},{},{},{},{},{},{},{}\n",
-            rel.id,
-            rel.source_id,
-            rel.target_id,
-            format!("{:?}", rel.relation_type),
-            rel.description.as_ref().unwrap_or(&String::new()).replace(',', ";"),
-            rel.weight,
-            rel.confidence,
-            rel.source.as_ref().unwrap_or(&String::new()).replace(',', ";"),
-            rel.created_at.to_rfc3339()
-        ));
+}
+
+/// Sets the confidence of several nodes at once
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_ids` - UUIDs of the nodes to update
+/// * `value` - New confidence, between 0.0 and 1.0
+///
+/// # Returns
+/// * `Ok(BulkConfidenceUpdate)` - Number of nodes updated and the IDs that were not found
+/// * `Err(String)` - Error message if the value is out of range or an ID is malformed
+#[tauri::command]
+fn set_confidence_bulk(state: State<AppState>, node_ids: Vec<String>, value: f32) -> Result<BulkConfidenceUpdate, String> {
+    if !(0.0..=1.0).contains(&value) {
+        return Err("Confidence must be between 0.0 and 1.0".to_string());
     }
-    
-    std::fs::write(&file_path, csv_content).map_err(|e| e.to_string())?;
-    Ok(())
+    let ids = node_ids
+        .iter()
+        .map(|id| Uuid::parse_str(id).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<Uuid>, String>>()?;
+    state.set_confidence_bulk(&ids, value).map_err(|e| e.to_string())
 }
 
-/// Exports investigation data to GraphML format
+/// Lists enrichable nodes due for re-enrichment
 ///
-/// Creates a GraphML file compatible with graph analysis tools like Gephi
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `older_than_days` - Nodes not enriched within this many days (or never) are returned
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - Stale nodes, least recently enriched first
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_stale_nodes(state: State<AppState>, older_than_days: u32) -> Result<Vec<Node>, String> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(older_than_days as i64);
+    state.get_stale_nodes(cutoff).map_err(|e| e.to_string())
+}
+
+/// Pins a node so bulk cleanup operations leave it in place
 ///
 /// # Arguments
 /// * `state` - Application state containing the database
-/// * `file_path` - Path where to save the GraphML file
+/// * `id` - UUID of the node to pin
 ///
 /// # Returns
 /// * `Ok(())` - Success
-/// * `Err(String)` - Error message if export fails
+/// * `Err(String)` - Error message if the node doesn't exist
 #[tauri::command]
-fn export_graphml(state: State<AppState>, file_path: String) -> Result<(), String> {
-    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
-    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
-    
-    let mut graphml = String::new();
-    graphml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    graphml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n");
-    
-    // Define keys for attributes
-    graphml.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
-    graphml.push_str("  <key id=\"nodeType\" for=\"node\" attr.name=\"nodeType\" attr.type=\"string\"/>\n");
-    graphml.push_str("  <key id=\"confidence\" for=\"node\" attr.name=\"confidence\" attr.type=\"double\"/>\n");
-    graphml.push_str("  <key id=\"relationType\" for=\"edge\" attr.name=\"relationType\" attr.type=\"string\"/>\n");
-    graphml.push_str("  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n");
-    graphml.push_str("  <key id=\"edgeConfidence\" for=\"edge\" attr.name=\"edgeConfidence\" attr.type=\"double\"/>\n");
-    graphml.push_str("  <key id=\"source\" for=\"edge\" attr.name=\"source\" attr.type=\"string\"/>\n");
-    
-    graphml.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
-    
-    // Add nodes
-    for node in &nodes {
-        graphml.push_str(&format!("    <node id=\"{}\">\n", node.id));
-        graphml.push_str(&format!("      <data key=\"label\">{}</data>\n", node.label));
-        graphml.push_str(&format!("      <data key=\"nodeType\">{:?}</data>\n", node.node_type));
-        graphml.push_str(&format!("      <data key=\"confidence\">{}</data>\n", node.confidence));
-        graphml.push_str("    </node>\n");
-    }
-    
-    // Add edges
-    for rel in &relationships {
-        graphml.push_str(&format!("    <edge id=\"{}\" source=\"{}\" target=\"{}\">\n", rel.id, rel.source_id, rel.target_id));
-        graphml.push_str(&format!("      <data key=\"relationType\">{:?}</data>\n", rel.relation_type));
-        graphml.push_str(&format!("      <data key=\"weight\">{}</data>\n", rel.weight));
-        graphml.push_str(&format!("      <data key=\"edgeConfidence\">{}</data>\n", rel.confidence));
-        if let Some(source) = &rel.source {
-            graphml.push_str(&format!("      <data key=\"source\">{}</data>\n", source));
-        }
-        graphml.push_str("    </edge>\n");
+fn pin_node(state: State<AppState>, id: String) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    match state.set_node_pinned(uuid, true).map_err(|e| e.to_string())? {
+        true => Ok(()),
+        false => Err("Node not found".to_string()),
     }
-    
-    graphml.push_str("  </graph>\n");
-    graphml.push_str("</graphml>\n");
-    
-    std::fs::write(&file_path, graphml).map_err(|e| e.to_string())?;
-    Ok(())
 }
 
+/// Removes the pin from a node
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `id` - UUID of the node to unpin
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the node doesn't exist
 #[tauri::command]
-fn export_json(state: State<AppState>, file_path: String) -> Result<(), String> {
-    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
-    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
-    
-    let project_data = ProjectData {
-        nodes,
-        relationships,
-        metadata: ProjectMetadata {
-            name: "Exported Data".to_string(),
-            created_at: chrono::Utc::now().to_rfc3339(),
-            updated_at: chrono::Utc::now().to_rfc3339(),
-            version: "1.0.0".to_string(),
-        },
-    };
-    
-    let json_data = serde_json::to_string_pretty(&project_data).map_err(|e| e.to_string())?;
-    std::fs::write(&file_path, json_data).map_err(|e| e.to_string())?;
-    
-    Ok(())
+fn unpin_node(state: State<AppState>, id: String) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    match state.set_node_pinned(uuid, false).map_err(|e| e.to_string())? {
+        true => Ok(()),
+        false => Err("Node not found".to_string()),
+    }
 }
 
+/// Iteratively removes leaf nodes to declutter the graph
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `iterations` - Number of rounds; 0 repeats until no leaves remain
+/// * `dry_run` - When true, only report which nodes would be removed (default false)
+///
+/// # Returns
+/// * `Ok(BulkRemovalReport)` - IDs of the removed nodes and how many pinned leaves were kept
+/// * `Err(String)` - Error message if trimming fails
 #[tauri::command]
-fn write_report(file_path: String, content: String) -> Result<(), String> {
-    std::fs::write(&file_path, content).map_err(|e| e.to_string())?;
-    Ok(())
+fn trim_leaves(state: State<AppState>, iterations: usize, dry_run: Option<bool>) -> Result<BulkRemovalReport, String> {
+    state
+        .trim_leaves(iterations, dry_run.unwrap_or(false))
+        .map_err(|e| e.to_string())
 }
 
+/// Returns all nodes whose expiry time has passed, including pinned ones
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - Expired nodes, earliest expiry first
+/// * `Err(String)` - Error message if retrieval fails
 #[tauri::command]
-fn save_attachment(node_id: String, filename: String, content_base64: String) -> Result<String, String> {
-    // Create attachments directory if it doesn't exist
-    let attachments_dir = "./attachments";
-    std::fs::create_dir_all(attachments_dir).map_err(|e| e.to_string())?;
-    
-    // Decode base64 content
-    let content = base64::prelude::BASE64_STANDARD.decode(&content_base64).map_err(|e| e.to_string())?;
-    
-    // Generate unique filename
-    let attachment_id = Uuid::new_v4().to_string();
-    let file_extension = std::path::Path::new(&filename)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("bin");
-    let stored_filename = format!("{}_{}.{}", attachment_id, node_id, file_extension);
-    let file_path = format!("{}/{}", attachments_dir, stored_filename);
-    
-    // Save file
-    std::fs::write(&file_path, content).map_err(|e| e.to_string())?;
-    
-    Ok(attachment_id)
+fn get_expired_nodes(state: State<AppState>) -> Result<Vec<Node>, String> {
+    state.get_expired_nodes(chrono::Utc::now()).map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn list_attachments(node_id: String) -> Result<Vec<AttachmentData>, String> {
-    let attachments_dir = "./attachments";
-    let mut attachments = Vec::new();
-    
+/// Deletes all expired nodes and their relationships, skipping pinned nodes
+///
+/// Pinned nodes are never purged, even after their expiry time has passed;
+/// they stay listed by `get_expired_nodes` until unpinned or their expiry is
+/// cleared through `update_node`.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(BulkRemovalReport)` - Deleted node IDs and how many expired pinned nodes were kept
+/// * `Err(String)` - Error message if deletion fails
+#[tauri::command]
+fn purge_expired(state: State<AppState>) -> Result<BulkRemovalReport, String> {
+    state.purge_expired(chrono::Utc::now()).map_err(|e| e.to_string())
+}
+
+/// Deletes all nodes without any relationships, skipping pinned nodes
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(BulkRemovalReport)` - Deleted node IDs and how many pinned nodes were kept
+/// * `Err(String)` - Error message if cleanup fails
+#[tauri::command]
+fn delete_orphan_nodes(state: State<AppState>) -> Result<BulkRemovalReport, String> {
+    state.delete_orphan_nodes().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_relationship(state: State<AppState>, request: CreateRelationshipRequest) -> Result<RelationshipCreated, String> {
+    insert_relationship(&state, request)
+}
+
+/// Builds a relationship from a creation request and stores it
+///
+/// Shared by the `create_relationship` command and headless mode.
+///
+/// # Arguments
+/// * `state` - Database to insert into
+/// * `request` - Relationship creation request
+///
+/// # Returns
+/// * `Ok(RelationshipCreated)` - The UUID of the created relationship and any limit warnings
+/// * `Err(String)` - Error message if validation or creation fails
+fn insert_relationship(state: &Database, request: CreateRelationshipRequest) -> Result<RelationshipCreated, String> {
+    let source_id = Uuid::parse_str(&request.source_id).map_err(|e| e.to_string())?;
+    let target_id = Uuid::parse_str(&request.target_id).map_err(|e| e.to_string())?;
+    
+    let relation_type = parse_relation_type(&request.relation_type)?;
+    let timestamps = resolve_timestamp_overrides(request.created_at, request.updated_at)?;
+    check_self_loop(source_id, target_id, request.allow_self_loop.unwrap_or(false))?;
+
+    let mut relationship = Relationship::new(source_id, target_id, relation_type);
+    
+    // Set confidence if provided
+    if let Some(confidence) = request.confidence {
+        relationship = relationship.with_confidence(confidence);
+    }
+    
+    // Set weight if provided
+    if let Some(weight) = request.weight {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err("Relationship weight must be a non-negative number".to_string());
+        }
+        relationship = relationship.with_weight(weight);
+    }
+    
+    // Set source if provided
+    if let Some(source) = request.source {
+        relationship = relationship.with_source(source);
+    }
+
+    if let Some(label) = request.label {
+        relationship = relationship.with_label(label);
+    }
+    
+    // Set description if provided
+    if let Some(description) = request.description {
+        relationship.description = Some(description);
+    }
+
+    if let Some((created_at, updated_at)) = timestamps {
+        relationship.created_at = created_at;
+        relationship.updated_at = updated_at;
+    }
+
+    let warnings = check_relationship_limits(state, &[(source_id, 1), (target_id, 1)])?;
+
+    let result = match request.idempotency_key {
+        Some(key) => state.create_relationship_idempotent(relationship, &key),
+        None if request.merge_duplicates.unwrap_or(false) => state.create_or_merge_relationship(relationship),
+        None => state.create_relationship(relationship),
+    };
+    match result {
+        Ok(id) => Ok(RelationshipCreated { id: id.to_string(), warnings }),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Rejects relationships whose source and target are the same node
+///
+/// Self-loops break several layout algorithms and skew centrality, so they
+/// must be requested explicitly.
+///
+/// # Arguments
+/// * `source_id` - Source node of the relationship
+/// * `target_id` - Target node of the relationship
+/// * `allow_self_loop` - Whether a self-loop was explicitly requested
+///
+/// # Returns
+/// * `Ok(())` - If the relationship may be created
+/// * `Err(String)` - Error message if it is an unrequested self-loop
+fn check_self_loop(source_id: Uuid, target_id: Uuid, allow_self_loop: bool) -> Result<(), String> {
+    if source_id == target_id && !allow_self_loop {
+        return Err("A relationship cannot connect a node to itself unless allow_self_loop is set".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that adding relationships to nodes stays within the configured limit
+///
+/// Over the limit, the relationships are rejected when `reject_over_limit`
+/// is set; otherwise creation proceeds and a warning is returned for the
+/// caller to pass back in its response.
+///
+/// # Arguments
+/// * `state` - Database holding the nodes and settings
+/// * `additions` - Each node gaining relationships, with how many it is about to gain
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - One warning per node over the limit in warn-only mode, with its current degree
+/// * `Err(String)` - Error message with the node's current degree if rejected
+fn check_relationship_limits(state: &Database, additions: &[(Uuid, usize)]) -> Result<Vec<String>, String> {
+    let settings = state.settings().map_err(|e| e.to_string())?;
+    let mut warnings = Vec::new();
+    for &(node_id, additional) in additions {
+        let Some(node) = state.get_node(node_id).map_err(|e| e.to_string())? else {
+            continue;
+        };
+        if node.degree + additional <= settings.relationship_limit {
+            continue;
+        }
+
+        let message = format!(
+            "Node \"{}\" already has {} relationships (limit {})",
+            node.label, node.degree, settings.relationship_limit
+        );
+        if settings.reject_over_limit {
+            return Err(message);
+        }
+        warnings.push(message);
+    }
+    Ok(warnings)
+}
+
+/// Resolves a node label to a single node ID
+///
+/// # Arguments
+/// * `state` - Database to search
+/// * `label` - Exact node label to look up
+///
+/// # Returns
+/// * `Ok(Uuid)` - The ID of the only node carrying this label
+/// * `Err(String)` - Error message if no node or several nodes match
+fn resolve_unique_label(state: &Database, label: &str) -> Result<Uuid, String> {
+    let matches = state.find_nodes_by_label(label).map_err(|e| e.to_string())?;
+    match matches.as_slice() {
+        [node] => Ok(node.id),
+        [] => Err(format!("No node found with label \"{}\"", label)),
+        _ => Err(format!("Label \"{}\" is ambiguous: {} nodes share it", label, matches.len())),
+    }
+}
+
+/// Creates relationships implied by node IDs stored in metadata
+///
+/// For every node and every key in `mapping`, the value of
+/// `metadata.<key>` (a node ID or an array of node IDs) is resolved and a
+/// relationship of the rule's type is created between the two nodes.
+/// References that already have a relationship of that type in the same
+/// direction are skipped, so the command can be re-run after each import.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `mapping` - Metadata key to relationship rule, e.g.
+///   `{"owner_id": {"relation_type": "Owns", "reverse": true}}`
+///
+/// # Returns
+/// * `Ok(MetadataInferenceReport)` - Created relationships and unresolved references
+/// * `Err(String)` - Error message if a relation type is invalid or creation fails
+#[tauri::command]
+fn infer_relationships_from_metadata(
+    state: State<AppState>,
+    mapping: std::collections::HashMap<String, MetadataRelationRule>,
+) -> Result<MetadataInferenceReport, String> {
+    insert_inferred_relationships(&state, &mapping)
+}
+
+/// Does the work of `infer_relationships_from_metadata`
+fn insert_inferred_relationships(
+    state: &Database,
+    mapping: &std::collections::HashMap<String, MetadataRelationRule>,
+) -> Result<MetadataInferenceReport, String> {
+    let mut rules = Vec::new();
+    for (key, rule) in mapping {
+        rules.push((key, parse_relation_type(&rule.relation_type)?, rule.reverse));
+    }
+    rules.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    nodes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+    let mut report = MetadataInferenceReport { created: Vec::new(), existing: 0, missing: Vec::new(), warnings: Vec::new() };
+    for node in &nodes {
+        for (key, relation_type, reverse) in &rules {
+            let values: Vec<&serde_json::Value> = match node.metadata.get(key.as_str()) {
+                Some(serde_json::Value::Array(values)) => values.iter().collect(),
+                Some(serde_json::Value::Null) | None => continue,
+                Some(value) => vec![value],
+            };
+
+            for value in values {
+                let reference = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                let target = Uuid::parse_str(&reference)
+                    .ok()
+                    .filter(|id| *id != node.id)
+                    .and_then(|id| state.get_node(id).ok().flatten());
+                let Some(target) = target else {
+                    report.missing.push(MissingReference {
+                        node_id: node.id.to_string(),
+                        key: key.to_string(),
+                        value: reference,
+                    });
+                    continue;
+                };
+
+                let (source_id, target_id) = if *reverse { (target.id, node.id) } else { (node.id, target.id) };
+                let already_linked = state
+                    .get_relationships_between(source_id, target_id, true)
+                    .map_err(|e| e.to_string())?
+                    .iter()
+                    .any(|rel| rel.relation_type == *relation_type);
+                if already_linked {
+                    report.existing += 1;
+                    continue;
+                }
+
+                report
+                    .warnings
+                    .extend(check_relationship_limits(state, &[(source_id, 1), (target_id, 1)])?);
+
+                let mut relationship = Relationship::new(source_id, target_id, relation_type.clone());
+                relationship.description = Some(format!("Inferred from metadata.{}", key));
+                let id = state.create_relationship(relationship).map_err(|e| e.to_string())?;
+                report.created.push(id.to_string());
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Creates a relationship between two nodes identified by their labels
+///
+/// Both labels must resolve to exactly one node. This is intended for
+/// scripted data entry where only entity names are known.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `source_label` - Label of the source node
+/// * `target_label` - Label of the target node
+/// * `relation_type` - Type of relationship
+///
+/// # Returns
+/// * `Ok(RelationshipCreated)` - The UUID of the created relationship and any limit warnings
+/// * `Err(String)` - Error message if a label is missing or ambiguous
+#[tauri::command]
+fn create_relationship_by_label(
+    state: State<AppState>,
+    source_label: String,
+    target_label: String,
+    relation_type: String,
+) -> Result<RelationshipCreated, String> {
+    insert_relationship_by_label(&state, &source_label, &target_label, &relation_type)
+}
+
+/// Does the work of `create_relationship_by_label`
+fn insert_relationship_by_label(
+    state: &Database,
+    source_label: &str,
+    target_label: &str,
+    relation_type: &str,
+) -> Result<RelationshipCreated, String> {
+    let relation_type = parse_relation_type(relation_type)?;
+    let source_id = resolve_unique_label(state, source_label)?;
+    let target_id = resolve_unique_label(state, target_label)?;
+    check_self_loop(source_id, target_id, false)?;
+
+    let warnings = check_relationship_limits(state, &[(source_id, 1), (target_id, 1)])?;
+
+    let relationship = Relationship::new(source_id, target_id, relation_type);
+    match state.create_relationship(relationship) {
+        Ok(id) => Ok(RelationshipCreated { id: id.to_string(), warnings }),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Links an event node to several participants in one call
+///
+/// Creates one relationship from the event to each participant. All of them
+/// carry the same `metadata.hyperedge_group` ID so they can be treated as a
+/// single multi-party connection.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `event_node_id` - UUID of the Event node
+/// * `participant_ids` - UUIDs of the participating nodes
+/// * `relation_type` - Type of relationship to create for each participant
+///
+/// # Returns
+/// * `Ok(HyperedgeResult)` - The group ID and created relationship IDs
+/// * `Err(String)` - Error message if a node is missing or the event node is not an Event
+#[tauri::command]
+fn create_event_hyperedge(
+    state: State<AppState>,
+    event_node_id: String,
+    participant_ids: Vec<String>,
+    relation_type: String,
+) -> Result<HyperedgeResult, String> {
+    insert_event_hyperedge(&state, &event_node_id, &participant_ids, &relation_type)
+}
+
+/// Does the work of `create_event_hyperedge`
+fn insert_event_hyperedge(
+    state: &Database,
+    event_node_id: &str,
+    participant_ids: &[String],
+    relation_type: &str,
+) -> Result<HyperedgeResult, String> {
+    let event_id = Uuid::parse_str(event_node_id).map_err(|e| e.to_string())?;
+    let relation_type = parse_relation_type(relation_type)?;
+
+    let event = state.get_node(event_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Event node not found".to_string())?;
+    if !matches!(event.node_type, NodeType::Event) {
+        return Err("Hyperedges must be anchored on an Event node".to_string());
+    }
+    if participant_ids.is_empty() {
+        return Err("At least one participant is required".to_string());
+    }
+
+    let mut participants = Vec::with_capacity(participant_ids.len());
+    for id in participant_ids {
+        let uuid = Uuid::parse_str(id).map_err(|e| e.to_string())?;
+        if state.get_node(uuid).map_err(|e| e.to_string())?.is_none() {
+            return Err(format!("Participant node not found: {}", id));
+        }
+        check_self_loop(event_id, uuid, false)?;
+        participants.push(uuid);
+    }
+
+    let mut additions = vec![(event_id, participants.len())];
+    additions.extend(participants.iter().map(|participant_id| (*participant_id, 1)));
+    let warnings = check_relationship_limits(state, &additions)?;
+
+    let group_id = Uuid::new_v4().to_string();
+    let mut relationship_ids = Vec::with_capacity(participants.len());
+    for participant_id in participants {
+        let mut relationship = Relationship::new(event_id, participant_id, relation_type.clone());
+        relationship.metadata = serde_json::json!({ "hyperedge_group": group_id });
+        let id = state.create_relationship(relationship).map_err(|e| e.to_string())?;
+        relationship_ids.push(id.to_string());
+    }
+
+    Ok(HyperedgeResult { group_id, relationship_ids, warnings })
+}
+
+/// Retrieves every relationship belonging to an event hyperedge
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `group_id` - Hyperedge group ID returned by `create_event_hyperedge`
+///
+/// # Returns
+/// * `Ok(Vec<Relationship>)` - Relationships sharing the group ID
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_hyperedge(state: State<AppState>, group_id: String) -> Result<Vec<Relationship>, String> {
+    state.get_hyperedge(&group_id).map_err(|e| e.to_string())
+}
+
+/// Retrieves all relationships with source and target labels and types
+///
+/// Endpoints that no longer exist get a placeholder label and no type.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(Vec<RelationshipDetail>)` - All relationships with hydrated endpoints
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_relationships_detailed(state: State<AppState>) -> Result<Vec<RelationshipDetail>, String> {
+    state.get_relationships_detailed().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_relationships(state: State<AppState>) -> Result<Vec<Relationship>, String> {
+    state.get_relationships().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_node_relationships(state: State<AppState>, node_id: String) -> Result<Vec<Relationship>, String> {
+    let uuid = Uuid::parse_str(&node_id).map_err(|e| e.to_string())?;
+    state.get_node_relationships(uuid).map_err(|e| e.to_string())
+}
+
+/// Counts a node's relationships by type and direction
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_id` - UUID of the node
+///
+/// # Returns
+/// * `Ok(BTreeMap<String, DirectionCounts>)` - Incoming and outgoing counts keyed by relation type
+/// * `Err(String)` - Error message if the node doesn't exist
+#[tauri::command]
+fn get_node_relationship_summary(
+    state: State<AppState>,
+    node_id: String,
+) -> Result<std::collections::BTreeMap<String, DirectionCounts>, String> {
+    let uuid = Uuid::parse_str(&node_id).map_err(|e| e.to_string())?;
+    if state.get_node(uuid).map_err(|e| e.to_string())?.is_none() {
+        return Err("Node not found".to_string());
+    }
+    state.get_node_relationship_summary(uuid).map_err(|e| e.to_string())
+}
+
+/// Retrieves all relationships connecting two specific nodes
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_a` - UUID of the first node
+/// * `node_b` - UUID of the second node
+/// * `directed` - When true, only relationships from `node_a` to `node_b` are returned (default false)
+///
+/// # Returns
+/// * `Ok(Vec<Relationship>)` - Relationships between the two nodes
+/// * `Err(String)` - Error message if an ID is invalid or retrieval fails
+#[tauri::command]
+fn get_relationships_between(
+    state: State<AppState>,
+    node_a: String,
+    node_b: String,
+    directed: Option<bool>,
+) -> Result<Vec<Relationship>, String> {
+    let node_a = Uuid::parse_str(&node_a).map_err(|e| e.to_string())?;
+    let node_b = Uuid::parse_str(&node_b).map_err(|e| e.to_string())?;
+    state
+        .get_relationships_between(node_a, node_b, directed.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Reports when two nodes first and last interacted
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `a` - UUID of the first node
+/// * `b` - UUID of the second node
+///
+/// # Returns
+/// * `Ok(PairInteractionSummary)` - Relationship count and first/last timestamps
+/// * `Err(String)` - Error message if an ID is invalid or retrieval fails
+#[tauri::command]
+fn get_pair_interaction_summary(state: State<AppState>, a: String, b: String) -> Result<PairInteractionSummary, String> {
+    let node_a = Uuid::parse_str(&a).map_err(|e| e.to_string())?;
+    let node_b = Uuid::parse_str(&b).map_err(|e| e.to_string())?;
+    state.get_pair_interaction_summary(node_a, node_b).map_err(|e| e.to_string())
+}
+
+/// Reports count and mean/min/max confidence for each relationship type in use
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(Vec<RelationConfidenceStats>)` - One entry per relationship type in use
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_confidence_by_relation_type(state: State<AppState>) -> Result<Vec<RelationConfidenceStats>, String> {
+    state.get_confidence_by_relation_type().map_err(|e| e.to_string())
+}
+
+/// Confidence threshold used by `get_quality_report` when none is given
+const DEFAULT_QUALITY_THRESHOLD: f32 = 0.5;
+
+/// Grades data quality by low-confidence and unsourced entities
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `threshold` - Confidence below which entities are flagged (default 0.5)
+///
+/// # Returns
+/// * `Ok(QualityReport)` - Counts and percentages for nodes and relationships
+/// * `Err(String)` - Error message if the threshold is outside 0.0-1.0 or retrieval fails
+#[tauri::command]
+fn get_quality_report(state: State<AppState>, threshold: Option<f32>) -> Result<QualityReport, String> {
+    let threshold = threshold.unwrap_or(DEFAULT_QUALITY_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("Threshold must be between 0.0 and 1.0".to_string());
+    }
+    state.get_quality_report(threshold).map_err(|e| e.to_string())
+}
+
+/// Returns cumulative node and relationship counts over time
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `bucket` - Bucket size: `day`, `week`, or `month`
+///
+/// # Returns
+/// * `Ok(Vec<GrowthPoint>)` - Cumulative counts per bucket, oldest first, with no gaps
+/// * `Err(String)` - Error message if the bucket size is unknown or the range needs too many buckets
+#[tauri::command]
+fn get_growth_timeline(state: State<AppState>, bucket: String) -> Result<Vec<GrowthPoint>, String> {
+    let bucket = TimeBucket::from_name(&bucket).ok_or_else(|| {
+        format!("Unknown bucket '{}'; expected one of: {}", bucket, TimeBucket::NAMES.join(", "))
+    })?;
+    state.get_growth_timeline(bucket).map_err(|e| e.to_string())
+}
+
+/// Returns the distribution of relationship weights
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `buckets` - Number of equal-width buckets (at least 1)
+///
+/// # Returns
+/// * `Ok(Vec<HistogramBucket>)` - Buckets in ascending weight order
+/// * `Err(String)` - Error message if `buckets` is zero or retrieval fails
+#[tauri::command]
+fn get_weight_histogram(state: State<AppState>, buckets: usize) -> Result<Vec<HistogramBucket>, String> {
+    if buckets == 0 {
+        return Err("Bucket count must be at least 1".to_string());
+    }
+    state.get_weight_histogram(buckets).map_err(|e| e.to_string())
+}
+
+/// Finds nodes whose metadata matches a key/value condition
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `key` - Metadata key, optionally a dot-path such as `address.city`
+/// * `value` - Value to compare against
+/// * `comparator` - Optional comparison (`eq`, `ne`, `gt`, `gte`, `lt`, `lte`, `contains`); defaults to `eq`
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - Matching nodes
+/// * `Err(String)` - Error message if the query fails
+#[tauri::command]
+fn query_node_metadata(
+    state: State<AppState>,
+    key: String,
+    value: serde_json::Value,
+    comparator: Option<MetadataComparator>,
+) -> Result<Vec<Node>, String> {
+    state
+        .query_node_metadata(&key, &value, comparator.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Finds relationships whose metadata matches a key/value condition
+///
+/// Useful for filtering on custom attributes such as transaction amounts,
+/// e.g. key `amount`, value `1000`, comparator `gt`.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `key` - Metadata key, optionally a dot-path such as `payment.amount`
+/// * `value` - Value to compare against
+/// * `comparator` - Optional comparison (`eq`, `ne`, `gt`, `gte`, `lt`, `lte`, `contains`); defaults to `eq`
+///
+/// # Returns
+/// * `Ok(Vec<Relationship>)` - Matching relationships
+/// * `Err(String)` - Error message if the query fails
+#[tauri::command]
+fn query_relationship_metadata(
+    state: State<AppState>,
+    key: String,
+    value: serde_json::Value,
+    comparator: Option<MetadataComparator>,
+) -> Result<Vec<Relationship>, String> {
+    state
+        .query_relationship_metadata(&key, &value, comparator.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the provenance record for a relationship
+///
+/// Combines the relationship's sources, confidence, timestamps, and audit
+/// history into one object for evidentiary review.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `id` - UUID of the relationship
+///
+/// # Returns
+/// * `Ok(Provenance)` - The provenance record
+/// * `Err(String)` - Error message if the relationship doesn't exist
+#[tauri::command]
+fn get_relationship_provenance(state: State<AppState>, id: String) -> Result<Provenance, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    state.get_relationship_provenance(uuid)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Relationship not found".to_string())
+}
+
+/// Updates an existing relationship
+///
+/// Non-finite and negative weights are rejected; weights above
+/// [`MAX_RELATIONSHIP_WEIGHT`] are clamped to it.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `request` - New values for the relationship
+///
+/// # Returns
+/// * `Ok(())` - If the relationship was updated
+/// * `Err(String)` - Error message if the relationship doesn't exist or a value is invalid
+#[tauri::command]
+fn update_relationship(state: State<AppState>, request: UpdateRelationshipRequest) -> Result<(), String> {
+    apply_relationship_update(&state, request)
+}
+
+/// Does the work of `update_relationship`
+fn apply_relationship_update(state: &Database, request: UpdateRelationshipRequest) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&request.id).map_err(|e| e.to_string())?;
+    if !request.weight.is_finite() || request.weight < 0.0 {
+        return Err("Relationship weight must be a non-negative number".to_string());
+    }
+    
+    // Get the existing relationship
+    let mut relationship = state.get_relationship(uuid)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Relationship not found".to_string())?;
+    
+    // Parse the relation type
+    let relation_type = parse_relation_type(&request.relation_type)?;
+    
+    // Update fields
+    relationship.relation_type = relation_type;
+    relationship.description = request.description;
+    relationship.weight = (request.weight as f32).min(MAX_RELATIONSHIP_WEIGHT);
+    if let Some(confidence) = request.confidence {
+        relationship.confidence = confidence.clamp(0.0, 1.0);
+    }
+    if let Some(source) = request.source {
+        relationship.set_source(Some(source));
+    }
+    if let Some(label) = request.label {
+        relationship = relationship.with_label(label);
+    }
+    relationship.updated_at = chrono::Utc::now();
+    
+    state.update_relationship(relationship).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_relationship(state: State<AppState>, id: String) -> Result<bool, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    state.delete_relationship(uuid).map_err(|e| e.to_string())
+}
+
+/// Deletes every relationship of a given type
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `relation_type` - Relationship type name (e.g. "RelatedTo")
+/// * `below_confidence` - Optional threshold; only relationships with lower confidence are deleted
+///
+/// # Returns
+/// * `Ok(usize)` - Number of relationships deleted
+/// * `Err(String)` - Error message if the type is invalid or deletion fails
+#[tauri::command]
+fn delete_relationships_by_type(
+    state: State<AppState>,
+    relation_type: String,
+    below_confidence: Option<f32>,
+) -> Result<usize, String> {
+    let relation_type = parse_relation_type(&relation_type)?;
+    state
+        .delete_relationships_by_type(&relation_type, below_confidence)
+        .map_err(|e| e.to_string())
+}
+
+/// Detects contradictory parent/child relationships
+///
+/// A conflict is a pair of nodes each claimed to be the other's parent via
+/// `ParentOf` or `ChildOf`.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `auto_fix` - Whether to keep the higher-confidence direction and delete the other (default false)
+///
+/// # Returns
+/// * `Ok(HierarchyValidation)` - Conflicts found and any relationships removed
+/// * `Err(String)` - Error message if validation fails
+#[tauri::command]
+fn validate_hierarchy(state: State<AppState>, auto_fix: Option<bool>) -> Result<HierarchyValidation, String> {
+    state.validate_hierarchy(auto_fix.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Repairs NaN, infinite, and out-of-range confidence and weight values
+///
+/// Runs automatically when a project is loaded; exposed for repairing data
+/// that arrived by other means.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(usize)` - Number of nodes and relationships repaired
+/// * `Err(String)` - Error message if the repair fails
+#[tauri::command]
+fn sanitize_numeric_fields(state: State<AppState>) -> Result<usize, String> {
+    state.sanitize_numeric_fields().map_err(|e| e.to_string())
+}
+
+/// Caps each relationship's confidence at the lower of its endpoints' confidences
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(usize)` - Number of relationships whose confidence was lowered
+/// * `Err(String)` - Error message if the update fails
+#[tauri::command]
+fn cap_relationship_confidence_by_endpoints(state: State<AppState>) -> Result<usize, String> {
+    state.cap_relationship_confidence_by_endpoints().map_err(|e| e.to_string())
+}
+
+/// Computes display weights that fade edges toward leaf nodes
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(HashMap<Uuid, f64>)` - Adjusted weight per relationship ID; stored weights are unchanged
+/// * `Err(String)` - Error message if computation fails
+#[tauri::command]
+fn compute_visual_edge_weights(state: State<AppState>) -> Result<std::collections::HashMap<Uuid, f64>, String> {
+    state.compute_visual_edge_weights().map_err(|e| e.to_string())
+}
+
+/// Computes the diameter and average shortest path length of the largest connected component
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(PathLengthStats)` - Diameter, average path length, and the node pair achieving the diameter
+/// * `Err(String)` - Error message if computation fails
+#[tauri::command]
+fn get_path_length_stats(state: State<AppState>) -> Result<PathLengthStats, String> {
+    state.get_path_length_stats().map_err(|e| e.to_string())
+}
+
+/// Computes degree centrality for all nodes
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `directed` - Whether to report in-degree and out-degree separately
+/// * `recency_half_life_days` - Opt-in recency weighting: edges lose half their weight per this many days since their last update
+///
+/// # Returns
+/// * `Ok(Vec<DegreeCentrality>)` - Per-node degree scores, highest first
+/// * `Err(String)` - Error message if the half-life is not positive or computation fails
+#[tauri::command]
+fn compute_degree_centrality(
+    state: State<AppState>,
+    directed: bool,
+    recency_half_life_days: Option<f64>,
+) -> Result<Vec<DegreeCentrality>, String> {
+    if let Some(half_life) = recency_half_life_days {
+        if !half_life.is_finite() || half_life <= 0.0 {
+            return Err("Recency half-life must be a positive number of days".to_string());
+        }
+    }
+    state
+        .compute_degree_centrality(directed, recency_half_life_days)
+        .map_err(|e| e.to_string())
+}
+
+/// Summarizes confidence across the connected component containing a node
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_id` - Any node in the component
+///
+/// # Returns
+/// * `Ok(ClusterConfidence)` - Node and relationship confidence statistics
+/// * `Err(String)` - Error message if the node doesn't exist
+#[tauri::command]
+fn get_cluster_confidence(state: State<AppState>, node_id: String) -> Result<ClusterConfidence, String> {
+    let uuid = Uuid::parse_str(&node_id).map_err(|e| e.to_string())?;
+    state.get_cluster_confidence(uuid)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Node not found".to_string())
+}
+
+/// Compares two groups of nodes by the neighbors they share
+///
+/// Useful for attribution, e.g. checking whether two suspected personas
+/// connect to the same contacts or assets.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `set_a` - UUIDs of the first group
+/// * `set_b` - UUIDs of the second group
+///
+/// # Returns
+/// * `Ok(NodeSetComparison)` - Common neighbors and overlap counts
+/// * `Err(String)` - Error message if an ID is invalid or computation fails
+#[tauri::command]
+fn compare_node_sets(state: State<AppState>, set_a: Vec<String>, set_b: Vec<String>) -> Result<NodeSetComparison, String> {
+    let parse = |ids: Vec<String>| -> Result<Vec<Uuid>, String> {
+        ids.iter().map(|id| Uuid::parse_str(id).map_err(|e| e.to_string())).collect()
+    };
+    let set_a = parse(set_a)?;
+    let set_b = parse(set_b)?;
+    state.compare_node_sets(&set_a, &set_b).map_err(|e| e.to_string())
+}
+
+/// Ranks other nodes by the similarity of their neighborhoods to a node
+///
+/// Surfaces entities playing similar roles (e.g. wallets used by the same
+/// people) even when they are not directly linked.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_id` - UUID of the reference node
+/// * `top_n` - Maximum number of results (default 10)
+///
+/// # Returns
+/// * `Ok(Vec<SimilarityScore>)` - Most similar nodes first
+/// * `Err(String)` - Error message if the ID is invalid or computation fails
+#[tauri::command]
+fn compute_structural_similarity(
+    state: State<AppState>,
+    node_id: String,
+    top_n: Option<usize>,
+) -> Result<Vec<SimilarityScore>, String> {
+    let uuid = Uuid::parse_str(&node_id).map_err(|e| e.to_string())?;
+    state
+        .compute_structural_similarity(uuid, top_n.unwrap_or(10))
+        .map_err(|e| e.to_string())
+}
+
+/// Groups nodes into communities using label propagation
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(HashMap<Uuid, usize>)` - Community number for every node ID
+/// * `Err(String)` - Error message if detection fails
+#[tauri::command]
+fn detect_communities(state: State<AppState>) -> Result<std::collections::HashMap<Uuid, usize>, String> {
+    state.detect_communities().map_err(|e| e.to_string())
+}
+
+/// Finds the shortest path between two nodes
+///
+/// Edge direction is ignored unless `directed` is set; symmetric types such
+/// as `SameAs` are traversable both ways even then. Nodes of the listed types
+/// are excluded from the interior of the path, which helps route around
+/// generic hubs. Restricting relationship types (e.g. to `TransactsWith`)
+/// follows only those edges.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `source_id` - UUID of the start node
+/// * `target_id` - UUID of the end node
+/// * `avoid_types` - Optional node type names that may not appear between the endpoints
+/// * `allowed_types` - Optional relationship type names the path may use (default all)
+/// * `directed` - Whether to follow relationships only from source to target (default false)
+///
+/// # Returns
+/// * `Ok(Some(Vec<String>))` - Node IDs along the path, endpoints included
+/// * `Ok(None)` - If no path exists under the constraints
+/// * `Err(String)` - Error message if an ID or type name is invalid
+#[tauri::command]
+fn find_shortest_path(
+    state: State<AppState>,
+    source_id: String,
+    target_id: String,
+    avoid_types: Option<Vec<String>>,
+    allowed_types: Option<Vec<String>>,
+    directed: Option<bool>,
+) -> Result<Option<Vec<String>>, String> {
+    let source_id = Uuid::parse_str(&source_id).map_err(|e| e.to_string())?;
+    let target_id = Uuid::parse_str(&target_id).map_err(|e| e.to_string())?;
+    let constraints = PathConstraints {
+        avoid_node_types: avoid_types
+            .unwrap_or_default()
+            .iter()
+            .map(|name| parse_node_type(name))
+            .collect::<Result<Vec<NodeType>, String>>()?,
+        allowed_relation_types: allowed_types
+            .unwrap_or_default()
+            .iter()
+            .map(|name| parse_relation_type(name))
+            .collect::<Result<Vec<RelationType>, String>>()?,
+        directed: directed.unwrap_or(false),
+    };
+
+    let path = state
+        .find_shortest_path(source_id, target_id, &constraints)
+        .map_err(|e| e.to_string())?;
+    Ok(path.map(|ids| ids.iter().map(|id| id.to_string()).collect()))
+}
+
+/// Groups nodes that are transitively linked by `SameAs` relationships
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `create_missing` - Whether to create the missing direct `SameAs` edges inside each cluster
+///
+/// # Returns
+/// * `Ok(SameAsInference)` - Clusters of equivalent nodes and any created edge IDs
+/// * `Err(String)` - Error message if inference fails
+#[tauri::command]
+fn infer_same_as_transitivity(state: State<AppState>, create_missing: Option<bool>) -> Result<SameAsInference, String> {
+    state
+        .infer_same_as_transitivity(create_missing.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Moves a node's attachments to another node
+///
+/// Attachments are stored as `<attachment_id>_<node_id>.<ext>`, so this
+/// renames every file belonging to `from_node_id`. Each completed rename is
+/// appended to `renamed` as `(old path, new path)` so a caller can undo it.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of attachments moved
+/// * `Err(String)` - Error message if a file cannot be renamed
+fn reassign_attachments(
+    from_node_id: Uuid,
+    to_node_id: Uuid,
+    renamed: &mut Vec<(std::path::PathBuf, std::path::PathBuf)>,
+) -> Result<usize, String> {
+    let Ok(entries) = std::fs::read_dir("./attachments") else {
+        return Ok(0);
+    };
+    let from_suffix = format!("_{}", from_node_id);
+    let mut moved = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if let Some(attachment_id) = stem.strip_suffix(&from_suffix) {
+            let mut target = path.with_file_name(format!("{}_{}", attachment_id, to_node_id));
+            if let Some(extension) = path.extension() {
+                target.set_extension(extension);
+            }
+            std::fs::rename(&path, &target).map_err(|e| e.to_string())?;
+            renamed.push((path, target));
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+/// Moves the attachments of every merged node to the node that absorbed it
+///
+/// All or nothing: if any rename fails, the renames already done are
+/// reversed before the error is returned.
+fn reassign_merged_attachments(merges: &[NodeMerge]) -> Result<(), String> {
+    let mut renamed = Vec::new();
+    for merge in merges {
+        for merged_id in &merge.merged {
+            if let Err(e) = reassign_attachments(*merged_id, merge.kept, &mut renamed) {
+                for (original, target) in renamed.iter().rev() {
+                    let _ = std::fs::rename(target, original);
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs a merge and moves the merged nodes' attachments, undoing both on failure
+///
+/// The graph is snapshotted first; if moving attachments fails, the
+/// attachment renames are reversed and the snapshot is restored, so the
+/// merge leaves no half-applied state behind.
+fn merge_with_attachments<T>(
+    state: &Database,
+    merge: impl FnOnce(&Database) -> Result<T, String>,
+    merges_of: impl Fn(&T) -> &[NodeMerge],
+) -> Result<T, String> {
+    let snapshot = state.snapshot();
+    let result = merge(state)?;
+    if let Err(e) = reassign_merged_attachments(merges_of(&result)) {
+        state.restore_snapshot(snapshot);
+        return Err(format!("Merge undone: failed to move attachments: {}", e));
+    }
+    Ok(result)
+}
+
+/// Folds duplicate nodes into one
+///
+/// Relationships and attachments of the merged nodes move to the kept node.
+/// If an attachment can't be moved, the whole merge is undone. Pinned nodes
+/// are not folded in and are listed in `skipped_pinned` instead.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `keep_id` - UUID of the node that survives
+/// * `merge_ids` - UUIDs of the nodes to fold into it
+///
+/// # Returns
+/// * `Ok(NodeMerge)` - What was merged and moved
+/// * `Err(String)` - Error message if the kept node doesn't exist or merging fails
+#[tauri::command]
+fn merge_nodes(state: State<AppState>, keep_id: String, merge_ids: Vec<String>) -> Result<NodeMerge, String> {
+    let keep_id = Uuid::parse_str(&keep_id).map_err(|e| e.to_string())?;
+    let merge_ids = merge_ids
+        .iter()
+        .map(|id| Uuid::parse_str(id).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<Uuid>, String>>()?;
+    merge_with_attachments(
+        &state,
+        |state| {
+            state
+                .merge_nodes(keep_id, &merge_ids)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "Node not found".to_string())
+        },
+        std::slice::from_ref,
+    )
+}
+
+/// Merges `SameAs` clusters whose every linking edge meets a confidence threshold
+///
+/// Clusters with any weaker link are reported as skipped for manual review.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `min_confidence` - Minimum confidence every `SameAs` edge in a cluster must have
+///
+/// # Returns
+/// * `Ok(SameAsMergeReport)` - Merges performed and clusters skipped
+/// * `Err(String)` - Error message if the threshold is invalid or merging fails
+#[tauri::command]
+fn auto_merge_same_as(state: State<AppState>, min_confidence: f32) -> Result<SameAsMergeReport, String> {
+    if !(0.0..=1.0).contains(&min_confidence) {
+        return Err("Confidence must be between 0.0 and 1.0".to_string());
+    }
+    merge_with_attachments(
+        &state,
+        |state| state.auto_merge_same_as(min_confidence).map_err(|e| e.to_string()),
+        |report| &report.merges,
+    )
+}
+
+/// Recomputes the cached degree on every node from scratch
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if recomputation fails
+#[tauri::command]
+fn refresh_degrees(state: State<AppState>) -> Result<(), String> {
+    state.refresh_degrees().map_err(|e| e.to_string())
+}
+
+/// Compacts the database, dropping dead records and releasing unused memory
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(CompactionReport)` - Counts of removed records and freed capacity
+/// * `Err(String)` - Error message if compaction fails
+#[tauri::command]
+fn compact_database(state: State<AppState>) -> Result<CompactionReport, String> {
+    state.compact().map_err(|e| e.to_string())
+}
+
+/// Saves the current investigation project to a JSON file
+///
+/// Exports all nodes, relationships, and metadata to a JSON file
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the project file
+/// * `project_name` - Name of the project
+/// * `include_attachments` - Whether to embed attachment contents, not just their metadata (default false)
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if save fails
+#[tauri::command]
+fn save_project(
+    state: State<AppState>,
+    file_path: String,
+    project_name: String,
+    include_attachments: Option<bool>,
+) -> Result<(), String> {
+    write_project(&state, &file_path, project_name, include_attachments.unwrap_or(false))
+}
+
+/// Writes all nodes and relationships to a project file
+///
+/// Shared by the `save_project` command and headless mode. `created_at`
+/// keeps the project's original creation time; only `updated_at` is set to
+/// the current time.
+///
+/// # Arguments
+/// * `state` - Database to save
+/// * `file_path` - Path where to save the project file
+/// * `project_name` - Name of the project
+/// * `include_attachments` - Whether to embed attachment contents as base64
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if save fails
+fn write_project(state: &Database, file_path: &str, project_name: String, include_attachments: bool) -> Result<(), String> {
+    let json_data = serialize_project(state, project_name, include_attachments)?;
+    std::fs::write(file_path, json_data).map_err(|e| e.to_string())?;
+    
+    Ok(())
+}
+
+/// Serializes all nodes, relationships, project metadata, and attachment inventory to JSON
+///
+/// Attachments of nodes that are no longer in the database are left out.
+///
+/// # Arguments
+/// * `state` - Database to serialize
+/// * `project_name` - Name of the project
+/// * `include_attachments` - Whether to embed attachment contents as base64
+///
+/// # Returns
+/// * `Ok(String)` - The project document
+/// * `Err(String)` - Error message if serialization or reading an attachment fails
+fn serialize_project(state: &Database, project_name: String, include_attachments: bool) -> Result<String, String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let node_ids: std::collections::HashSet<String> = nodes.iter().map(|node| node.id.to_string()).collect();
+    let mut attachments = Vec::new();
+    for meta in collect_attachment_meta(None)? {
+        if !node_ids.contains(&meta.node_id) {
+            continue;
+        }
+        let content_base64 = if include_attachments {
+            let path = find_attachment_path(&meta.id).ok_or_else(|| format!("Attachment {} disappeared", meta.id))?;
+            Some(BASE64_STANDARD.encode(std::fs::read(path).map_err(|e| e.to_string())?))
+        } else {
+            None
+        };
+        attachments.push(ProjectAttachment { meta, content_base64 });
+    }
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+    let project_notes = state.project_notes().map_err(|e| e.to_string())?;
+    let created_at = state.project_created_at().map_err(|e| e.to_string())?;
+    let audit_log = state.get_audit_log(None, None).map_err(|e| e.to_string())?;
+    
+    let project_data = ProjectData {
+        nodes,
+        relationships,
+        metadata: ProjectMetadata {
+            name: project_name,
+            created_at: created_at.to_rfc3339(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            version: "1.0.0".to_string(),
+            notes: project_notes.notes,
+            case_summary: project_notes.case_summary,
+        },
+        attachments,
+        audit_log,
+    };
+    
+    serde_json::to_string_pretty(&project_data).map_err(|e| e.to_string())
+}
+
+/// Maps unknown or legacy node types in raw project JSON to `Custom`
+///
+/// Keeps files from older (or newer) versions loadable when a node type has
+/// been renamed or removed. The original type string is preserved in
+/// `metadata.legacy_node_type` so nothing is lost.
+///
+/// # Arguments
+/// * `raw` - Project file parsed as generic JSON, modified in place
+///
+/// # Returns
+/// One entry per migrated node
+fn migrate_legacy_node_types(raw: &mut serde_json::Value) -> Vec<NodeTypeMigration> {
+    let mut migrations = Vec::new();
+    let Some(nodes) = raw.get_mut("nodes").and_then(|n| n.as_array_mut()) else {
+        return migrations;
+    };
+
+    for node in nodes.iter_mut().filter_map(|n| n.as_object_mut()) {
+        let original_type = match node.get("node_type") {
+            Some(serde_json::Value::String(name)) if parse_node_type(name).is_ok() => continue,
+            Some(serde_json::Value::String(name)) => name.clone(),
+            Some(other) => other.to_string(),
+            None => continue,
+        };
+
+        node.insert("node_type".to_string(), serde_json::json!("Custom"));
+        let metadata = node
+            .entry("metadata")
+            .or_insert_with(|| serde_json::json!({}));
+        if !metadata.is_object() {
+            *metadata = serde_json::json!({});
+        }
+        if let Some(metadata) = metadata.as_object_mut() {
+            metadata.insert("legacy_node_type".to_string(), serde_json::json!(original_type));
+        }
+
+        migrations.push(NodeTypeMigration {
+            node_id: node.get("id").and_then(|id| id.as_str()).unwrap_or_default().to_string(),
+            original_type,
+            migrated_to: "Custom".to_string(),
+        });
+    }
+
+    migrations
+}
+
+/// Checks project data for problems that don't prevent loading
+///
+/// # Arguments
+/// * `project_data` - Parsed project file
+///
+/// # Returns
+/// Human-readable warnings, empty if the data looks consistent
+fn validate_project_data(project_data: &ProjectData) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let mut node_ids = std::collections::HashSet::new();
+    for node in &project_data.nodes {
+        if !node_ids.insert(node.id) {
+            warnings.push(format!("Duplicate node ID {} (later entry wins)", node.id));
+        }
+        if !(0.0..=1.0).contains(&node.confidence) {
+            warnings.push(format!("Node {} has out-of-range confidence {}", node.id, node.confidence));
+        }
+    }
+
+    let mut relationship_ids = std::collections::HashSet::new();
+    for rel in &project_data.relationships {
+        if !relationship_ids.insert(rel.id) {
+            warnings.push(format!("Duplicate relationship ID {}", rel.id));
+        }
+        for endpoint in [rel.source_id, rel.target_id] {
+            if !node_ids.contains(&endpoint) {
+                warnings.push(format!("Relationship {} references missing node {}", rel.id, endpoint));
+            }
+        }
+        if !(0.0..=1.0).contains(&rel.confidence) {
+            warnings.push(format!("Relationship {} has out-of-range confidence {}", rel.id, rel.confidence));
+        }
+    }
+
+    warnings
+}
+
+/// Loads an investigation project from a JSON file
+///
+/// Clears current data and loads nodes and relationships from file.
+/// In dry-run mode the file is parsed and validated only, and the database
+/// is left untouched.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path to the project file to load
+/// * `dry_run` - When true, only report what would be loaded
+/// * `create_stubs` - Create placeholder nodes for missing relationship endpoints instead of dropping those relationships (default false)
+///
+/// # Returns
+/// * `Ok(LoadReport)` - Project metadata, counts, validation warnings, and repairs
+/// * `Err(String)` - Error message if the file can't be read or parsed
+#[tauri::command]
+fn load_project(
+    state: State<AppState>,
+    file_path: String,
+    dry_run: Option<bool>,
+    create_stubs: Option<bool>,
+) -> Result<LoadReport, String> {
+    read_project(&state, &file_path, dry_run.unwrap_or(false), create_stubs.unwrap_or(false))
+}
+
+/// Reads a project file and, unless `dry_run` is set, replaces all data with it
+///
+/// Shared by the `load_project` command and headless mode. Nodes and
+/// relationships are stored exactly as read, so their timestamps are kept.
+///
+/// # Arguments
+/// * `state` - Database to load into
+/// * `file_path` - Path to the project file to load
+/// * `dry_run` - When true, only report what would be loaded
+/// * `create_stubs` - Whether to stub missing relationship endpoints instead of dropping the relationships
+///
+/// # Returns
+/// * `Ok(LoadReport)` - Project metadata, counts, validation warnings, and repairs
+/// * `Err(String)` - Error message if the file can't be read or parsed
+fn read_project(state: &Database, file_path: &str, dry_run: bool, create_stubs: bool) -> Result<LoadReport, String> {
+    let json_data = std::fs::read_to_string(file_path).map_err(|e| e.to_string())?;
+    apply_project(state, &json_data, dry_run, create_stubs)
+}
+
+/// Parses a project document and, unless `dry_run` is set, replaces all data with it
+///
+/// Relationships whose endpoints are missing from the document are
+/// repaired before loading (see `repair_dangling_relationships`), so no
+/// dangling edges reach the database.
+///
+/// # Arguments
+/// * `state` - Database to load into
+/// * `json_data` - Project document as written by `serialize_project`
+/// * `dry_run` - When true, only report what would be loaded
+/// * `create_stubs` - Whether to stub missing relationship endpoints instead of dropping the relationships
+///
+/// # Returns
+/// * `Ok(LoadReport)` - Project metadata, counts, validation warnings, and repairs
+/// * `Err(String)` - Error message if the document can't be parsed
+fn apply_project(state: &Database, json_data: &str, dry_run: bool, create_stubs: bool) -> Result<LoadReport, String> {
+    let (mut report, attachments) = apply_project_graph(state, json_data, dry_run, create_stubs)?;
+    if !dry_run {
+        report.warnings.extend(restore_project_attachments(state, &attachments)?);
+    }
+    Ok(report)
+}
+
+/// Does everything `apply_project` does except making attachments available
+///
+/// # Arguments
+/// * `state` - Database to load into
+/// * `json_data` - Project document as written by `serialize_project`
+/// * `dry_run` - When true, only report what would be loaded
+/// * `create_stubs` - Whether to stub missing relationship endpoints instead of dropping the relationships
+///
+/// # Returns
+/// * `Ok((LoadReport, Vec<ProjectAttachment>))` - The load report and the document's attachment entries
+/// * `Err(String)` - Error message if the document can't be parsed
+fn apply_project_graph(
+    state: &Database,
+    json_data: &str,
+    dry_run: bool,
+    create_stubs: bool,
+) -> Result<(LoadReport, Vec<ProjectAttachment>), String> {
+    let mut raw: serde_json::Value = serde_json::from_str(json_data).map_err(|e| e.to_string())?;
+    let type_migrations = migrate_legacy_node_types(&mut raw);
+    let mut project_data: ProjectData = serde_json::from_value(raw).map_err(|e| e.to_string())?;
+
+    // Validate what will actually be loaded, so repaired relationships aren't also reported as missing nodes
+    let (dangling_relationships_dropped, stub_nodes_created) =
+        repair_dangling_relationships(&mut project_data, create_stubs, &state.settings().map_err(|e| e.to_string())?);
+    let mut warnings = validate_project_data(&project_data);
+    let node_count = project_data.nodes.len();
+    let relationship_count = project_data.relationships.len();
+    let attachment_count = project_data.attachments.len();
+
+    if dry_run {
+        let report = LoadReport {
+            metadata: project_data.metadata,
+            nodes: node_count,
+            relationships: relationship_count,
+            warnings,
+            type_migrations,
+            attachments: attachment_count,
+            dangling_relationships_dropped,
+            stub_nodes_created,
+            dry_run,
+        };
+        return Ok((report, project_data.attachments));
+    }
+
+    // Replace existing data, restoring the saved history instead of logging the load itself
+    state
+        .restore_project(project_data.nodes, project_data.relationships, project_data.audit_log)
+        .map_err(|e| e.to_string())?;
+
+    let repaired = state.sanitize_numeric_fields().map_err(|e| e.to_string())?;
+    if repaired > 0 {
+        warnings.push(format!("Repaired invalid confidence or weight values on {} entities", repaired));
+    }
+
+    state
+        .set_project_notes(ProjectNotes {
+            notes: project_data.metadata.notes.clone(),
+            case_summary: project_data.metadata.case_summary.clone(),
+        })
+        .map_err(|e| e.to_string())?;
+
+    // Keep the file's creation time so re-saving doesn't reset it
+    if let Ok(created_at) = chrono::DateTime::parse_from_rfc3339(&project_data.metadata.created_at) {
+        state
+            .set_project_created_at(created_at.with_timezone(&chrono::Utc))
+            .map_err(|e| e.to_string())?;
+    }
+    
+    let report = LoadReport {
+        metadata: project_data.metadata,
+        nodes: node_count,
+        relationships: relationship_count,
+        warnings,
+        type_migrations,
+        attachments: attachment_count,
+        dangling_relationships_dropped,
+        stub_nodes_created,
+        dry_run,
+    };
+    Ok((report, project_data.attachments))
+}
+
+/// Makes the attachments listed in a loaded project available locally
+///
+/// Entries for nodes that are not in the database are skipped; see
+/// `restore_attachment` for the rest.
+///
+/// # Arguments
+/// * `state` - Database the project was loaded into
+/// * `attachments` - Attachment entries from the project document
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - A warning for each attachment that is not available
+/// * `Err(String)` - Error message if writing an attachment fails
+fn restore_project_attachments(state: &Database, attachments: &[ProjectAttachment]) -> Result<Vec<String>, String> {
+    let loaded_node_ids: std::collections::HashSet<String> = state
+        .get_all_nodes()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|node| node.id.to_string())
+        .collect();
+
+    let mut warnings = Vec::new();
+    for attachment in attachments {
+        if !loaded_node_ids.contains(&attachment.meta.node_id) {
+            warnings.push(format!(
+                "Attachment {} belongs to node {} which is not in the project; attachment skipped",
+                attachment.meta.id, attachment.meta.node_id
+            ));
+            continue;
+        }
+        if let Some(warning) = restore_attachment(attachment)? {
+            warnings.push(warning);
+        }
+    }
+    Ok(warnings)
+}
+
+/// Removes or stubs relationships that reference nodes missing from a project document
+///
+/// Hand-edited or generated files can contain relationships whose source
+/// or target is not among the document's nodes. By default those
+/// relationships are dropped; with `create_stubs`, a `Custom` placeholder
+/// node carrying the missing ID is added instead, marked with
+/// `metadata.stub = true` so it can be found and filled in later. Stubs are
+/// new nodes, so they get the auto-tag rules for `Custom`.
+///
+/// # Arguments
+/// * `project_data` - Parsed project document, modified in place
+/// * `create_stubs` - Whether to create placeholder nodes instead of dropping relationships
+/// * `settings` - Settings holding the auto-tag rules
+///
+/// # Returns
+/// Number of relationships dropped and number of stub nodes created
+fn repair_dangling_relationships(project_data: &mut ProjectData, create_stubs: bool, settings: &Settings) -> (usize, usize) {
+    let mut node_ids: std::collections::HashSet<Uuid> = project_data.nodes.iter().map(|node| node.id).collect();
+
+    if !create_stubs {
+        let before = project_data.relationships.len();
+        project_data
+            .relationships
+            .retain(|rel| node_ids.contains(&rel.source_id) && node_ids.contains(&rel.target_id));
+        return (before - project_data.relationships.len(), 0);
+    }
+
+    let mut stubs = Vec::new();
+    for rel in &project_data.relationships {
+        for endpoint in [rel.source_id, rel.target_id] {
+            if node_ids.insert(endpoint) {
+                let mut stub = Node::new(NodeType::Custom, format!("Missing node {}", endpoint))
+                    .with_description("Placeholder for a node referenced by a relationship but missing from the project file".to_string());
+                stub.id = endpoint;
+                stub.metadata = serde_json::json!({ "stub": true });
+                settings.apply_auto_tags(&mut stub);
+                stubs.push(stub);
+            }
+        }
+    }
+    let created = stubs.len();
+    project_data.nodes.extend(stubs);
+    (0, created)
+}
+
+/// Makes an attachment listed in a project file available locally
+///
+/// An attachment counts as available only if a file with its full stored
+/// name exists and matches the recorded SHA-256 hash. Otherwise embedded
+/// content is written to the attachments directory, unless some file with
+/// that attachment ID already exists, which is never overwritten. Entries
+/// whose IDs or file type can't form a safe file name are skipped, and
+/// content whose SHA-256 does not match the recorded hash is not written.
+///
+/// # Arguments
+/// * `attachment` - Attachment entry from the project file
+///
+/// # Returns
+/// * `Ok(None)` - The attachment is available locally
+/// * `Ok(Some(String))` - A warning if the attachment is invalid, missing, conflicting, or its content is corrupt
+/// * `Err(String)` - Error message if reading or writing the file fails
+fn restore_attachment(attachment: &ProjectAttachment) -> Result<Option<String>, String> {
+    let meta = &attachment.meta;
+    let stored_name = match stored_attachment_name(meta) {
+        Ok(name) => name,
+        Err(message) => return Ok(Some(format!("{}; attachment skipped", message))),
+    };
+    let stored_path = std::path::Path::new("./attachments").join(&stored_name);
+    if stored_path.is_file() {
+        let mut hasher = Sha256::new();
+        let mut file = std::fs::File::open(&stored_path).map_err(|e| e.to_string())?;
+        std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+        if format!("{:x}", hasher.finalize()) != meta.sha256 {
+            return Ok(Some(format!("Local copy of attachment {} does not match its SHA-256 hash", meta.id)));
+        }
+        return Ok(None);
+    }
+    if find_attachment_path(&meta.id).is_some() {
+        return Ok(Some(format!("Attachment {} exists locally under a different name; attachment skipped", meta.id)));
+    }
+    let Some(content_base64) = &attachment.content_base64 else {
+        return Ok(Some(format!("Attachment {} of node {} is not available locally", meta.id, meta.node_id)));
+    };
+    let Ok(content) = BASE64_STANDARD.decode(content_base64) else {
+        return Ok(Some(format!("Attachment {} has invalid base64 content", meta.id)));
+    };
+    if format!("{:x}", Sha256::digest(&content)) != meta.sha256 {
+        return Ok(Some(format!("Attachment {} content does not match its SHA-256 hash", meta.id)));
+    }
+
+    std::fs::create_dir_all("./attachments").map_err(|e| e.to_string())?;
+    std::fs::write(stored_path, content).map_err(|e| e.to_string())?;
+    Ok(None)
+}
+
+/// Saves the current investigation project to a password-encrypted file
+///
+/// The project JSON is encrypted with AES-256-GCM using a key derived from
+/// the password with Argon2id; see the `crypto` module for the file layout.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the encrypted project file
+/// * `project_name` - Name of the project
+/// * `password` - Password protecting the file
+/// * `include_attachments` - Whether to embed attachment contents, not just their metadata (default false)
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the password is empty or save fails
+#[tauri::command]
+fn save_project_encrypted(
+    state: State<AppState>,
+    file_path: String,
+    project_name: String,
+    password: String,
+    include_attachments: Option<bool>,
+) -> Result<(), String> {
+    if password.is_empty() {
+        return Err("Password must not be empty".to_string());
+    }
+    let json_data = serialize_project(&state, project_name, include_attachments.unwrap_or(false))?;
+    let encrypted = crypto::encrypt(json_data.as_bytes(), &password)?;
+    std::fs::write(&file_path, encrypted).map_err(|e| e.to_string())
+}
+
+/// Loads an investigation project from a password-encrypted file
+///
+/// The file is fully decrypted and authenticated before anything is
+/// loaded, so a wrong password leaves the current data untouched.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path to the encrypted project file
+/// * `password` - Password the file was saved with
+/// * `dry_run` - When true, only report what would be loaded
+/// * `create_stubs` - Create placeholder nodes for missing relationship endpoints instead of dropping those relationships (default false)
+///
+/// # Returns
+/// * `Ok(LoadReport)` - Project metadata, counts, validation warnings, and repairs
+/// * `Err(String)` - Error message if the password is wrong or the file can't be read
+#[tauri::command]
+fn load_project_encrypted(
+    state: State<AppState>,
+    file_path: String,
+    password: String,
+    dry_run: Option<bool>,
+    create_stubs: Option<bool>,
+) -> Result<LoadReport, String> {
+    let data = std::fs::read(&file_path).map_err(|e| e.to_string())?;
+    let plaintext = crypto::decrypt(&data, &password)?;
+    let json_data = String::from_utf8(plaintext).map_err(|e| e.to_string())?;
+    apply_project(&state, &json_data, dry_run.unwrap_or(false), create_stubs.unwrap_or(false))
+}
+
+/// Exports investigation data to CSV format
+///
+/// Creates a CSV file with separate sections for nodes and relationships
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the CSV file
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_csv(state: State<AppState>, file_path: String) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+    let csv_content = export::render_csv(&nodes, &relationships);
+    std::fs::write(&file_path, csv_content).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Exports investigation data to GraphML format
+///
+/// Creates a GraphML file compatible with graph analysis tools like Gephi
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the GraphML file
+/// * `include_communities` - Whether to run community detection and add a `community` node attribute (default false)
+/// * `include_legend` - Whether to also write a `.legend.csv` file mapping node types to colors (default false)
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_graphml(
+    state: State<AppState>,
+    file_path: String,
+    include_communities: Option<bool>,
+    include_legend: Option<bool>,
+) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+    let communities = if include_communities.unwrap_or(false) {
+        Some(state.detect_communities().map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    let settings = state.settings().map_err(|e| e.to_string())?;
+    let graphml = export::render_graphml(
+        &nodes,
+        &relationships,
+        communities.as_ref(),
+        settings.freshness_max_age_days,
+    );
+    std::fs::write(&file_path, graphml).map_err(|e| e.to_string())?;
+    if include_legend.unwrap_or(false) {
+        let legend_path = std::path::Path::new(&file_path).with_extension("legend.csv");
+        std::fs::write(legend_path, export::render_legend_csv(&nodes)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Serializes nodes and relationships as a project-style JSON export
+///
+/// Each relationship also carries a `parallel_index` (see
+/// [`export::parallel_indices`]) so viewers can separate parallel edges.
+/// The extra field is ignored when the file is loaded as a project.
+///
+/// # Arguments
+/// * `nodes` - Nodes to export
+/// * `relationships` - Relationships to export
+///
+/// # Returns
+/// * `Ok(String)` - Pretty-printed JSON
+/// * `Err(String)` - Error message if serialization fails
+fn render_json_export(nodes: Vec<Node>, relationships: Vec<Relationship>) -> Result<String, String> {
+    let parallel = export::parallel_indices(&relationships);
+    let project_data = ProjectData {
+        nodes,
+        relationships,
+        metadata: ProjectMetadata {
+            name: "Exported Data".to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            version: "1.0.0".to_string(),
+            notes: String::new(),
+            case_summary: String::new(),
+        },
+        attachments: Vec::new(),
+        audit_log: Vec::new(),
+    };
+
+    let mut document = serde_json::to_value(&project_data).map_err(|e| e.to_string())?;
+    if let Some(relationships) = document.get_mut("relationships").and_then(|r| r.as_array_mut()) {
+        for (value, relationship) in relationships.iter_mut().zip(&project_data.relationships) {
+            if let Some(object) = value.as_object_mut() {
+                object.insert("parallel_index".to_string(), serde_json::json!(parallel[&relationship.id]));
+            }
+        }
+    }
+    
+    serde_json::to_string_pretty(&document).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn export_json(state: State<AppState>, file_path: String) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+    
+    let json_data = render_json_export(nodes, relationships)?;
+    std::fs::write(&file_path, json_data).map_err(|e| e.to_string())?;
+    
+    Ok(())
+}
+
+/// Exports investigation data as RDF in Turtle syntax
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the Turtle file
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_turtle(state: State<AppState>, file_path: String) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+
+    let turtle = export::render_turtle(&nodes, &relationships);
+    std::fs::write(&file_path, turtle).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Exports the graph as a standalone SVG image
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the SVG file
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_svg(state: State<AppState>, file_path: String) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+
+    std::fs::write(&file_path, export::render_svg(&nodes, &relationships)).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Exports investigation data as a Graphviz DOT file
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the DOT file
+/// * `include_legend` - Whether to embed a legend cluster listing each node type present (default false)
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_dot(state: State<AppState>, file_path: String, include_legend: Option<bool>) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+
+    let dot = export::render_dot(&nodes, &relationships, include_legend.unwrap_or(false));
+    std::fs::write(&file_path, dot).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Exports investigation data as networkx node-link JSON
+///
+/// The file loads in Python with `json_graph.node_link_graph(data, edges="links")`.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the JSON file
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_networkx_json(state: State<AppState>, file_path: String) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+
+    let document = export::render_networkx_json(&nodes, &relationships);
+    let json_data = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+    std::fs::write(&file_path, json_data).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// File extensions understood by the unified `export` command
+const SUPPORTED_EXPORT_EXTENSIONS: [&str; 9] = ["csv", "json", "graphml", "gexf", "html", "ttl", "dot", "cypher", "svg"];
+
+/// Writes nodes and relationships to a file, choosing the format from its extension
+///
+/// # Arguments
+/// * `state` - Database whose settings control format options
+/// * `nodes` - Nodes to export
+/// * `relationships` - Relationships to export
+/// * `file_path` - Destination path; its extension selects the format
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the extension is unsupported or writing fails
+fn write_export(
+    state: &Database,
+    nodes: Vec<Node>,
+    relationships: Vec<Relationship>,
+    file_path: &str,
+) -> Result<(), String> {
+    let extension = std::path::Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    write_export_as(state, extension, nodes, relationships, file_path)
+}
+
+/// Writes nodes and relationships to a file in the given format
+///
+/// # Arguments
+/// * `state` - Database whose settings control format options
+/// * `format` - Export format, one of [`SUPPORTED_EXPORT_EXTENSIONS`] (case-insensitive)
+/// * `nodes` - Nodes to export
+/// * `relationships` - Relationships to export
+/// * `file_path` - Destination path
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the format is unsupported or writing fails
+fn write_export_as(
+    state: &Database,
+    format: &str,
+    nodes: Vec<Node>,
+    relationships: Vec<Relationship>,
+    file_path: &str,
+) -> Result<(), String> {
+    let extension = format.trim_start_matches('.').to_lowercase();
+    let settings = state.settings().map_err(|e| e.to_string())?;
+
+    let content = match extension.as_str() {
+        "csv" => export::render_csv(&nodes, &relationships),
+        "json" => render_json_export(nodes, relationships)?,
+        "graphml" => export::render_graphml(&nodes, &relationships, None, settings.freshness_max_age_days),
+        "gexf" => export::render_gexf(&nodes, &relationships, None, settings.freshness_max_age_days),
+        "html" => export::render_html_report("Investigation Report", &nodes, &relationships),
+        "ttl" => export::render_turtle(&nodes, &relationships),
+        "dot" => export::render_dot(&nodes, &relationships, false),
+        "cypher" => export::render_cypher(&nodes, &relationships),
+        "svg" => export::render_svg(&nodes, &relationships),
+        _ => {
+            return Err(format!(
+                "Unsupported export format \".{}\". Supported extensions: {}",
+                extension,
+                SUPPORTED_EXPORT_EXTENSIONS.map(|ext| format!(".{}", ext)).join(", ")
+            ))
+        }
+    };
+
+    std::fs::write(file_path, content).map_err(|e| e.to_string())
+}
+
+/// Exports investigation data, picking the format from the file extension
+///
+/// Supports `.csv`, `.json`, `.graphml`, `.gexf`, `.html`, `.ttl`, `.dot`, `.cypher`, and `.svg`, so the frontend can
+/// offer a single export button.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the export
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message listing supported extensions if the extension is unknown
+#[tauri::command]
+fn export(state: State<AppState>, file_path: String) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+    write_export(&state, nodes, relationships, &file_path)
+}
+
+/// Result of an incremental export
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DeltaExportReport {
+    /// Number of changed nodes written
+    nodes: usize,
+    /// Number of changed relationships written
+    relationships: usize,
+    /// IDs of nodes deleted since the cutoff
+    deleted_nodes: Vec<String>,
+    /// IDs of relationships deleted since the cutoff
+    deleted_relationships: Vec<String>,
+}
+
+/// Exports only what changed since a point in time, for syncing external systems
+///
+/// Nodes and relationships whose `updated_at` is after `since` are written
+/// in the requested format. Deleted IDs can't be represented in every
+/// format, so they are returned instead of written.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `since` - Exclusive cutoff (RFC 3339)
+/// * `file_path` - Path where to save the export
+/// * `format` - Export format, one of [`SUPPORTED_EXPORT_EXTENSIONS`]
+///
+/// # Returns
+/// * `Ok(DeltaExportReport)` - Counts of exported entities and the deleted IDs
+/// * `Err(String)` - Error message if the format is unsupported or export fails
+#[tauri::command]
+fn export_delta(
+    state: State<AppState>,
+    since: chrono::DateTime<chrono::Utc>,
+    file_path: String,
+    format: String,
+) -> Result<DeltaExportReport, String> {
+    let delta = state.changes_since(since).map_err(|e| e.to_string())?;
+    let report = DeltaExportReport {
+        nodes: delta.nodes.len(),
+        relationships: delta.relationships.len(),
+        deleted_nodes: delta.deleted_nodes.iter().map(|id| id.to_string()).collect(),
+        deleted_relationships: delta.deleted_relationships.iter().map(|id| id.to_string()).collect(),
+    };
+    write_export_as(&state, &format, delta.nodes, delta.relationships, &file_path)?;
+    Ok(report)
+}
+
+/// Exports the investigation as it looked at a past point in time
+///
+/// Only nodes and relationships created at or before `timestamp` are
+/// included; relationships touching a node created later are dropped.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `timestamp` - Snapshot time (RFC 3339)
+/// * `format` - Export format, one of [`SUPPORTED_EXPORT_EXTENSIONS`]
+/// * `file_path` - Path where to save the export
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the format is unsupported or export fails
+#[tauri::command]
+fn export_as_of(
+    state: State<AppState>,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    format: String,
+    file_path: String,
+) -> Result<(), String> {
+    let (nodes, relationships) = state.snapshot_as_of(timestamp).map_err(|e| e.to_string())?;
+    write_export_as(&state, &format, nodes, relationships, &file_path)
+}
+
+/// Exports the investigation as a self-contained HTML report
+///
+/// The report contains summary statistics, node and relationship tables, and
+/// an embedded SVG drawing of the graph, with no external assets.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the HTML file
+/// * `title` - Report title
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_html_report(state: State<AppState>, file_path: String, title: String) -> Result<(), String> {
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+
+    let html = export::render_html_report(&title, &nodes, &relationships);
+    std::fs::write(&file_path, html).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Retrieves the audit log, optionally limited to a time range
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `from` - Optional inclusive lower bound (RFC 3339)
+/// * `to` - Optional inclusive upper bound (RFC 3339)
+///
+/// # Returns
+/// * `Ok(Vec<AuditEntry>)` - Entries in chronological order
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_audit_log(
+    state: State<AppState>,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<AuditEntry>, String> {
+    state.get_audit_log(from, to).map_err(|e| e.to_string())
+}
+
+/// Exports the audit log to a CSV file
+///
+/// Writes one row per logged operation, sorted chronologically, with
+/// columns for timestamp, operation, entity type, entity ID, changed
+/// fields (semicolon-separated), and the old and new value of each field
+/// (`field: old -> new`, semicolon-separated).
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the CSV file
+/// * `from` - Optional inclusive lower bound (RFC 3339)
+/// * `to` - Optional inclusive upper bound (RFC 3339)
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_audit_log(
+    state: State<AppState>,
+    file_path: String,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<(), String> {
+    let entries = state.get_audit_log(from, to).map_err(|e| e.to_string())?;
+    let file = std::fs::File::create(&file_path).map_err(|e| e.to_string())?;
+    write_audit_log_csv(&entries, file)
+}
+
+/// Writes audit entries as CSV rows, in the layout used by `export_audit_log`
+fn write_audit_log_csv(entries: &[AuditEntry], sink: impl std::io::Write) -> Result<(), String> {
+    let mut writer = csv::Writer::from_writer(sink);
+    writer
+        .write_record(["Timestamp", "Operation", "EntityType", "EntityID", "ChangedFields", "Changes"])
+        .map_err(|e| e.to_string())?;
+    for entry in entries {
+        writer
+            .write_record([
+                entry.timestamp.to_rfc3339(),
+                format!("{:?}", entry.operation),
+                format!("{:?}", entry.entity_type),
+                entry.entity_id.to_string(),
+                entry.changed_fields.join(";"),
+                entry
+                    .changes
+                    .iter()
+                    .map(|change| format!("{}: {} -> {}", change.field, change.old, change.new))
+                    .collect::<Vec<_>>()
+                    .join(";"),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// File name used for a node type by `export_csv_by_type`
+fn node_type_csv_name(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::Person => "persons.csv",
+        NodeType::Organization => "organizations.csv",
+        NodeType::CryptoWallet => "crypto_wallets.csv",
+        NodeType::SocialAccount => "social_accounts.csv",
+        NodeType::Domain => "domains.csv",
+        NodeType::IpAddress => "ip_addresses.csv",
+        NodeType::Email => "emails.csv",
+        NodeType::Phone => "phones.csv",
+        NodeType::Document => "documents.csv",
+        NodeType::Event => "events.csv",
+        NodeType::Custom => "custom_entities.csv",
+    }
+}
+
+/// Exports one CSV per node type present, plus a relationships CSV
+///
+/// Node files share the common columns and add one `meta.<key>` column for
+/// every top-level metadata key used by nodes of that type, so each file
+/// carries the fields relevant to its type.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `dir` - Directory to write the files into (created if missing)
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Paths of the written files
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_csv_by_type(state: State<AppState>, dir: String) -> Result<Vec<String>, String> {
+    write_csv_by_type(&state, std::path::Path::new(&dir))
+}
+
+/// Does the work of `export_csv_by_type`
+fn write_csv_by_type(state: &Database, dir: &std::path::Path) -> Result<Vec<String>, String> {
+    let mut nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+    nodes.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let mut by_type: std::collections::BTreeMap<&'static str, Vec<&Node>> = std::collections::BTreeMap::new();
+    for node in &nodes {
+        by_type.entry(node_type_csv_name(&node.node_type)).or_default().push(node);
+    }
+
+    let mut written = Vec::new();
+    for (file_name, typed_nodes) in by_type {
+        let metadata_keys: std::collections::BTreeSet<&String> = typed_nodes
+            .iter()
+            .filter_map(|node| node.metadata.as_object())
+            .flat_map(|metadata| metadata.keys())
+            .collect();
+
+        let path = dir.join(file_name);
+        let mut writer = csv::Writer::from_path(&path).map_err(|e| e.to_string())?;
+        let mut header: Vec<String> = ["ID", "Label", "Description", "Tags", "Confidence", "Source", "CreatedAt"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        header.extend(metadata_keys.iter().map(|key| format!("meta.{}", key)));
+        writer.write_record(&header).map_err(|e| e.to_string())?;
+
+        for node in typed_nodes {
+            let mut record = vec![
+                node.id.to_string(),
+                node.label.clone(),
+                node.description.clone().unwrap_or_default(),
+                node.tags.join(";"),
+                node.confidence.to_string(),
+                node.source.clone().unwrap_or_default(),
+                node.created_at.to_rfc3339(),
+            ];
+            record.extend(metadata_keys.iter().map(|key| match node.metadata.get(key.as_str()) {
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(serde_json::Value::Null) | None => String::new(),
+                Some(value) => value.to_string(),
+            }));
+            writer.write_record(&record).map_err(|e| e.to_string())?;
+        }
+        writer.flush().map_err(|e| e.to_string())?;
+        written.push(path.to_string_lossy().into_owned());
+    }
+
+    let path = dir.join("relationships.csv");
+    let mut writer = csv::Writer::from_path(&path).map_err(|e| e.to_string())?;
+    writer
+        .write_record([
+            "ID", "SourceID", "TargetID", "RelationType", "Description", "Weight", "Confidence", "Source", "CreatedAt",
+        ])
+        .map_err(|e| e.to_string())?;
+    for rel in &relationships {
+        writer
+            .write_record([
+                rel.id.to_string(),
+                rel.source_id.to_string(),
+                rel.target_id.to_string(),
+                format!("{:?}", rel.relation_type),
+                rel.description.clone().unwrap_or_default(),
+                rel.weight.to_string(),
+                rel.confidence.to_string(),
+                rel.source.clone().unwrap_or_default(),
+                rel.created_at.to_rfc3339(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    written.push(path.to_string_lossy().into_owned());
+
+    Ok(written)
+}
+
+/// Column heading used in the provenance matrix for relationships without a source
+const UNSOURCED_COLUMN: &str = "(no source)";
+
+/// Exports a node-pair by source matrix showing which sources attest each connection
+///
+/// Each row is an unordered pair of connected nodes, each column a distinct
+/// relationship source, and a cell holds `x` when at least one relationship
+/// between the pair cites that source. A `SourceCount` column makes pairs
+/// resting on a single source easy to filter.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the CSV file
+///
+/// # Returns
+/// * `Ok(usize)` - Number of node pairs written
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_provenance_matrix(state: State<AppState>, file_path: String) -> Result<usize, String> {
+    let file = std::fs::File::create(&file_path).map_err(|e| e.to_string())?;
+    write_provenance_matrix(&state, file)
+}
+
+/// Writes the provenance matrix as CSV rows, in the layout used by `export_provenance_matrix`
+fn write_provenance_matrix(state: &Database, sink: impl std::io::Write) -> Result<usize, String> {
+    let labels: std::collections::HashMap<Uuid, String> = state
+        .get_all_nodes()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|node| (node.id, node.label))
+        .collect();
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+
+    let mut pairs: std::collections::BTreeMap<(Uuid, Uuid), std::collections::BTreeSet<String>> =
+        std::collections::BTreeMap::new();
+    let mut sources = std::collections::BTreeSet::new();
+    for rel in &relationships {
+        let pair = if rel.source_id <= rel.target_id {
+            (rel.source_id, rel.target_id)
+        } else {
+            (rel.target_id, rel.source_id)
+        };
+        let mut attested = rel.source_list();
+        if attested.is_empty() {
+            attested.push(UNSOURCED_COLUMN.to_string());
+        }
+        for source in attested {
+            sources.insert(source.clone());
+            pairs.entry(pair).or_default().insert(source);
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(sink);
+    let mut header = vec!["NodeA".to_string(), "NodeB".to_string(), "SourceCount".to_string()];
+    header.extend(sources.iter().cloned());
+    writer.write_record(&header).map_err(|e| e.to_string())?;
+
+    let label = |id: &Uuid| labels.get(id).cloned().unwrap_or_else(|| id.to_string());
+    for ((a, b), attested) in &pairs {
+        let mut record = vec![label(a), label(b), attested.len().to_string()];
+        record.extend(sources.iter().map(|source| {
+            if attested.contains(source) { "x".to_string() } else { String::new() }
+        }));
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+
+    Ok(pairs.len())
+}
+
+/// Writes one JSON file per node, named after its label
+///
+/// Each file holds the node and the relationships touching it. Filenames
+/// go through `export::sanitize_filename`, so labels like `../etc/passwd`
+/// or `CON` cannot escape the directory or clash with each other.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_ids` - UUIDs of the nodes to export; all nodes if omitted
+/// * `dir` - Directory to write the files into (created if missing)
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Paths of the written files
+/// * `Err(String)` - Error message if a node doesn't exist or writing fails
+#[tauri::command]
+fn export_nodes_individually(
+    state: State<AppState>,
+    node_ids: Option<Vec<String>>,
+    dir: String,
+) -> Result<Vec<String>, String> {
+    let mut nodes = match node_ids {
+        Some(ids) => {
+            let mut nodes = Vec::new();
+            for id in &ids {
+                let uuid = Uuid::parse_str(id).map_err(|e| e.to_string())?;
+                let node = state
+                    .get_node(uuid)
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("Node {} not found", id))?;
+                nodes.push(node);
+            }
+            nodes
+        }
+        None => state.get_all_nodes().map_err(|e| e.to_string())?,
+    };
+    nodes.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+
+    let dir = std::path::Path::new(&dir);
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let mut taken = std::collections::HashSet::new();
+    let mut written = Vec::new();
+    for node in nodes {
+        let relationships = state.get_node_relationships(node.id).map_err(|e| e.to_string())?;
+        let path = dir.join(format!("{}.json", export::sanitize_filename(&node.label, node.id, &mut taken)));
+        let document = serde_json::json!({ "node": node, "relationships": relationships });
+        let json_data = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json_data).map_err(|e| e.to_string())?;
+        written.push(path.to_string_lossy().into_owned());
+    }
+
+    Ok(written)
+}
+
+/// Exports money-relevant relationships as a flat CSV for flow analysis
+///
+/// Only `TransactsWith`, `Owns`, and `Controls` relationships between
+/// `Person`, `Organization`, and `CryptoWallet` nodes are written; every
+/// other relationship is skipped. The amount comes from the relationship's
+/// `metadata.amount` and is left blank when absent.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the CSV file
+///
+/// # Returns
+/// * `Ok(usize)` - Number of flows written
+/// * `Err(String)` - Error message if export fails
+#[tauri::command]
+fn export_financial_flows(state: State<AppState>, file_path: String) -> Result<usize, String> {
+    let file = std::fs::File::create(&file_path).map_err(|e| e.to_string())?;
+    write_financial_flows(&state, file)
+}
+
+/// Writes financial flows as CSV rows, in the layout used by `export_financial_flows`
+fn write_financial_flows(state: &Database, sink: impl std::io::Write) -> Result<usize, String> {
+    let nodes: std::collections::HashMap<Uuid, Node> = state
+        .get_all_nodes()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|node| (node.id, node))
+        .collect();
+    let relationships = state.get_relationships().map_err(|e| e.to_string())?;
+
+    let is_party = |id: &Uuid| {
+        nodes.get(id).filter(|node| {
+            matches!(node.node_type, NodeType::Person | NodeType::Organization | NodeType::CryptoWallet)
+        })
+    };
+
+    let mut writer = csv::Writer::from_writer(sink);
+    writer
+        .write_record(["From", "To", "RelationType", "Amount", "Confidence", "Source"])
+        .map_err(|e| e.to_string())?;
+
+    let mut written = 0;
+    for rel in &relationships {
+        if !matches!(rel.relation_type, RelationType::TransactsWith | RelationType::Owns | RelationType::Controls) {
+            continue;
+        }
+        let (Some(from), Some(to)) = (is_party(&rel.source_id), is_party(&rel.target_id)) else {
+            continue;
+        };
+        let amount = match rel.metadata.get("amount") {
+            Some(serde_json::Value::String(amount)) => amount.clone(),
+            Some(serde_json::Value::Null) | None => String::new(),
+            Some(amount) => amount.to_string(),
+        };
+        writer
+            .write_record([
+                from.label.clone(),
+                to.label.clone(),
+                format!("{:?}", rel.relation_type),
+                amount,
+                rel.confidence.to_string(),
+                rel.source.clone().unwrap_or_default(),
+            ])
+            .map_err(|e| e.to_string())?;
+        written += 1;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+
+    Ok(written)
+}
+
+/// Writes report content to a file
+///
+/// # Arguments
+/// * `file_path` - Path of the report file
+/// * `content` - Text to write
+/// * `append` - When true, add to the end of the file (creating it if absent)
+///   instead of overwriting it; defaults to false
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if writing fails
+#[tauri::command]
+fn write_report(file_path: String, content: String, append: Option<bool>) -> Result<(), String> {
+    if append.unwrap_or(false) {
+        use std::io::Write;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)
+            .map_err(|e| e.to_string())?;
+        file.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    } else {
+        std::fs::write(&file_path, content).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn save_attachment(node_id: String, filename: String, content_base64: String) -> Result<String, String> {
+    // Create attachments directory if it doesn't exist
+    let attachments_dir = "./attachments";
+    std::fs::create_dir_all(attachments_dir).map_err(|e| e.to_string())?;
+    
+    // Decode base64 content
+    let content = base64::prelude::BASE64_STANDARD.decode(&content_base64).map_err(|e| e.to_string())?;
+    
+    let node_id = Uuid::parse_str(&node_id).map_err(|e| e.to_string())?;
+
+    // Generate unique filename
+    let attachment_id = Uuid::new_v4().to_string();
+    // Extensions that couldn't be restored from a project file or archive are stored as .bin
+    let file_extension = std::path::Path::new(&filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| attachment_file_name(&attachment_id, &node_id.to_string(), ext).is_ok())
+        .unwrap_or("bin");
+    let stored_filename = format!("{}_{}.{}", attachment_id, node_id, file_extension);
+    let file_path = format!("{}/{}", attachments_dir, stored_filename);
+    
+    // Save file
+    std::fs::write(&file_path, content).map_err(|e| e.to_string())?;
+    
+    Ok(attachment_id)
+}
+
+/// Counts stored attachments per node ID
+///
+/// Attachments are stored as `<attachment_id>_<node_id>.<ext>`, so the
+/// directory is scanned once and grouped by the node ID part of the name.
+///
+/// # Returns
+/// * Map from node ID to number of attachments; empty if the directory is missing
+fn attachment_counts() -> std::collections::HashMap<String, usize> {
+    let mut counts = std::collections::HashMap::new();
+    if let Ok(entries) = std::fs::read_dir("./attachments") {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if let Some((_, node_id)) = stem.split_once('_') {
+                *counts.entry(node_id.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+#[tauri::command]
+fn list_attachments(node_id: String) -> Result<Vec<AttachmentData>, String> {
+    let attachments_dir = "./attachments";
+    let mut attachments = Vec::new();
+    
+    if let Ok(entries) = std::fs::read_dir(attachments_dir) {
+        for entry in entries.flatten() {
+            if let Some(filename) = entry.file_name().to_str() {
+                if filename.contains(&format!("_{}", node_id)) {
+                    // Parse filename to extract attachment ID and original name
+                    let parts: Vec<&str> = filename.split('_').collect();
+                    if parts.len() >= 2 {
+                        let attachment_id = parts[0].to_string();
+                        let file_extension = std::path::Path::new(filename)
+                            .extension()
+                            .and_then(|ext| ext.to_str())
+                            .unwrap_or("bin");
+                        
+                        // Read file content and encode as base64
+                        if let Ok(content) = std::fs::read(entry.path()) {
+                            let content_base64 = base64::prelude::BASE64_STANDARD.encode(&content);
+                            
+                            attachments.push(AttachmentData {
+                                id: attachment_id,
+                                node_id: node_id.clone(),
+                                filename: format!("attachment.{}", file_extension),
+                                file_type: file_extension.to_string(),
+                                content_base64,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    Ok(attachments)
+}
+
+/// Lists attachment metadata for a node without loading file contents
+///
+/// The SHA-256 hash is computed by streaming each file through the hasher,
+/// so memory use stays constant regardless of attachment size.
+///
+/// # Arguments
+/// * `node_id` - ID of the node whose attachments to list
+///
+/// # Returns
+/// * `Ok(Vec<AttachmentMeta>)` - Metadata for each attachment of the node
+/// * `Err(String)` - Error message if a file could not be read
+#[tauri::command]
+fn list_attachments_meta(node_id: String) -> Result<Vec<AttachmentMeta>, String> {
+    collect_attachment_meta(Some(&node_id))
+}
+
+/// Reads metadata for stored attachments, hashing each file by streaming it
+///
+/// # Arguments
+/// * `node_id` - Only list attachments of this node; `None` lists every attachment
+///
+/// # Returns
+/// * `Ok(Vec<AttachmentMeta>)` - Metadata for each matching attachment
+/// * `Err(String)` - Error message if a file could not be read
+fn collect_attachment_meta(node_id: Option<&str>) -> Result<Vec<AttachmentMeta>, String> {
+    let attachments_dir = "./attachments";
+    let mut attachments = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir(attachments_dir) {
+        for entry in entries.flatten() {
+            if let Some(filename) = entry.file_name().to_str() {
+                let path = entry.path();
+                let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                let Some((attachment_id, owner_id)) = stem.split_once('_') else {
+                    continue;
+                };
+                if node_id.is_some_and(|node_id| node_id != owner_id) {
+                    continue;
+                }
+                let file_extension = std::path::Path::new(filename)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .unwrap_or("bin");
+
+                let mut file = std::fs::File::open(entry.path()).map_err(|e| e.to_string())?;
+                let size = file.metadata().map_err(|e| e.to_string())?.len();
+                let mut hasher = Sha256::new();
+                std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+
+                attachments.push(AttachmentMeta {
+                    id: attachment_id.to_string(),
+                    node_id: owner_id.to_string(),
+                    filename: format!("attachment.{}", file_extension),
+                    file_type: file_extension.to_string(),
+                    size,
+                    sha256: format!("{:x}", hasher.finalize()),
+                });
+            }
+        }
+    }
+
+    Ok(attachments)
+}
+
+/// Locates the stored file for an attachment ID
+///
+/// # Arguments
+/// * `attachment_id` - ID of the attachment
+///
+/// # Returns
+/// * `Some(PathBuf)` - Path of the stored file
+/// * `None` - If no attachment has this ID
+fn find_attachment_path(attachment_id: &str) -> Option<std::path::PathBuf> {
+    let prefix = format!("{}_", attachment_id);
+    std::fs::read_dir("./attachments")
+        .ok()?
+        .flatten()
+        .find(|entry| entry.file_name().to_str().is_some_and(|filename| filename.starts_with(&prefix)))
+        .map(|entry| entry.path())
+}
+
+/// Reads the content of a single attachment
+///
+/// # Arguments
+/// * `attachment_id` - ID of the attachment to read
+///
+/// # Returns
+/// * `Ok(String)` - Base64-encoded file content
+/// * `Err(String)` - Error message if the attachment was not found or unreadable
+#[tauri::command]
+fn get_attachment_content(attachment_id: String) -> Result<String, String> {
+    let path = find_attachment_path(&attachment_id).ok_or_else(|| "Attachment not found".to_string())?;
+    let content = std::fs::read(path).map_err(|e| e.to_string())?;
+    Ok(base64::prelude::BASE64_STANDARD.encode(&content))
+}
+
+/// Maximum number of bytes of text returned by `extract_attachment_text`
+const MAX_EXTRACTED_TEXT_BYTES: usize = 1024 * 1024;
+
+/// Largest attachment `extract_attachment_text` will read, in bytes
+const MAX_EXTRACTION_INPUT_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Plain text extracted from an attachment
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ExtractedText {
+    /// Extracted text, cut to `MAX_EXTRACTED_TEXT_BYTES`
+    text: String,
+    /// Whether the text was cut short
+    truncated: bool,
+}
+
+/// Extracts plain text from a text, Markdown, or PDF attachment
+///
+/// PDFs yield their text layer; scanned PDFs without one return little or
+/// no text. Other file types are rejected, as are files larger than
+/// `MAX_EXTRACTION_INPUT_BYTES`. The PDF parser can panic on malformed
+/// files; such a panic is caught and reported as an error.
+///
+/// # Arguments
+/// * `attachment_id` - ID of the attachment to read
+///
+/// # Returns
+/// * `Ok(ExtractedText)` - The text, capped at `MAX_EXTRACTED_TEXT_BYTES`
+/// * `Err(String)` - Error message if the attachment is missing, unsupported, or unreadable
+#[tauri::command]
+fn extract_attachment_text(attachment_id: String) -> Result<ExtractedText, String> {
+    let path = find_attachment_path(&attachment_id).ok_or_else(|| "Attachment not found".to_string())?;
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let size = std::fs::metadata(&path).map_err(|e| e.to_string())?.len();
+    if size > MAX_EXTRACTION_INPUT_BYTES {
+        return Err(format!(
+            "Attachment is {} bytes; text extraction is limited to {} bytes",
+            size, MAX_EXTRACTION_INPUT_BYTES
+        ));
+    }
+    let content = std::fs::read(&path).map_err(|e| e.to_string())?;
+
+    let mut text = match extension.as_str() {
+        "txt" | "md" => String::from_utf8_lossy(&content).into_owned(),
+        "pdf" => std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&content))
+            .map_err(|_| "Could not read PDF: the file is malformed".to_string())?
+            .map_err(|e| format!("Could not read PDF: {}", e))?,
+        _ => return Err(format!("Text extraction is not supported for \".{}\" files", extension)),
+    };
+
+    let truncated = text.len() > MAX_EXTRACTED_TEXT_BYTES;
+    if truncated {
+        let mut end = MAX_EXTRACTED_TEXT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    Ok(ExtractedText { text, truncated })
+}
+
+#[tauri::command]
+fn delete_attachment(attachment_id: String, node_id: String) -> Result<(), String> {
+    let attachments_dir = "./attachments";
+    
     if let Ok(entries) = std::fs::read_dir(attachments_dir) {
         for entry in entries.flatten() {
             if let Some(filename) = entry.file_name().to_str() {
-                if filename.contains(&format!("_{}", node_id)) {
-                    // Parse filename to extract attachment ID and original name
-                    let parts: Vec<&str> = filename.split('_').collect();
-                    if parts.len() >= 2 {
-                        let attachment_id = parts[0].to_string();
-                        let file_extension = std::path::Path::new(filename)
-                            .extension()
-                            .and_then(|ext| ext.to_str())
-                            .unwrap_or("bin");
-                        
-                        // Read file content and encode as base64
-                        if let Ok(content) = std::fs::read(entry.path()) {
-                            let content_base64 = base64::prelude::BASE64_STANDARD.encode(&content);
-                            
-                            attachments.push(AttachmentData {
-                                id: attachment_id,
-                                node_id: node_id.clone(),
-                                filename: format!("attachment.{}", file_extension),
-                                file_type: file_extension.to_string(),
-                                content_base64,
-                            });
-                        }
-                    }
+                if filename.starts_with(&attachment_id) && filename.contains(&format!("_{}", node_id)) {
+                    std::fs::remove_file(entry.path()).map_err(|e| e.to_string())?;
+                    return Ok(());
                 }
             }
         }
     }
     
-    Ok(attachments)
+    Err("Attachment not found".to_string())
+}
+
+/// Exports selected nodes, their interconnections, and attachments as a zip bundle
+///
+/// The archive contains `manifest.json`, `subgraph.json` with the nodes and
+/// the relationships between them, and every attachment of those nodes
+/// under `attachments/`. Restore it with `import_mini_case`.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_ids` - UUIDs of the nodes to include
+/// * `file_path` - Path where to save the zip file
+///
+/// # Returns
+/// * `Ok(MiniCaseManifest)` - The manifest written into the bundle
+/// * `Err(String)` - Error message if a node doesn't exist or writing fails
+#[tauri::command]
+fn export_mini_case(state: State<AppState>, node_ids: Vec<String>, file_path: String) -> Result<MiniCaseManifest, String> {
+    write_mini_case(&state, &node_ids, &file_path)
+}
+
+/// Does the work of `export_mini_case`
+fn write_mini_case(state: &Database, node_ids: &[String], file_path: &str) -> Result<MiniCaseManifest, String> {
+    use std::io::Write;
+
+    let mut nodes = Vec::new();
+    for id in node_ids {
+        let uuid = Uuid::parse_str(id).map_err(|e| e.to_string())?;
+        let node = state
+            .get_node(uuid)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Node {} not found", id))?;
+        nodes.push(node);
+    }
+
+    let selected: std::collections::HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+    let relationships: Vec<Relationship> = state
+        .get_relationships()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|rel| selected.contains(&rel.source_id) && selected.contains(&rel.target_id))
+        .collect();
+
+    let mut attachment_files = Vec::new();
+    if let Ok(entries) = std::fs::read_dir("./attachments") {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let belongs_to_selection = stem
+                .split_once('_')
+                .and_then(|(_, node_id)| Uuid::parse_str(node_id).ok())
+                .is_some_and(|node_id| selected.contains(&node_id));
+            if belongs_to_selection {
+                attachment_files.push(path);
+            }
+        }
+    }
+    attachment_files.sort();
+
+    let manifest = MiniCaseManifest {
+        version: "1.0.0".to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        nodes: nodes.len(),
+        relationships: relationships.len(),
+        attachments: attachment_files
+            .iter()
+            .filter_map(|path| path.file_name().and_then(|name| name.to_str()).map(str::to_string))
+            .collect(),
+    };
+    let subgraph = MiniCaseSubgraph { nodes, relationships };
+
+    let file = std::fs::File::create(file_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+
+    zip.start_file("manifest.json", options).map_err(|e| e.to_string())?;
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.write_all(&manifest_json).map_err(|e| e.to_string())?;
+
+    zip.start_file("subgraph.json", options).map_err(|e| e.to_string())?;
+    let subgraph_json = serde_json::to_vec_pretty(&subgraph).map_err(|e| e.to_string())?;
+    zip.write_all(&subgraph_json).map_err(|e| e.to_string())?;
+
+    for (path, name) in attachment_files.iter().zip(&manifest.attachments) {
+        let content = std::fs::read(path).map_err(|e| e.to_string())?;
+        zip.start_file(format!("attachments/{}", name), options).map_err(|e| e.to_string())?;
+        zip.write_all(&content).map_err(|e| e.to_string())?;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+/// Restores a bundle written by `export_mini_case` into the current investigation
+///
+/// Nodes and relationships keep their IDs and are added alongside existing
+/// data; any whose ID already exists is skipped, as is any relationship
+/// with an endpoint in neither the bundle nor the investigation. The
+/// relationship limits are checked before anything is written, so a
+/// rejected import changes nothing. Attachments of the nodes that were
+/// added are copied into the attachments directory; existing attachment
+/// files are never overwritten, and attachments whose ID is already taken
+/// are listed in `attachment_conflicts` instead. Entries larger than
+/// `MAX_ARCHIVE_ENTRY_BYTES`, or bundles extracting to more than
+/// `MAX_ARCHIVE_TOTAL_BYTES`, are refused.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path to the zip bundle
+///
+/// # Returns
+/// * `Ok(MiniCaseImportReport)` - Counts of what was added and skipped
+/// * `Err(String)` - Error message if the bundle can't be read
+#[tauri::command]
+fn import_mini_case(state: State<AppState>, file_path: String) -> Result<MiniCaseImportReport, String> {
+    read_mini_case(&state, &file_path)
+}
+
+/// Does the work of `import_mini_case`
+fn read_mini_case(state: &Database, file_path: &str) -> Result<MiniCaseImportReport, String> {
+    let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+    let mut total: u64 = 0;
+    let manifest: MiniCaseManifest =
+        serde_json::from_slice(&read_bundle_entry(&mut archive, "manifest.json", &mut total)?).map_err(|e| e.to_string())?;
+    let mut raw_subgraph: serde_json::Value =
+        serde_json::from_slice(&read_bundle_entry(&mut archive, "subgraph.json", &mut total)?).map_err(|e| e.to_string())?;
+    let migrations = migrate_legacy_node_types(&mut raw_subgraph);
+    let subgraph: MiniCaseSubgraph = serde_json::from_value(raw_subgraph).map_err(|e| e.to_string())?;
+
+    let mut type_mapping = TypeMappingReport::default();
+    for node in &subgraph.nodes {
+        let node_id = node.id.to_string();
+        match migrations.iter().find(|migration| migration.node_id == node_id) {
+            Some(migration) => type_mapping.record(&migration.original_type, Some(&node.node_type), true),
+            None => type_mapping.record(&format!("{:?}", node.node_type), Some(&node.node_type), false),
+        }
+    }
+
+    // Read every attachment before touching the database so a corrupt bundle changes nothing
+    let mut attachments = Vec::new();
+    for name in &manifest.attachments {
+        let (attachment_id, node_id) = parse_stored_attachment_name(name)?;
+        let content = read_bundle_entry(&mut archive, &format!("attachments/{}", name), &mut total)?;
+        attachments.push((name, attachment_id, node_id, content));
+    }
+
+    let mut report = MiniCaseImportReport {
+        nodes: 0,
+        relationships: 0,
+        attachments: 0,
+        skipped: 0,
+        attachments_skipped: 0,
+        attachment_conflicts: Vec::new(),
+        type_mapping,
+        warnings: Vec::new(),
+    };
+
+    let mut new_nodes = Vec::new();
+    for node in subgraph.nodes {
+        if state.get_node(node.id).map_err(|e| e.to_string())?.is_some() {
+            report.skipped += 1;
+        } else {
+            new_nodes.push(node);
+        }
+    }
+    let imported_nodes: std::collections::HashSet<Uuid> = new_nodes.iter().map(|node| node.id).collect();
+
+    let mut new_relationships = Vec::new();
+    let mut additions: std::collections::HashMap<Uuid, usize> = std::collections::HashMap::new();
+    for relationship in subgraph.relationships {
+        if state.get_relationship(relationship.id).map_err(|e| e.to_string())?.is_some() {
+            report.skipped += 1;
+            continue;
+        }
+        let mut endpoints_present = true;
+        for endpoint in [relationship.source_id, relationship.target_id] {
+            if !imported_nodes.contains(&endpoint) && state.get_node(endpoint).map_err(|e| e.to_string())?.is_none() {
+                endpoints_present = false;
+            }
+        }
+        if !endpoints_present {
+            report.skipped += 1;
+            continue;
+        }
+        *additions.entry(relationship.source_id).or_insert(0) += 1;
+        *additions.entry(relationship.target_id).or_insert(0) += 1;
+        new_relationships.push(relationship);
+    }
+    let mut additions: Vec<(Uuid, usize)> = additions.into_iter().collect();
+    additions.sort();
+    report.warnings = check_relationship_limits(state, &additions)?;
+
+    for node in new_nodes {
+        state.create_node(node).map_err(|e| e.to_string())?;
+        report.nodes += 1;
+    }
+    for relationship in new_relationships {
+        state.create_relationship(relationship).map_err(|e| e.to_string())?;
+        report.relationships += 1;
+    }
+
+    let attachments_dir = std::path::Path::new("./attachments");
+    std::fs::create_dir_all(attachments_dir).map_err(|e| e.to_string())?;
+    for (name, attachment_id, node_id, content) in attachments {
+        if !imported_nodes.contains(&node_id) {
+            report.attachments_skipped += 1;
+            continue;
+        }
+        if find_attachment_path(&attachment_id.to_string()).is_some() {
+            report.attachment_conflicts.push(name.clone());
+            continue;
+        }
+        // create_new refuses to replace a file that appeared in the meantime
+        let written = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(attachments_dir.join(name))
+            .and_then(|mut file| std::io::Write::write_all(&mut file, &content));
+        match written {
+            Ok(()) => report.attachments += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => report.attachment_conflicts.push(name.clone()),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Splits a stored attachment file name into its attachment and node IDs
+///
+/// Accepts only names `stored_attachment_name` could have produced, so a
+/// name from an untrusted bundle can't point outside the attachments
+/// directory.
+///
+/// # Arguments
+/// * `name` - File name, `<attachment_id>_<node_id>.<ext>`
+///
+/// # Returns
+/// * `Ok((Uuid, Uuid))` - The attachment ID and node ID
+/// * `Err(String)` - Error message if the name is not a valid stored attachment name
+fn parse_stored_attachment_name(name: &str) -> Result<(Uuid, Uuid), String> {
+    let invalid = || format!("Invalid attachment name \"{}\"", name);
+    let (stem, file_type) = name.rsplit_once('.').ok_or_else(invalid)?;
+    let (id, node_id) = stem.split_once('_').ok_or_else(invalid)?;
+    if attachment_file_name(id, node_id, file_type).map_err(|_| invalid())? != name {
+        return Err(invalid());
+    }
+    Ok((Uuid::parse_str(id).map_err(|_| invalid())?, Uuid::parse_str(node_id).map_err(|_| invalid())?))
+}
+
+/// Builds the name of an attachment file in the attachments directory and in archives
+///
+/// The metadata may come from an untrusted project file or archive, so
+/// both IDs must be UUIDs and the file type 1-10 ASCII letters or digits;
+/// anything else could name a path outside the attachments directory.
+///
+/// # Arguments
+/// * `meta` - Attachment metadata
+///
+/// # Returns
+/// * `Ok(String)` - The file name, `<attachment_id>_<node_id>.<ext>`
+/// * `Err(String)` - Error message if a field is not safe to use in a file name
+fn stored_attachment_name(meta: &AttachmentMeta) -> Result<String, String> {
+    attachment_file_name(&meta.id, &meta.node_id, &meta.file_type)
+}
+
+/// Builds `<attachment_id>_<node_id>.<ext>` from untrusted parts; see `stored_attachment_name`
+fn attachment_file_name(attachment_id: &str, node_id: &str, file_type: &str) -> Result<String, String> {
+    let id = Uuid::parse_str(attachment_id).map_err(|_| format!("Invalid attachment ID \"{}\"", attachment_id))?;
+    let node_id = Uuid::parse_str(node_id)
+        .map_err(|_| format!("Attachment {} has invalid node ID \"{}\"", attachment_id, node_id))?;
+    let valid_type = (1..=10).contains(&file_type.len()) && file_type.chars().all(|c| c.is_ascii_alphanumeric());
+    if !valid_type {
+        return Err(format!("Attachment {} has invalid file type \"{}\"", attachment_id, file_type));
+    }
+    let name = format!("{}_{}.{}", id, node_id, file_type);
+    if std::path::Path::new(&name).file_name().and_then(|file_name| file_name.to_str()) != Some(name.as_str()) {
+        return Err(format!("Invalid attachment name \"{}\"", name));
+    }
+    Ok(name)
+}
+
+/// Exports the whole project, including attachment files, as one zip archive
+///
+/// The archive contains `project.json` (the same document `save_project`
+/// writes), every attachment of the project's nodes under `attachments/`,
+/// and `manifest.json` listing the attachments with their SHA-256 hashes.
+/// Restore it with `import_archive`.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path where to save the zip file
+/// * `project_name` - Name of the project
+///
+/// # Returns
+/// * `Ok(ArchiveManifest)` - The manifest written into the archive
+/// * `Err(String)` - Error message if reading an attachment or writing fails
+#[tauri::command]
+fn export_archive(state: State<AppState>, file_path: String, project_name: String) -> Result<ArchiveManifest, String> {
+    write_archive(&state, &file_path, project_name)
+}
+
+/// Does the work of `export_archive`
+fn write_archive(state: &Database, file_path: &str, project_name: String) -> Result<ArchiveManifest, String> {
+    use std::io::Write;
+
+    let project_json = serialize_project(state, project_name, false)?;
+    let nodes = state.get_all_nodes().map_err(|e| e.to_string())?;
+    let node_ids: std::collections::HashSet<String> = nodes.iter().map(|node| node.id.to_string()).collect();
+    let mut attachments: Vec<AttachmentMeta> = collect_attachment_meta(None)?
+        .into_iter()
+        .filter(|meta| node_ids.contains(&meta.node_id))
+        .collect();
+    attachments.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let manifest = ArchiveManifest {
+        version: "1.0.0".to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        nodes: nodes.len(),
+        relationships: state.get_relationships().map_err(|e| e.to_string())?.len(),
+        attachments,
+    };
+
+    let file = std::fs::File::create(file_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+
+    zip.start_file("manifest.json", options).map_err(|e| e.to_string())?;
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.write_all(&manifest_json).map_err(|e| e.to_string())?;
+
+    zip.start_file("project.json", options).map_err(|e| e.to_string())?;
+    zip.write_all(project_json.as_bytes()).map_err(|e| e.to_string())?;
+
+    for meta in &manifest.attachments {
+        let path = find_attachment_path(&meta.id).ok_or_else(|| format!("Attachment {} disappeared", meta.id))?;
+        let content = std::fs::read(path).map_err(|e| e.to_string())?;
+        zip.start_file(format!("attachments/{}", stored_attachment_name(meta)?), options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(&content).map_err(|e| e.to_string())?;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+/// Largest single entry `import_archive` will extract, in bytes
+const MAX_ARCHIVE_ENTRY_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Largest total size `import_archive` will extract, in bytes
+const MAX_ARCHIVE_TOTAL_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Copies a zip entry into `sink`, refusing entries larger than `limit`
+///
+/// The limit is enforced on the bytes actually decompressed, not on the
+/// size the archive claims, so a zip bomb can't get past it.
+///
+/// # Returns
+/// * `Ok(u64)` - Number of bytes copied
+/// * `Err(String)` - Error message if the entry is too large or can't be read
+fn copy_archive_entry(entry: impl std::io::Read, name: &str, limit: u64, sink: &mut impl std::io::Write) -> Result<u64, String> {
+    let copied = std::io::copy(&mut entry.take(limit.saturating_add(1)), sink).map_err(|e| e.to_string())?;
+    if copied > limit {
+        return Err(format!("Archive entry \"{}\" is larger than {} bytes", name, limit));
+    }
+    Ok(copied)
+}
+
+/// Reads one entry of a mini-case bundle, enforcing the archive size limits
+///
+/// # Arguments
+/// * `archive` - The open bundle
+/// * `name` - Name of the entry to read
+/// * `total` - Bytes read from the bundle so far, updated in place
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The entry's content
+/// * `Err(String)` - Error message if the entry is missing, unreadable, or too large
+fn read_bundle_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str, total: &mut u64) -> Result<Vec<u8>, String> {
+    let entry = archive.by_name(name).map_err(|e| e.to_string())?;
+    let mut content = Vec::new();
+    *total += copy_archive_entry(entry, name, MAX_ARCHIVE_ENTRY_BYTES, &mut content)?;
+    if *total > MAX_ARCHIVE_TOTAL_BYTES {
+        return Err(format!("Bundle extracts to more than {} bytes", MAX_ARCHIVE_TOTAL_BYTES));
+    }
+    Ok(content)
+}
+
+/// Replaces the current project with one exported by `export_archive`
+///
+/// Attachments are first extracted into a staging directory and checked
+/// against the SHA-256 hashes in the manifest; entries larger than
+/// `MAX_ARCHIVE_ENTRY_BYTES`, or archives extracting to more than
+/// `MAX_ARCHIVE_TOTAL_BYTES`, are refused. Only once the project has loaded
+/// are the staged files moved into the attachments directory, so a corrupt
+/// archive or a project that fails to load leaves the current data and
+/// attachments untouched. Attachment files left over from the previous
+/// project are moved into an `attachments/.previous-<id>` folder instead of
+/// being deleted, and a warning names the folder.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `file_path` - Path to the zip archive
+/// * `create_stubs` - Create placeholder nodes for missing relationship endpoints instead of dropping those relationships (default false)
+///
+/// # Returns
+/// * `Ok(LoadReport)` - Project metadata, counts, validation warnings, and repairs
+/// * `Err(String)` - Error message if the archive is unreadable, incomplete, too large, or an attachment hash doesn't match
+#[tauri::command]
+fn import_archive(state: State<AppState>, file_path: String, create_stubs: Option<bool>) -> Result<LoadReport, String> {
+    let attachments_dir = std::path::Path::new("./attachments");
+    std::fs::create_dir_all(attachments_dir).map_err(|e| e.to_string())?;
+    let staging_dir = attachments_dir.join(format!(".import-{}", Uuid::new_v4()));
+    std::fs::create_dir(&staging_dir).map_err(|e| e.to_string())?;
+
+    let result = import_archive_staged(&state, &file_path, attachments_dir, &staging_dir, create_stubs.unwrap_or(false));
+    let _ = std::fs::remove_dir_all(&staging_dir);
+    result
+}
+
+/// Does the work of `import_archive`, extracting attachments into `staging_dir`
+fn import_archive_staged(
+    state: &Database,
+    file_path: &str,
+    attachments_dir: &std::path::Path,
+    staging_dir: &std::path::Path,
+    create_stubs: bool,
+) -> Result<LoadReport, String> {
+    let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut total: u64 = 0;
+
+    let manifest: ArchiveManifest = {
+        let entry = archive.by_name("manifest.json").map_err(|e| e.to_string())?;
+        let mut json = Vec::new();
+        total += copy_archive_entry(entry, "manifest.json", MAX_ARCHIVE_ENTRY_BYTES, &mut json)?;
+        serde_json::from_slice(&json).map_err(|e| e.to_string())?
+    };
+    let project_json = {
+        let entry = archive.by_name("project.json").map_err(|e| e.to_string())?;
+        let mut json = Vec::new();
+        total += copy_archive_entry(entry, "project.json", MAX_ARCHIVE_ENTRY_BYTES, &mut json)?;
+        String::from_utf8(json).map_err(|e| e.to_string())?
+    };
+
+    let mut staged = Vec::new();
+    for meta in &manifest.attachments {
+        let name = stored_attachment_name(meta)?;
+        let entry_name = format!("attachments/{}", name);
+        let entry = archive.by_name(&entry_name).map_err(|e| e.to_string())?;
+        let staged_path = staging_dir.join(&name);
+        let mut staged_file = std::fs::File::create(&staged_path).map_err(|e| e.to_string())?;
+        total += copy_archive_entry(entry, &entry_name, MAX_ARCHIVE_ENTRY_BYTES, &mut staged_file)?;
+        if total > MAX_ARCHIVE_TOTAL_BYTES {
+            return Err(format!("Archive extracts to more than {} bytes", MAX_ARCHIVE_TOTAL_BYTES));
+        }
+
+        let mut hasher = Sha256::new();
+        let mut staged_file = std::fs::File::open(&staged_path).map_err(|e| e.to_string())?;
+        std::io::copy(&mut staged_file, &mut hasher).map_err(|e| e.to_string())?;
+        if format!("{:x}", hasher.finalize()) != meta.sha256 {
+            return Err(format!("Attachment {} content does not match its SHA-256 hash", meta.id));
+        }
+        staged.push(name);
+    }
+
+    let (mut report, attachments) = apply_project_graph(state, &project_json, false, create_stubs)?;
+
+    // The project is loaded; set aside the previous project's files and move the new ones in
+    let incoming: std::collections::HashSet<&String> = staged.iter().collect();
+    let previous_dir = attachments_dir.join(format!(".previous-{}", Uuid::new_v4()));
+    let mut set_aside = 0;
+    for entry in std::fs::read_dir(attachments_dir).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if !path.is_file() || incoming.contains(&name) {
+            continue;
+        }
+        std::fs::create_dir_all(&previous_dir).map_err(|e| e.to_string())?;
+        std::fs::rename(&path, previous_dir.join(&name)).map_err(|e| e.to_string())?;
+        set_aside += 1;
+    }
+    if set_aside > 0 {
+        report.warnings.push(format!(
+            "Moved {} attachment files from the previous project to {}",
+            set_aside,
+            previous_dir.display()
+        ));
+    }
+    for name in &staged {
+        let target = attachments_dir.join(name);
+        if target.exists() {
+            // Same attachment ID and node; the verified archive copy replaces it
+            std::fs::remove_file(&target).map_err(|e| e.to_string())?;
+        }
+        std::fs::rename(staging_dir.join(name), target).map_err(|e| e.to_string())?;
+    }
+
+    report.warnings.extend(restore_project_attachments(state, &attachments)?);
+    Ok(report)
+}
+
+/// Returns the current application settings
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(Settings)` - The current settings
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_settings(state: State<AppState>) -> Result<Settings, String> {
+    state.settings().map_err(|e| e.to_string())
+}
+
+/// Controls whether tags are lowercased on input
+///
+/// Tags are always trimmed and deduplicated case-insensitively; this only
+/// decides whether the stored value keeps its original casing.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `enabled` - `true` to lowercase tags, `false` to keep display casing
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the update fails
+#[tauri::command]
+fn set_lowercase_tags(state: State<AppState>, enabled: bool) -> Result<(), String> {
+    state
+        .update_settings(|settings| settings.lowercase_tags = enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// Sets the age at which nodes are exported with zero freshness
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `days` - Age in days at which the GraphML `freshness` attribute reaches 0.0
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the update fails
+#[tauri::command]
+fn set_freshness_max_age(state: State<AppState>, days: u32) -> Result<(), String> {
+    state
+        .update_settings(|settings| settings.freshness_max_age_days = days)
+        .map_err(|e| e.to_string())
+}
+
+/// Sets how duplicate relationships are merged
+///
+/// Applies when a relationship is created with `merge_duplicates` set.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `confidence` - Confidence aggregation: `max`, `mean`, or `noisy_or`
+/// * `sources` - Source handling: `union` or `replace`
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if a policy name is unknown
+#[tauri::command]
+fn set_merge_policy(state: State<AppState>, confidence: String, sources: String) -> Result<(), String> {
+    let confidence = ConfidenceAggregation::from_name(&confidence).ok_or_else(|| {
+        format!("Unknown confidence aggregation '{}'; expected one of: {}", confidence, ConfidenceAggregation::NAMES.join(", "))
+    })?;
+    let sources = SourceMerge::from_name(&sources).ok_or_else(|| {
+        format!("Unknown source merge '{}'; expected one of: {}", sources, SourceMerge::NAMES.join(", "))
+    })?;
+    state
+        .update_settings(|settings| settings.merge_policy = MergePolicy { confidence, sources })
+        .map_err(|e| e.to_string())
+}
+
+/// Sets the per-node relationship limit
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `limit` - Maximum number of relationships per node
+/// * `reject` - `true` to reject relationships over the limit, `false` to only warn
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the update fails
+#[tauri::command]
+fn set_relationship_limit(state: State<AppState>, limit: usize, reject: bool) -> Result<(), String> {
+    state
+        .update_settings(|settings| {
+            settings.relationship_limit = limit;
+            settings.reject_over_limit = reject;
+        })
+        .map_err(|e| e.to_string())
 }
 
+/// Returns the description template for a node type
+///
+/// A user override takes precedence over the built-in template.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_type` - Node type name (e.g. "CryptoWallet")
+///
+/// # Returns
+/// * `Ok(String)` - Template text to prefill the description field
+/// * `Err(String)` - Error message if the type is invalid
 #[tauri::command]
-fn delete_attachment(attachment_id: String, node_id: String) -> Result<(), String> {
-    let attachments_dir = "./attachments";
-    
-    if let Ok(entries) = std::fs::read_dir(attachments_dir) {
-        for entry in entries.flatten() {
-            if let Some(filename) = entry.file_name().to_str() {
-                if filename.starts_with(&attachment_id) && filename.contains(&format!("_{}", node_id)) {
-                    std::fs::remove_file(entry.path()).map_err(|e| e.to_string())?;
-                    return Ok(());
-                }
+fn get_description_template(state: State<AppState>, node_type: String) -> Result<String, String> {
+    let node_type = parse_node_type(&node_type)?;
+    let settings = state.settings().map_err(|e| e.to_string())?;
+    Ok(settings.description_template(&node_type))
+}
+
+/// Overrides the description template for a node type
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `node_type` - Node type name (e.g. "CryptoWallet")
+/// * `template` - New template text, or `None` to restore the built-in template
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the type is invalid or the update fails
+#[tauri::command]
+fn set_description_template(state: State<AppState>, node_type: String, template: Option<String>) -> Result<(), String> {
+    let node_type = parse_node_type(&node_type)?;
+    state
+        .update_settings(|settings| match template {
+            Some(template) => {
+                settings.description_templates.insert(node_type, template);
             }
+            None => {
+                settings.description_templates.remove(&node_type);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Replaces the rules that tag new nodes automatically by type
+///
+/// Rule tags are added to the tags of every node created afterwards, by any
+/// command or import, and deduplicated with them, so a tag given by the
+/// user is never repeated. Loading a project doesn't apply them, except to
+/// stub nodes created for missing relationship endpoints.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `rules` - Default tags by node type name (e.g. `{"CryptoWallet": ["financial"]}`); an empty map removes all rules
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if a type is invalid or the update fails
+#[tauri::command]
+fn set_auto_tag_rules(state: State<AppState>, rules: std::collections::HashMap<String, Vec<String>>) -> Result<(), String> {
+    replace_auto_tag_rules(&state, rules)
+}
+
+/// Does the work of `set_auto_tag_rules`
+fn replace_auto_tag_rules(state: &Database, rules: std::collections::HashMap<String, Vec<String>>) -> Result<(), String> {
+    let lowercase = state.settings().map_err(|e| e.to_string())?.lowercase_tags;
+    let mut parsed = std::collections::HashMap::new();
+    for (node_type, tags) in rules {
+        let node_type = parse_node_type(&node_type)?;
+        let tags = normalize_tags(tags, lowercase);
+        if !tags.is_empty() {
+            parsed.insert(node_type, tags);
         }
     }
-    
-    Err("Attachment not found".to_string())
+    state
+        .update_settings(|settings| settings.auto_tag_rules = parsed)
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the project-level case notes
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+///
+/// # Returns
+/// * `Ok(ProjectNotes)` - Notes and case summary
+/// * `Err(String)` - Error message if retrieval fails
+#[tauri::command]
+fn get_project_notes(state: State<AppState>) -> Result<ProjectNotes, String> {
+    state.project_notes().map_err(|e| e.to_string())
+}
+
+/// Replaces the project-level case notes
+///
+/// Notes are saved with the project file and restored on load.
+///
+/// # Arguments
+/// * `state` - Application state containing the database
+/// * `notes` - Free-form case notes
+/// * `case_summary` - Optional short case summary (unchanged if omitted)
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(String)` - Error message if the update fails
+#[tauri::command]
+fn set_project_notes(state: State<AppState>, notes: String, case_summary: Option<String>) -> Result<(), String> {
+    let current = state.project_notes().map_err(|e| e.to_string())?;
+    state
+        .set_project_notes(ProjectNotes {
+            notes,
+            case_summary: case_summary.unwrap_or(current.case_summary),
+        })
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -653,6 +4741,20 @@ fn clear_all_data(state: State<AppState>) -> Result<(), String> {
     state.clear_all().map_err(|e| e.to_string())
 }
 
+/// Runs OSINT Studio without a GUI, serving commands over stdin/stdout
+///
+/// Reads newline-delimited JSON requests until stdin is closed; see the
+/// `headless` module for the request and response envelope.
+///
+/// # Returns
+/// * `Ok(())` - Stdin was closed
+/// * `Err(std::io::Error)` - Reading stdin or writing stdout failed
+pub fn run_headless() -> std::io::Result<()> {
+    let database = Database::new();
+    let stdin = std::io::stdin();
+    headless::serve(&database, stdin.lock(), std::io::stdout().lock())
+}
+
 /// Main entry point for the OSINT Studio application
 ///
 /// Initializes the Tauri application with:
@@ -674,27 +4776,1126 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![
             create_node,
+            import_indicators,
+            import_mapped,
             get_all_nodes,
             search_nodes,
+            get_nodes_by_type,
+            get_all_node_sources,
+            get_nodes_sorted,
+            search_nodes_ranked,
+            search_with_context,
+            autocomplete_labels,
+            search_within_subgraph,
+            query,
             get_node,
             update_node,
+            update_node_metadata,
+            preview_node_deletion,
             delete_node,
+            find_identifier_duplicates,
+            cross_check_sources,
+            mark_node_enriched,
+            set_confidence_bulk,
+            get_stale_nodes,
+            pin_node,
+            unpin_node,
+            delete_orphan_nodes,
+            get_expired_nodes,
+            purge_expired,
+            trim_leaves,
             create_relationship,
+            create_relationship_by_label,
+            infer_relationships_from_metadata,
+            create_event_hyperedge,
+            get_hyperedge,
             update_relationship,
             delete_relationship,
+            delete_relationships_by_type,
+            validate_hierarchy,
             get_relationships,
+            get_relationships_detailed,
             get_node_relationships,
+            get_node_relationship_summary,
+            get_relationships_between,
+            get_pair_interaction_summary,
+            get_confidence_by_relation_type,
+            get_weight_histogram,
+            get_growth_timeline,
+            get_quality_report,
+            query_node_metadata,
+            query_relationship_metadata,
+            get_relationship_provenance,
+            compute_degree_centrality,
+            get_path_length_stats,
+            compute_visual_edge_weights,
+            refresh_degrees,
+            compact_database,
+            sanitize_numeric_fields,
+            cap_relationship_confidence_by_endpoints,
+            find_shortest_path,
+            detect_communities,
+            get_cluster_confidence,
+            compute_structural_similarity,
+            compare_node_sets,
+            infer_same_as_transitivity,
+            merge_nodes,
+            auto_merge_same_as,
             save_project,
             load_project,
+            save_project_encrypted,
+            load_project_encrypted,
             export_csv,
+            export_csv_by_type,
+            export_financial_flows,
+            export_nodes_individually,
+            export_provenance_matrix,
             export_graphml,
             export_json,
+            export_networkx_json,
+            export_turtle,
+            export_dot,
+            export_svg,
+            export,
+            export_as_of,
+            export_delta,
+            export_html_report,
+            get_audit_log,
+            export_audit_log,
             write_report,
             save_attachment,
             list_attachments,
+            list_attachments_meta,
+            get_attachment_content,
+            extract_attachment_text,
             delete_attachment,
+            export_mini_case,
+            import_mini_case,
+            export_archive,
+            import_archive,
+            get_project_notes,
+            set_project_notes,
+            get_settings,
+            set_lowercase_tags,
+            set_relationship_limit,
+            set_freshness_max_age,
+            set_merge_policy,
+            get_description_template,
+            set_description_template,
+            set_auto_tag_rules,
             clear_all_data
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_node(db: &Database, node_type: NodeType, label: &str) -> Uuid {
+        db.create_node(Node::new(node_type, label.to_string())).unwrap()
+    }
+
+    #[test]
+    fn relationship_by_label_requires_unique_labels() {
+        let db = Database::new();
+        add_node(&db, NodeType::Person, "Alice");
+        add_node(&db, NodeType::Person, "Alice");
+        let acme = add_node(&db, NodeType::Organization, "Acme");
+        let bob = add_node(&db, NodeType::Person, "Bob");
+
+        let error = insert_relationship_by_label(&db, "Alice", "Acme", "MemberOf").err().unwrap();
+        assert!(error.contains("ambiguous"), "{}", error);
+        let error = insert_relationship_by_label(&db, "Carol", "Acme", "MemberOf").err().unwrap();
+        assert!(error.contains("No node found"), "{}", error);
+        assert!(db.get_relationships().unwrap().is_empty());
+
+        let created = insert_relationship_by_label(&db, "Bob", "Acme", "MemberOf").unwrap();
+        let rel = db.get_relationship(Uuid::parse_str(&created.id).unwrap()).unwrap().unwrap();
+        assert_eq!((rel.source_id, rel.target_id), (bob, acme));
+    }
+
+    #[test]
+    fn event_hyperedge_links_every_participant_under_one_group() {
+        let db = Database::new();
+        let event = add_node(&db, NodeType::Event, "Meeting");
+        let participants: Vec<String> = ["A", "B", "C"]
+            .iter()
+            .map(|label| add_node(&db, NodeType::Person, label).to_string())
+            .collect();
+
+        let result = insert_event_hyperedge(&db, &event.to_string(), &participants, "ConnectedTo").unwrap();
+        assert_eq!(result.relationship_ids.len(), 3);
+        let hyperedge = db.get_hyperedge(&result.group_id).unwrap();
+        assert_eq!(hyperedge.len(), 3);
+        assert!(hyperedge.iter().all(|rel| rel.source_id == event));
+        let mut targets: Vec<String> = hyperedge.iter().map(|rel| rel.target_id.to_string()).collect();
+        targets.sort();
+        let mut expected = participants.clone();
+        expected.sort();
+        assert_eq!(targets, expected);
+    }
+
+    fn link(db: &Database, source: Uuid, target: Uuid, relation_type: RelationType) -> Uuid {
+        db.create_relationship(Relationship::new(source, target, relation_type)).unwrap()
+    }
+
+    /// Serializes a two-node, one-relationship project; reads `./attachments`, so call it inside `in_scratch_dir`
+    fn sample_project() -> String {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Organization, "B");
+        link(&db, a, b, RelationType::MemberOf);
+        serialize_project(&db, "Sample".to_string(), false).unwrap()
+    }
+
+    #[test]
+    fn dry_run_load_reports_counts_without_loading() {
+        in_scratch_dir(|_| {
+            let db = Database::new();
+            let report = apply_project(&db, &sample_project(), true, false).unwrap();
+
+            assert!(report.dry_run);
+            assert_eq!((report.nodes, report.relationships), (2, 1));
+            assert!(db.get_all_nodes().unwrap().is_empty());
+            assert!(db.get_relationships().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn unknown_node_types_load_as_custom_and_are_reported() {
+        in_scratch_dir(|_| {
+            let mut project: serde_json::Value = serde_json::from_str(&sample_project()).unwrap();
+            let node = &mut project["nodes"][0];
+            node["node_type"] = serde_json::json!("Spaceship");
+            let node_id = node["id"].as_str().unwrap().to_string();
+
+            let db = Database::new();
+            let report = apply_project(&db, &project.to_string(), false, false).unwrap();
+            assert_eq!(report.nodes, 2);
+            assert_eq!(report.type_migrations.len(), 1);
+            let migration = &report.type_migrations[0];
+            assert_eq!((migration.node_id.as_str(), migration.original_type.as_str()), (node_id.as_str(), "Spaceship"));
+            let loaded = db.get_node(Uuid::parse_str(&node_id).unwrap()).unwrap().unwrap();
+            assert!(matches!(loaded.node_type, NodeType::Custom));
+        });
+    }
+
+    /// Deserializes a command request from `base` with the fields of `extra` laid over it
+    fn request_from_json<T: serde::de::DeserializeOwned>(mut base: serde_json::Value, extra: serde_json::Value) -> T {
+        if let (Some(base), serde_json::Value::Object(extra)) = (base.as_object_mut(), extra) {
+            base.extend(extra);
+        }
+        serde_json::from_value(base).unwrap()
+    }
+
+    /// Builds a relationship request from JSON, as the frontend would send it
+    fn relationship_request(source: Uuid, target: Uuid, extra: serde_json::Value) -> CreateRelationshipRequest {
+        let request = serde_json::json!({
+            "source_id": source.to_string(),
+            "target_id": target.to_string(),
+            "relation_type": "ConnectedTo",
+        });
+        request_from_json(request, extra)
+    }
+
+    #[test]
+    fn relationship_weight_is_stored_from_the_request() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+
+        let created = insert_relationship(&db, relationship_request(a, b, serde_json::json!({ "weight": 2.5 }))).unwrap();
+        let rel = db.get_relationship(Uuid::parse_str(&created.id).unwrap()).unwrap().unwrap();
+        assert_eq!(rel.weight, 2.5);
+
+        let negative = insert_relationship(&db, relationship_request(a, b, serde_json::json!({ "weight": -1.0 })));
+        assert!(negative.is_err());
+    }
+
+    #[test]
+    fn audit_log_csv_has_one_row_per_operation_in_range() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let b = add_node(&db, NodeType::Person, "B");
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        link(&db, a, b, RelationType::ConnectedTo);
+
+        let all = db.get_audit_log(None, None).unwrap();
+        assert_eq!(all.len(), 3);
+        let entries = db.get_audit_log(Some(all[1].timestamp), None).unwrap();
+        let mut csv = Vec::new();
+        write_audit_log_csv(&entries, &mut csv).unwrap();
+
+        let mut reader = csv::Reader::from_reader(csv.as_slice());
+        let rows: Vec<csv::StringRecord> = reader.records().map(|row| row.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((&rows[0][2], &rows[0][3]), ("Node", b.to_string().as_str()));
+        assert_eq!(&rows[1][2], "Relationship");
+        assert!(rows.iter().all(|row| &row[1] == "Create"));
+    }
+
+    /// Creates an empty scratch directory under the system temp directory
+    fn scratch_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("osint-studio-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn export_picks_the_format_from_the_extension() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        link(&db, a, b, RelationType::SameAs);
+        let nodes = db.get_all_nodes().unwrap();
+        let relationships = db.get_relationships().unwrap();
+        let dir = scratch_dir();
+        let export_to = |name: &str| {
+            let path = dir.join(name);
+            write_export(&db, nodes.clone(), relationships.clone(), path.to_str().unwrap()).map(|_| path)
+        };
+
+        let graphml = std::fs::read_to_string(export_to("out.graphml").unwrap()).unwrap();
+        assert!(graphml.contains("<graphml"));
+        let gexf = std::fs::read_to_string(export_to("out.GEXF").unwrap()).unwrap();
+        assert!(gexf.contains("<gexf"));
+        let cypher = std::fs::read_to_string(export_to("out.cypher").unwrap()).unwrap();
+        assert!(cypher.contains("CREATE") && cypher.contains(":SAME_AS"));
+
+        let error = export_to("out.xyz").unwrap_err();
+        assert!(error.contains(".xyz") && error.contains(".graphml"), "{}", error);
+        assert!(!dir.join("out.xyz").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Serializes tests that depend on the working directory, where attachments live
+    static WORKING_DIR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Runs `test` with a fresh scratch directory as the working directory
+    fn in_scratch_dir<T>(test: impl FnOnce(&std::path::Path) -> T) -> T {
+        let _guard = WORKING_DIR.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let previous = std::env::current_dir().unwrap();
+        let dir = scratch_dir();
+        std::env::set_current_dir(&dir).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| test(&dir)));
+        std::env::set_current_dir(previous).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        result.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+
+    #[test]
+    fn attachment_metadata_is_listed_by_streaming() {
+        const SIZE: u64 = 4 * 1024 * 1024;
+        in_scratch_dir(|_| {
+            let node_id = Uuid::new_v4().to_string();
+            std::fs::create_dir_all("attachments").unwrap();
+            for _ in 0..10 {
+                let name = attachment_file_name(&Uuid::new_v4().to_string(), &node_id, "bin").unwrap();
+                // Sparse files: large on paper, cheap to create
+                std::fs::File::create(std::path::Path::new("attachments").join(name)).unwrap().set_len(SIZE).unwrap();
+            }
+            save_attachment(Uuid::new_v4().to_string(), "other.txt".to_string(), "b3RoZXI=".to_string()).unwrap();
+
+            let mut hasher = Sha256::new();
+            std::io::copy(&mut std::io::Read::take(std::io::repeat(0), SIZE), &mut hasher).unwrap();
+            let zeros_hash = format!("{:x}", hasher.finalize());
+
+            // AttachmentMeta has no content field, so listing returns sizes and hashes only
+            let listed = list_attachments_meta(node_id.clone()).unwrap();
+            assert_eq!(listed.len(), 10);
+            assert!(listed.iter().all(|meta| meta.node_id == node_id && meta.size == SIZE && meta.sha256 == zeros_hash));
+        });
+    }
+
+    #[test]
+    fn nodes_by_type_returns_only_that_type() {
+        let db = Database::new();
+        let wallet = add_node(&db, NodeType::CryptoWallet, "bc1qexample");
+        add_node(&db, NodeType::Person, "Alice");
+
+        let node_type = parse_node_type("CryptoWallet").unwrap();
+        let wallets = db.get_nodes_by_type(&node_type).unwrap();
+        assert_eq!(wallets.iter().map(|node| node.id).collect::<Vec<_>>(), vec![wallet]);
+        assert!(parse_node_type("Wallet").is_err());
+    }
+
+    #[test]
+    fn relationship_limit_warns_or_rejects() {
+        let db = Database::new();
+        let hub = add_node(&db, NodeType::Organization, "Hub");
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        assert!(db.settings().unwrap().relationship_limit >= 10_000);
+        db.update_settings(|settings| settings.relationship_limit = 1).unwrap();
+
+        let first = insert_relationship(&db, relationship_request(hub, a, serde_json::json!({}))).unwrap();
+        assert!(first.warnings.is_empty());
+        let second = insert_relationship(&db, relationship_request(hub, b, serde_json::json!({}))).unwrap();
+        assert_eq!(second.warnings, vec!["Node \"Hub\" already has 1 relationships (limit 1)".to_string()]);
+
+        db.update_settings(|settings| settings.reject_over_limit = true).unwrap();
+        let rejected = insert_relationship(&db, relationship_request(hub, b, serde_json::json!({})));
+        assert!(rejected.err().unwrap().contains("already has 2 relationships"));
+        assert_eq!(db.get_relationships().unwrap().len(), 2);
+    }
+
+    /// Builds a relationship update from JSON, as the frontend would send it
+    fn relationship_update(id: Uuid, extra: serde_json::Value) -> UpdateRelationshipRequest {
+        let request = serde_json::json!({ "id": id.to_string(), "relation_type": "ConnectedTo", "weight": 1.0 });
+        request_from_json(request, extra)
+    }
+
+    #[test]
+    fn relationship_update_applies_confidence_and_source() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let id = link(&db, a, b, RelationType::ConnectedTo);
+
+        apply_relationship_update(&db, relationship_update(id, serde_json::json!({ "confidence": 0.9, "source": "Registry" }))).unwrap();
+        let rel = db.get_relationship(id).unwrap().unwrap();
+        assert_eq!(rel.confidence, 0.9);
+        assert_eq!(rel.source.as_deref(), Some("Registry"));
+
+        apply_relationship_update(&db, relationship_update(id, serde_json::json!({ "confidence": 7.0 }))).unwrap();
+        let rel = db.get_relationship(id).unwrap().unwrap();
+        assert_eq!(rel.confidence, 1.0);
+        assert_eq!(rel.source.as_deref(), Some("Registry"));
+    }
+
+    /// Builds a node creation request from JSON, as the frontend would send it
+    fn node_request(node_type: &str, label: &str, extra: serde_json::Value) -> CreateNodeRequest {
+        let request = serde_json::json!({ "node_type": node_type, "label": label, "tags": [] });
+        request_from_json(request, extra)
+    }
+
+    /// Builds a node update that keeps the node's current label, tags, and confidence
+    fn node_update(node: &Node, extra: serde_json::Value) -> UpdateNodeRequest {
+        let request = serde_json::json!({
+            "id": node.id.to_string(),
+            "label": node.label,
+            "description": node.description,
+            "tags": node.tags,
+            "confidence": node.confidence,
+        });
+        request_from_json(request, extra)
+    }
+
+    fn fetch_node(db: &Database, id: &str) -> Node {
+        db.get_node(Uuid::parse_str(id).unwrap()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn node_source_is_set_on_create_and_changed_on_update() {
+        let db = Database::new();
+        let id = insert_node(&db, node_request("Person", "Alice", serde_json::json!({ "source": "FOIA request" }))).unwrap();
+        let node = fetch_node(&db, &id);
+        assert_eq!(node.source.as_deref(), Some("FOIA request"));
+
+        apply_node_update(&db, node_update(&node, serde_json::json!({}))).unwrap();
+        assert_eq!(fetch_node(&db, &id).source.as_deref(), Some("FOIA request"));
+        apply_node_update(&db, node_update(&node, serde_json::json!({ "source": "Court filing" }))).unwrap();
+        assert_eq!(fetch_node(&db, &id).source.as_deref(), Some("Court filing"));
+    }
+
+    #[test]
+    fn graphml_export_carries_shared_community_values() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let loner = add_node(&db, NodeType::Person, "Loner");
+        link(&db, a, b, RelationType::ConnectedTo);
+
+        let communities = db.detect_communities().unwrap();
+        let graphml = export::render_graphml(
+            &db.get_all_nodes().unwrap(),
+            &db.get_relationships().unwrap(),
+            Some(&communities),
+            365,
+        );
+        let community_of = |id: Uuid| {
+            let start = graphml.find(&format!("<node id=\"{}\">", id)).unwrap();
+            let node = &graphml[start..start + graphml[start..].find("</node>").unwrap()];
+            let value = node.split("<data key=\"community\">").nth(1).unwrap();
+            value[..value.find("</data>").unwrap()].to_string()
+        };
+        assert_eq!(community_of(a), community_of(b));
+        assert_ne!(community_of(a), community_of(loner));
+    }
+
+    #[test]
+    fn repeated_idempotency_key_creates_one_relationship() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let request = || relationship_request(a, b, serde_json::json!({ "idempotency_key": "retry-1" }));
+
+        let first = insert_relationship(&db, request()).unwrap();
+        let second = insert_relationship(&db, request()).unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(db.get_relationships().unwrap().len(), 1);
+
+        let other = insert_relationship(&db, relationship_request(a, b, serde_json::json!({ "idempotency_key": "retry-2" }))).unwrap();
+        assert_ne!(other.id, first.id);
+    }
+
+    #[test]
+    fn project_notes_round_trip_and_default_to_empty() {
+        in_scratch_dir(|_| {
+            let db = Database::new();
+            add_node(&db, NodeType::Person, "A");
+            let notes = ProjectNotes { notes: "Follow the money".to_string(), case_summary: "Fraud ring".to_string() };
+            db.set_project_notes(notes).unwrap();
+            let project = serialize_project(&db, "Case".to_string(), false).unwrap();
+
+            let loaded = Database::new();
+            apply_project(&loaded, &project, false, false).unwrap();
+            let notes = loaded.project_notes().unwrap();
+            assert_eq!((notes.notes.as_str(), notes.case_summary.as_str()), ("Follow the money", "Fraud ring"));
+
+            let mut old: serde_json::Value = serde_json::from_str(&project).unwrap();
+            let metadata = old["metadata"].as_object_mut().unwrap();
+            metadata.remove("notes");
+            metadata.remove("case_summary");
+            let report = apply_project(&loaded, &old.to_string(), false, false).unwrap();
+            assert!(report.metadata.notes.is_empty());
+            assert!(loaded.project_notes().unwrap().notes.is_empty());
+        });
+    }
+
+    #[test]
+    fn reloading_and_resaving_keeps_original_timestamps() {
+        in_scratch_dir(|_| {
+            let mut project: serde_json::Value = serde_json::from_str(&sample_project()).unwrap();
+            project["metadata"]["created_at"] = serde_json::json!("2020-01-02T03:04:05+00:00");
+            project["nodes"][0]["created_at"] = serde_json::json!("2019-05-06T07:08:09Z");
+            project["relationships"][0]["created_at"] = serde_json::json!("2019-05-06T07:08:09Z");
+
+            let db = Database::new();
+            apply_project(&db, &project.to_string(), false, false).unwrap();
+            let resaved: serde_json::Value =
+                serde_json::from_str(&serialize_project(&db, "Sample".to_string(), false).unwrap()).unwrap();
+
+            let created_at = |value: &serde_json::Value| {
+                chrono::DateTime::parse_from_rfc3339(value.as_str().unwrap()).unwrap().with_timezone(&chrono::Utc)
+            };
+            assert_eq!(created_at(&resaved["metadata"]["created_at"]), created_at(&project["metadata"]["created_at"]));
+            let node_id = &project["nodes"][0]["id"];
+            let node = resaved["nodes"].as_array().unwrap().iter().find(|node| &node["id"] == node_id).unwrap();
+            assert_eq!(created_at(&node["created_at"]), created_at(&project["nodes"][0]["created_at"]));
+            assert_eq!(created_at(&resaved["relationships"][0]["created_at"]), created_at(&project["relationships"][0]["created_at"]));
+        });
+    }
+
+    #[test]
+    fn loading_a_project_repairs_out_of_range_confidence() {
+        in_scratch_dir(|_| {
+            let mut project: serde_json::Value = serde_json::from_str(&sample_project()).unwrap();
+            project["nodes"][0]["confidence"] = serde_json::json!(7.5);
+            let node_id = project["nodes"][0]["id"].as_str().unwrap().to_string();
+
+            let db = Database::new();
+            let report = apply_project(&db, &project.to_string(), false, false).unwrap();
+
+            assert!(report.warnings.iter().any(|warning| warning.contains("Repaired invalid confidence")));
+            assert_eq!(fetch_node(&db, &node_id).confidence, 1.0);
+        });
+    }
+
+    #[test]
+    fn csv_export_by_type_writes_one_file_per_node_type() {
+        let db = Database::new();
+        let alice = add_node(&db, NodeType::Person, "Alice");
+        add_node(&db, NodeType::Person, "Bob");
+        let domain = add_node(&db, NodeType::Domain, "example.com");
+        link(&db, alice, domain, RelationType::Owns);
+        let dir = scratch_dir();
+
+        let written = write_csv_by_type(&db, &dir).unwrap();
+        assert_eq!(written.len(), 3);
+
+        let rows = |file_name: &str| -> Vec<String> {
+            let mut reader = csv::Reader::from_path(dir.join(file_name)).unwrap();
+            reader.records().map(|record| record.unwrap()[1].to_string()).collect()
+        };
+        assert_eq!(rows("persons.csv"), vec!["Alice", "Bob"]);
+        assert_eq!(rows("domains.csv"), vec!["example.com"]);
+        assert_eq!(rows("relationships.csv").len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn indicator_import_classifies_each_line() {
+        let db = Database::new();
+        let hash = "a".repeat(64);
+        let text = format!("10.0.0.1\n\nevil.example.com\nops@example.org\n{}\nnot an indicator\n", hash);
+        let report = insert_indicators(&db, &text, &["campaign-x".to_string()]).unwrap();
+
+        assert_eq!(report.unclassified, vec!["not an indicator"]);
+        let types: Vec<(NodeType, String)> = report
+            .created
+            .iter()
+            .map(|id| fetch_node(&db, id))
+            .inspect(|node| assert_eq!(node.tags, vec!["campaign-x"]))
+            .map(|node| (node.node_type, node.label))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                (NodeType::IpAddress, "10.0.0.1".to_string()),
+                (NodeType::Domain, "evil.example.com".to_string()),
+                (NodeType::Email, "ops@example.org".to_string()),
+                (NodeType::Document, hash),
+            ]
+        );
+    }
+
+    #[test]
+    fn node_views_count_attachments_per_node() {
+        in_scratch_dir(|_| {
+            let db = Database::new();
+            let with_files = add_node(&db, NodeType::Person, "With files");
+            let without_files = add_node(&db, NodeType::Person, "Without files");
+            for name in ["a.txt", "b.txt"] {
+                save_attachment(with_files.to_string(), name.to_string(), "ZGF0YQ==".to_string()).unwrap();
+            }
+
+            let views = with_attachment_counts(db.get_all_nodes().unwrap());
+            let count = |id: Uuid| views.iter().find(|view| view.node.id == id).unwrap().attachment_count;
+            assert_eq!(count(with_files), 2);
+            assert_eq!(count(without_files), 0);
+        });
+    }
+
+    #[test]
+    fn relationships_keep_historical_timestamps() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let created =
+            insert_relationship(&db, relationship_request(a, b, serde_json::json!({ "created_at": "2019-03-01T10:00:00Z" })))
+                .unwrap();
+
+        let rel = db.get_relationship(Uuid::parse_str(&created.id).unwrap()).unwrap().unwrap();
+        assert_eq!(rel.created_at.to_rfc3339(), "2019-03-01T10:00:00+00:00");
+        assert_eq!(rel.updated_at, rel.created_at);
+
+        let timeline = db.get_growth_timeline(TimeBucket::Month).unwrap();
+        assert_eq!(timeline[0].bucket_start, chrono::NaiveDate::from_ymd_opt(2019, 3, 1).unwrap());
+        assert_eq!((timeline[0].nodes, timeline[0].relationships), (0, 1));
+
+        let future = (chrono::Utc::now() + chrono::Duration::days(30)).to_rfc3339();
+        assert!(insert_relationship(&db, relationship_request(a, b, serde_json::json!({ "created_at": future }))).is_err());
+    }
+
+    #[test]
+    fn mini_case_bundles_the_subgraph_and_its_attachments() {
+        in_scratch_dir(|dir| {
+            let db = Database::new();
+            let a = add_node(&db, NodeType::Person, "A");
+            let b = add_node(&db, NodeType::Organization, "B");
+            let outside = add_node(&db, NodeType::Person, "Outside");
+            let inner = link(&db, a, b, RelationType::MemberOf);
+            link(&db, a, outside, RelationType::ConnectedTo);
+            save_attachment(a.to_string(), "note.txt".to_string(), "aGVsbG8=".to_string()).unwrap();
+            save_attachment(outside.to_string(), "other.txt".to_string(), "b3RoZXI=".to_string()).unwrap();
+
+            let bundle = dir.join("case.zip");
+            let bundle = bundle.to_str().unwrap();
+            let manifest = write_mini_case(&db, &[a.to_string(), b.to_string()], bundle).unwrap();
+            assert_eq!((manifest.nodes, manifest.relationships, manifest.attachments.len()), (2, 1, 1));
+
+            let mut archive = zip::ZipArchive::new(std::fs::File::open(bundle).unwrap()).unwrap();
+            let mut blob = String::new();
+            std::io::Read::read_to_string(
+                &mut archive.by_name(&format!("attachments/{}", manifest.attachments[0])).unwrap(),
+                &mut blob,
+            )
+            .unwrap();
+            assert_eq!(blob, "hello");
+            let subgraph: MiniCaseSubgraph =
+                serde_json::from_reader(archive.by_name("subgraph.json").unwrap()).unwrap();
+            assert_eq!(subgraph.relationships.iter().map(|rel| rel.id).collect::<Vec<_>>(), vec![inner]);
+
+            std::fs::remove_dir_all("attachments").unwrap();
+            let target = Database::new();
+            let report = read_mini_case(&target, bundle).unwrap();
+            assert_eq!((report.nodes, report.relationships, report.attachments), (2, 1, 1));
+            assert_eq!(list_attachments(a.to_string()).unwrap().len(), 1);
+        });
+    }
+
+    #[test]
+    fn mini_case_import_skips_dangling_relationships_and_checks_limits() {
+        in_scratch_dir(|dir| {
+            let target = Database::new();
+            let hub = add_node(&target, NodeType::Organization, "Hub");
+            let member = add_node(&target, NodeType::Person, "Member");
+            link(&target, hub, member, RelationType::MemberOf);
+            target.update_settings(|settings| settings.relationship_limit = 1).unwrap();
+
+            let node = Node::new(NodeType::Person, "A".to_string());
+            let subgraph = MiniCaseSubgraph {
+                relationships: vec![
+                    Relationship::new(hub, node.id, RelationType::ConnectedTo),
+                    Relationship::new(node.id, Uuid::new_v4(), RelationType::ConnectedTo),
+                ],
+                nodes: vec![node],
+            };
+            let manifest = MiniCaseManifest {
+                version: "1.0.0".to_string(),
+                exported_at: chrono::Utc::now().to_rfc3339(),
+                nodes: 1,
+                relationships: 2,
+                attachments: Vec::new(),
+            };
+            let bundle = dir.join("case.zip");
+            let mut zip = zip::ZipWriter::new(std::fs::File::create(&bundle).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("manifest.json", options).unwrap();
+            std::io::Write::write_all(&mut zip, &serde_json::to_vec(&manifest).unwrap()).unwrap();
+            zip.start_file("subgraph.json", options).unwrap();
+            std::io::Write::write_all(&mut zip, &serde_json::to_vec(&subgraph).unwrap()).unwrap();
+            zip.finish().unwrap();
+            let bundle = bundle.to_str().unwrap();
+
+            target.update_settings(|settings| settings.reject_over_limit = true).unwrap();
+            assert!(read_mini_case(&target, bundle).err().unwrap().contains("already has 1 relationships"));
+            assert_eq!(target.get_all_nodes().unwrap().len(), 2);
+
+            target.update_settings(|settings| settings.reject_over_limit = false).unwrap();
+            let report = read_mini_case(&target, bundle).unwrap();
+            assert_eq!((report.nodes, report.relationships, report.skipped), (1, 1, 1));
+            assert_eq!(report.warnings, vec!["Node \"Hub\" already has 1 relationships (limit 1)".to_string()]);
+            assert_eq!(target.get_relationships().unwrap().len(), 2);
+        });
+    }
+
+    #[test]
+    fn financial_flows_skip_social_edges() {
+        let db = Database::new();
+        let alice = add_node(&db, NodeType::Person, "Alice");
+        let wallet = add_node(&db, NodeType::CryptoWallet, "bc1qexample");
+        let mut payment = Relationship::new(alice, wallet, RelationType::TransactsWith)
+            .with_confidence(0.9)
+            .with_source("chain analysis".to_string());
+        payment.metadata = serde_json::json!({ "amount": "1.5 BTC" });
+        db.create_relationship(payment).unwrap();
+        link(&db, alice, wallet, RelationType::ConnectedTo);
+
+        let mut csv = Vec::new();
+        assert_eq!(write_financial_flows(&db, &mut csv).unwrap(), 1);
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "From,To,RelationType,Amount,Confidence,Source\nAlice,bc1qexample,TransactsWith,1.5 BTC,0.9,chain analysis\n"
+        );
+    }
+
+    #[test]
+    fn appending_reports_concatenates_sections() {
+        let dir = scratch_dir();
+        let path = dir.join("report.md").to_string_lossy().into_owned();
+        write_report(path.clone(), "stale".to_string(), None).unwrap();
+        write_report(path.clone(), "# Summary\n".to_string(), Some(false)).unwrap();
+        write_report(path.clone(), "## Findings\n".to_string(), Some(true)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# Summary\n## Findings\n");
+
+        let fresh = dir.join("fresh.md").to_string_lossy().into_owned();
+        write_report(fresh.clone(), "one".to_string(), Some(true)).unwrap();
+        write_report(fresh.clone(), "two".to_string(), Some(true)).unwrap();
+        assert_eq!(std::fs::read_to_string(&fresh).unwrap(), "onetwo");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn nodes_sort_by_type_then_confidence_descending() {
+        let db = Database::new();
+        for (node_type, label, confidence) in [
+            (NodeType::Person, "Low person", 0.2),
+            (NodeType::Organization, "Org", 0.5),
+            (NodeType::Person, "High person", 0.9),
+            (NodeType::Domain, "example.com", 0.7),
+        ] {
+            let mut node = Node::new(node_type, label.to_string());
+            node.confidence = confidence;
+            db.create_node(node).unwrap();
+        }
+
+        let sort_keys = parse_sort_keys(&[("node_type".to_string(), false), ("confidence".to_string(), true)]).unwrap();
+        let labels: Vec<String> =
+            db.get_nodes_sorted(&sort_keys, 0, None).unwrap().into_iter().map(|node| node.label).collect();
+        assert_eq!(labels, vec!["example.com", "Org", "High person", "Low person"]);
+
+        let error = parse_sort_keys(&[("colour".to_string(), false)]).unwrap_err();
+        assert!(error.contains("\"colour\"") && error.contains(&NodeSortField::NAMES.join(", ")));
+    }
+
+    #[test]
+    fn provenance_matrix_marks_every_attesting_source() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Organization, "B");
+        let c = add_node(&db, NodeType::Organization, "C");
+        db.create_relationship(Relationship::new(a, b, RelationType::MemberOf).with_source("registry".to_string()))
+            .unwrap();
+        db.create_relationship(Relationship::new(b, a, RelationType::ConnectedTo).with_source("leak".to_string()))
+            .unwrap();
+        db.create_relationship(Relationship::new(a, c, RelationType::ConnectedTo).with_source("leak".to_string()))
+            .unwrap();
+
+        let mut csv = Vec::new();
+        assert_eq!(write_provenance_matrix(&db, &mut csv).unwrap(), 2);
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("NodeA,NodeB,SourceCount,leak,registry"));
+        let rows: Vec<&str> = lines.collect();
+        assert!(rows.iter().any(|row| row.ends_with(",2,x,x") && row.contains('A') && row.contains('B')));
+        assert!(rows.iter().any(|row| row.ends_with(",1,x,") && row.contains('C')));
+    }
+
+    #[test]
+    fn metadata_references_become_relationships() {
+        let db = Database::new();
+        let owner = add_node(&db, NodeType::Person, "Owner");
+        let mut wallet = Node::new(NodeType::CryptoWallet, "bc1qowned".to_string());
+        wallet.metadata = serde_json::json!({ "owner_id": owner.to_string() });
+        let wallet = db.create_node(wallet).unwrap();
+        let mut orphan = Node::new(NodeType::CryptoWallet, "bc1qorphan".to_string());
+        let missing = Uuid::new_v4().to_string();
+        orphan.metadata = serde_json::json!({ "owner_id": missing });
+        db.create_node(orphan).unwrap();
+
+        let mapping: std::collections::HashMap<String, MetadataRelationRule> =
+            serde_json::from_value(serde_json::json!({ "owner_id": { "relation_type": "Owns", "reverse": true } }))
+                .unwrap();
+        let report = insert_inferred_relationships(&db, &mapping).unwrap();
+
+        assert_eq!(report.created.len(), 1);
+        let rel = db.get_relationship(Uuid::parse_str(&report.created[0]).unwrap()).unwrap().unwrap();
+        assert_eq!((rel.source_id, rel.target_id, rel.relation_type), (owner, wallet, RelationType::Owns));
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].value, missing);
+
+        let rerun = insert_inferred_relationships(&db, &mapping).unwrap();
+        assert_eq!((rerun.created.len(), rerun.existing), (0, 1));
+    }
+
+    /// Builds a one-page PDF whose text layer is `text`
+    fn minimal_pdf(text: &str) -> Vec<u8> {
+        let stream = format!("BT /F1 24 Tf 72 720 Td ({}) Tj ET", text);
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>"
+                .to_string(),
+            format!("<< /Length {} >>\nstream\n{}\nendstream", stream.len(), stream),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+        ];
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).into_bytes());
+        }
+        let xref = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+        }
+        pdf.extend(
+            format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).into_bytes(),
+        );
+        pdf
+    }
+
+    #[test]
+    fn text_is_extracted_from_text_and_pdf_attachments() {
+        in_scratch_dir(|_| {
+            let node_id = Uuid::new_v4().to_string();
+            let encode = |bytes: &[u8]| BASE64_STANDARD.encode(bytes);
+
+            let note = save_attachment(node_id.clone(), "note.txt".to_string(), encode(b"Wire sent 2024-01-05")).unwrap();
+            let extracted = extract_attachment_text(note).unwrap();
+            assert_eq!(extracted.text, "Wire sent 2024-01-05");
+            assert!(!extracted.truncated);
+
+            let report = save_attachment(node_id.clone(), "report.pdf".to_string(), encode(&minimal_pdf("Hello Evidence")))
+                .unwrap();
+            assert!(extract_attachment_text(report).unwrap().text.contains("Hello Evidence"));
+
+            let image = save_attachment(node_id.clone(), "photo.png".to_string(), encode(b"\x89PNG")).unwrap();
+            assert!(extract_attachment_text(image).err().unwrap().contains(".png"));
+
+            let long = save_attachment(node_id, "long.md".to_string(), encode(&vec![b'a'; MAX_EXTRACTED_TEXT_BYTES + 10]))
+                .unwrap();
+            let extracted = extract_attachment_text(long).unwrap();
+            assert!(extracted.truncated);
+            assert_eq!(extracted.text.len(), MAX_EXTRACTED_TEXT_BYTES);
+        });
+    }
+
+    #[test]
+    fn indicator_import_reports_type_mapping_and_fallbacks() {
+        let db = Database::new();
+        let text = "10.0.0.1\n10.0.0.2\nevil.example.com\nnot an indicator\n";
+        let report = insert_indicators(&db, text, &[]).unwrap();
+
+        let mappings: Vec<(&str, Option<&str>, usize)> = report
+            .type_mapping
+            .mappings
+            .iter()
+            .map(|mapping| (mapping.source_type.as_str(), mapping.node_type.as_deref(), mapping.count))
+            .collect();
+        assert_eq!(
+            mappings,
+            vec![
+                ("IP address", Some("IpAddress"), 2),
+                ("domain", Some("Domain"), 1),
+                ("unrecognized", None, 1),
+            ]
+        );
+        assert_eq!(report.type_mapping.fallbacks, 1);
+    }
+
+    #[test]
+    fn relationship_update_rejects_invalid_weights_and_clamps_large_ones() {
+        let db = Database::new();
+        let a = add_node(&db, NodeType::Person, "A");
+        let b = add_node(&db, NodeType::Person, "B");
+        let id = link(&db, a, b, RelationType::ConnectedTo);
+
+        for weight in [f64::NAN, f64::INFINITY, -1.0] {
+            let mut request = relationship_update(id, serde_json::json!({}));
+            request.weight = weight;
+            assert!(apply_relationship_update(&db, request).err().unwrap().contains("non-negative"));
+        }
+        assert_eq!(db.get_relationship(id).unwrap().unwrap().weight, 1.0);
+
+        apply_relationship_update(&db, relationship_update(id, serde_json::json!({ "weight": 1e9 }))).unwrap();
+        assert_eq!(db.get_relationship(id).unwrap().unwrap().weight, MAX_RELATIONSHIP_WEIGHT);
+    }
+
+    #[test]
+    fn self_loops_need_the_explicit_flag() {
+        let db = Database::new();
+        let wallet = add_node(&db, NodeType::CryptoWallet, "bc1qself");
+
+        let error = insert_relationship(&db, relationship_request(wallet, wallet, serde_json::json!({}))).err().unwrap();
+        assert!(error.contains("allow_self_loop"));
+        assert!(db.get_relationships().unwrap().is_empty());
+
+        let created = insert_relationship(
+            &db,
+            relationship_request(wallet, wallet, serde_json::json!({ "relation_type": "TransactsWith", "allow_self_loop": true })),
+        )
+        .unwrap();
+        let rel = db.get_relationship(Uuid::parse_str(&created.id).unwrap()).unwrap().unwrap();
+        assert_eq!((rel.source_id, rel.target_id), (wallet, wallet));
+    }
+
+    #[test]
+    fn saved_projects_keep_attachments_with_their_nodes() {
+        in_scratch_dir(|_| {
+            let db = Database::new();
+            let with_file = add_node(&db, NodeType::Document, "Contract");
+            let without_file = add_node(&db, NodeType::Person, "Signer");
+            let attachment_id =
+                save_attachment(with_file.to_string(), "contract.txt".to_string(), "c2lnbmVk".to_string()).unwrap();
+
+            let inventory_only: ProjectData =
+                serde_json::from_str(&serialize_project(&db, "Case".to_string(), false).unwrap()).unwrap();
+            assert_eq!(inventory_only.attachments.len(), 1);
+            assert_eq!(inventory_only.attachments[0].meta.node_id, with_file.to_string());
+            assert!(inventory_only.attachments[0].content_base64.is_none());
+
+            let saved = serialize_project(&db, "Case".to_string(), true).unwrap();
+            std::fs::remove_dir_all("attachments").unwrap();
+            let loaded = Database::new();
+            let report = apply_project(&loaded, &saved, false, false).unwrap();
+
+            assert_eq!(report.attachments, 1);
+            let restored = list_attachments_meta(with_file.to_string()).unwrap();
+            assert_eq!(restored.len(), 1);
+            assert_eq!((restored[0].id.as_str(), restored[0].file_type.as_str()), (attachment_id.as_str(), "txt"));
+            assert_eq!(std::fs::read_to_string(find_attachment_path(&attachment_id).unwrap()).unwrap(), "signed");
+            assert!(list_attachments_meta(without_file.to_string()).unwrap().is_empty());
+
+            let stored = find_attachment_path(&attachment_id).unwrap();
+            std::fs::write(&stored, "tampered").unwrap();
+            let report = apply_project(&Database::new(), &saved, false, false).unwrap();
+            assert!(report.warnings.iter().any(|warning| warning.contains("does not match its SHA-256 hash")));
+            assert_eq!(std::fs::read_to_string(&stored).unwrap(), "tampered");
+
+            let renamed = stored.with_extension("bin");
+            std::fs::rename(&stored, &renamed).unwrap();
+            let report = apply_project(&Database::new(), &saved, false, false).unwrap();
+            assert!(report.warnings.iter().any(|warning| warning.contains("under a different name")));
+            assert!(!stored.exists());
+        });
+    }
+
+    #[test]
+    fn mapped_records_become_typed_nodes_with_metadata() {
+        let db = Database::new();
+        let records = vec![
+            serde_json::json!({ "name": "Acme Ltd", "kind": "company", "score": 0.8, "country": "UK" }),
+            serde_json::json!({ "name": "Jane Roe", "kind": "Person", "score": "0.6", "tags": "ceo,uk" }),
+            serde_json::json!({ "name": "", "kind": "Person" }),
+            serde_json::json!({ "name": "Bad score", "kind": "Person", "score": "high" }),
+            serde_json::json!("not an object"),
+        ];
+        let mapping: FieldMapping = serde_json::from_value(serde_json::json!({
+            "label": "name",
+            "node_type": "kind",
+            "type_values": { "company": "Organization" },
+            "confidence": "score",
+            "tags": "tags",
+        }))
+        .unwrap();
+
+        let report = insert_mapped_records(&db, &records, &mapping).unwrap();
+        assert_eq!(report.skipped.iter().map(|skipped| skipped.index).collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        let acme = fetch_node(&db, &report.created[0]);
+        assert_eq!((acme.node_type, acme.confidence), (NodeType::Organization, 0.8));
+        assert_eq!(acme.metadata, serde_json::json!({ "country": "UK" }));
+        let jane = fetch_node(&db, &report.created[1]);
+        assert_eq!((jane.node_type, jane.confidence), (NodeType::Person, 0.6));
+        assert_eq!(jane.tags, vec!["ceo", "uk"]);
+        assert_eq!(jane.metadata, serde_json::json!({}));
+
+        let out_of_range = node_request("Person", "Overconfident", serde_json::json!({ "confidence": 1.5 }));
+        assert!(insert_node(&db, out_of_range).err().unwrap().contains("Confidence"));
+        let scalar_metadata = node_request("Person", "Odd", serde_json::json!({ "metadata": 42 }));
+        assert!(insert_node(&db, scalar_metadata).err().unwrap().contains("JSON object"));
+    }
+
+    #[test]
+    fn mapped_import_reports_type_mapping_and_fallbacks() {
+        let db = Database::new();
+        let records = vec![
+            serde_json::json!({ "value": "Alice", "entity": "maltego.Person" }),
+            serde_json::json!({ "value": "Bob", "entity": "maltego.Person" }),
+            serde_json::json!({ "value": "example.com", "entity": "Domain" }),
+            serde_json::json!({ "value": "???", "entity": "maltego.Unknown" }),
+        ];
+        let mapping: FieldMapping = serde_json::from_value(serde_json::json!({
+            "label": "value",
+            "node_type": "entity",
+            "type_values": { "maltego.Person": "Person" },
+        }))
+        .unwrap();
+
+        let report = insert_mapped_records(&db, &records, &mapping).unwrap();
+        assert_eq!(report.created.len(), 4);
+        let mappings: Vec<(&str, Option<&str>, usize)> = report
+            .type_mapping
+            .mappings
+            .iter()
+            .map(|mapping| (mapping.source_type.as_str(), mapping.node_type.as_deref(), mapping.count))
+            .collect();
+        assert_eq!(
+            mappings,
+            vec![
+                ("Domain", Some("Domain"), 1),
+                ("maltego.Person", Some("Person"), 2),
+                ("maltego.Unknown", Some("Custom"), 1),
+            ]
+        );
+        assert_eq!(report.type_mapping.fallbacks, 1);
+    }
+
+    #[test]
+    fn archive_round_trip_preserves_graph_and_attachment_bytes() {
+        in_scratch_dir(|dir| {
+            let db = Database::new();
+            let alice = add_node(&db, NodeType::Person, "Alice");
+            let acme = add_node(&db, NodeType::Organization, "Acme");
+            let rel = link(&db, alice, acme, RelationType::MemberOf);
+            let bytes: Vec<u8> = (0..=255u8).collect();
+            save_attachment(alice.to_string(), "scan.bin".to_string(), BASE64_STANDARD.encode(&bytes)).unwrap();
+
+            let archive = dir.join("project.zip");
+            let archive = archive.to_str().unwrap();
+            let manifest = write_archive(&db, archive, "Case".to_string()).unwrap();
+            assert_eq!((manifest.nodes, manifest.relationships, manifest.attachments.len()), (2, 1, 1));
+
+            std::fs::remove_dir_all("attachments").unwrap();
+            std::fs::create_dir_all("attachments").unwrap();
+            let staging = std::path::Path::new("attachments").join(".import-test");
+            std::fs::create_dir(&staging).unwrap();
+            let target = Database::new();
+            import_archive_staged(&target, archive, std::path::Path::new("attachments"), &staging, false).unwrap();
+
+            assert_eq!(target.get_all_nodes().unwrap().len(), 2);
+            assert_eq!(target.get_relationships().unwrap().iter().map(|r| r.id).collect::<Vec<_>>(), vec![rel]);
+            let attachment = &list_attachments_meta(alice.to_string()).unwrap()[0];
+            let restored = std::fs::read(find_attachment_path(&attachment.id).unwrap()).unwrap();
+            assert_eq!(restored, bytes);
+        });
+    }
+
+    #[test]
+    fn node_update_sets_and_clears_expiry() {
+        let db = Database::new();
+        let id = add_node(&db, NodeType::IpAddress, "203.0.113.7").to_string();
+        let expires_at = "2030-01-01T00:00:00Z";
+
+        apply_node_update(&db, node_update(&fetch_node(&db, &id), serde_json::json!({ "expires_at": expires_at }))).unwrap();
+        assert_eq!(fetch_node(&db, &id).expires_at, Some(expires_at.parse().unwrap()));
+
+        apply_node_update(&db, node_update(&fetch_node(&db, &id), serde_json::json!({}))).unwrap();
+        assert!(fetch_node(&db, &id).expires_at.is_some());
+
+        apply_node_update(&db, node_update(&fetch_node(&db, &id), serde_json::json!({ "clear_expiry": true }))).unwrap();
+        assert_eq!(fetch_node(&db, &id).expires_at, None);
+    }
+
+    #[test]
+    fn loading_drops_dangling_relationship_or_creates_stub() {
+        in_scratch_dir(|_| {
+            let mut project: serde_json::Value = serde_json::from_str(&sample_project()).unwrap();
+            let missing = Uuid::new_v4();
+            let mut dangling = project["relationships"][0].clone();
+            dangling["id"] = serde_json::json!(Uuid::new_v4().to_string());
+            dangling["target_id"] = serde_json::json!(missing.to_string());
+            project["relationships"].as_array_mut().unwrap().push(dangling);
+            let project = project.to_string();
+
+            let db = Database::new();
+            let report = apply_project(&db, &project, false, false).unwrap();
+            assert_eq!((report.dangling_relationships_dropped, report.stub_nodes_created), (1, 0));
+            assert!(report.warnings.is_empty());
+            assert_eq!((report.nodes, report.relationships), (2, 1));
+            assert_eq!(db.get_relationships().unwrap().len(), 1);
+
+            let db = Database::new();
+            let report = apply_project(&db, &project, false, true).unwrap();
+            assert_eq!((report.dangling_relationships_dropped, report.stub_nodes_created), (0, 1));
+            assert!(report.warnings.is_empty());
+            assert_eq!(db.get_relationships().unwrap().len(), 2);
+            let stub = db.get_node(missing).unwrap().unwrap();
+            assert_eq!((stub.node_type, stub.metadata["stub"].as_bool()), (NodeType::Custom, Some(true)));
+        });
+    }
+
+    #[test]
+    fn new_crypto_wallet_gets_its_default_tag() {
+        let db = Database::new();
+        let rules = std::collections::HashMap::from([("CryptoWallet".to_string(), vec!["financial".to_string()])]);
+        replace_auto_tag_rules(&db, rules).unwrap();
+
+        let wallet = insert_node(&db, node_request("CryptoWallet", "bc1qexample", serde_json::json!({}))).unwrap();
+        assert_eq!(fetch_node(&db, &wallet).tags, vec!["financial"]);
+
+        let tagged = insert_node(&db, node_request("CryptoWallet", "bc1qother", serde_json::json!({ "tags": ["financial", "mixer"] }))).unwrap();
+        assert_eq!(fetch_node(&db, &tagged).tags, vec!["financial", "mixer"]);
+
+        let person = insert_node(&db, node_request("Person", "Alice", serde_json::json!({}))).unwrap();
+        assert!(fetch_node(&db, &person).tags.is_empty());
+    }
+}
@@ -14,6 +14,11 @@
 //!
 //! On Linux systems, the application defaults to X11 for maximum compatibility.
 //! Users can override this behavior by setting the `GDK_BACKEND` environment variable.
+//!
+//! ## Headless Mode
+//!
+//! Passing `--headless` skips the GUI entirely and serves newline-delimited
+//! JSON commands over stdin/stdout instead, for scripting and pipelines.
 
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
@@ -22,7 +27,16 @@
 ///
 /// Performs platform-specific initialization and launches the OSINT Studio application.
 /// On Linux, it configures the display backend for optimal compatibility.
+/// With `--headless`, runs the stdin/stdout command interface instead.
 fn main() {
+    if std::env::args().any(|arg| arg == "--headless") {
+        if let Err(e) = osint_studio_lib::run_headless() {
+            eprintln!("Headless mode failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Set up display backend compatibility on Linux
     #[cfg(target_os = "linux")]
     {
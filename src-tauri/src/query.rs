@@ -0,0 +1,427 @@
+//! # Node Query Language
+//!
+//! A small boolean language for selecting nodes, for users who outgrow
+//! plain text search.
+//!
+//! ## Syntax
+//!
+//! ```text
+//! type:CryptoWallet AND tag:exchange AND confidence:>0.8
+//! (label:"acme corp" OR label:acme) AND NOT type:Person
+//! ```
+//!
+//! - `type:<NodeType>` - exact node type name, e.g. `type:IpAddress`
+//! - `tag:<tag>` - has the tag (case-insensitive)
+//! - `label:<text>` - label contains the text (case-insensitive)
+//! - `confidence:<op><number>` - compares confidence with `>`, `>=`, `<`,
+//!   `<=`, or `=` (a bare number means `=`)
+//!
+//! Values containing spaces or parentheses can be double-quoted, with `\"`
+//! for a literal quote. Terms combine with `AND`, `OR`, `NOT`, and
+//! parentheses; `NOT` binds tightest and `AND` binds tighter than `OR`.
+//! Keywords are case-insensitive.
+//!
+//! Parse errors carry the 1-based character position where parsing failed.
+//! Parentheses and `NOT` can be nested at most [`MAX_NESTING_DEPTH`] deep.
+
+use crate::entities::{Node, NodeType};
+use std::fmt;
+
+/// Deepest nesting of parentheses and `NOT` a query may use
+///
+/// Keeps the recursive-descent parser (and `Query::matches`) from
+/// overflowing the stack on pathological input.
+pub const MAX_NESTING_DEPTH: usize = 64;
+
+/// A query that failed to parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    /// What went wrong
+    pub message: String,
+    /// 1-based character position of the problem
+    pub position: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Comparison operator in a `confidence:` term
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `=` or no operator
+    Equal,
+}
+
+impl Comparison {
+    /// Applies the comparison to `value` and `threshold`
+    fn holds(self, value: f32, threshold: f32) -> bool {
+        match self {
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+            Comparison::Equal => (value - threshold).abs() < f32::EPSILON,
+        }
+    }
+}
+
+/// A parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// Every operand matches
+    ///
+    /// Chains of `AND` parse into one flat list, so long queries don't nest.
+    And(Vec<Query>),
+    /// At least one operand matches
+    Or(Vec<Query>),
+    /// The inner query does not match
+    Not(Box<Query>),
+    /// Node has this type
+    Type(NodeType),
+    /// Node has this tag (stored lowercased)
+    Tag(String),
+    /// Node label contains this text (stored lowercased)
+    Label(String),
+    /// Node confidence compares to this value
+    Confidence(Comparison, f32),
+}
+
+impl Query {
+    /// Parses a query expression
+    ///
+    /// # Arguments
+    /// * `expr` - Query text
+    ///
+    /// # Returns
+    /// * `Ok(Query)` - The parsed query
+    /// * `Err(QueryError)` - What is wrong and where
+    pub fn parse(expr: &str) -> Result<Query, QueryError> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, index: 0, end: expr.chars().count() + 1, depth: 0 };
+        let query = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(query),
+            Some(token) => Err(QueryError {
+                message: "Expected AND, OR, or end of query".to_string(),
+                position: token.position,
+            }),
+        }
+    }
+
+    /// Checks whether a node satisfies the query
+    pub fn matches(&self, node: &Node) -> bool {
+        match self {
+            Query::And(operands) => operands.iter().all(|operand| operand.matches(node)),
+            Query::Or(operands) => operands.iter().any(|operand| operand.matches(node)),
+            Query::Not(inner) => !inner.matches(node),
+            Query::Type(node_type) => &node.node_type == node_type,
+            Query::Tag(tag) => node.tags.iter().any(|candidate| candidate.to_lowercase() == *tag),
+            Query::Label(text) => node.label.to_lowercase().contains(text.as_str()),
+            Query::Confidence(comparison, threshold) => comparison.holds(node.confidence, *threshold),
+        }
+    }
+}
+
+/// Lexical element of a query
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// `(`
+    Open,
+    /// `)`
+    Close,
+    /// `AND`
+    And,
+    /// `OR`
+    Or,
+    /// `NOT`
+    Not,
+    /// `field:value`
+    Term { field: String, value: String, value_position: usize },
+}
+
+/// A token and the 1-based position where it starts
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+/// Splits a query into tokens
+fn tokenize(expr: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '(' || c == ')' {
+            let kind = if c == '(' { TokenKind::Open } else { TokenKind::Close };
+            tokens.push(Token { kind, position });
+            i += 1;
+            continue;
+        }
+
+        let mut word = String::new();
+        while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '(' && chars[i] != ')' && chars[i] != ':' {
+            word.push(chars[i]);
+            i += 1;
+        }
+
+        if i >= chars.len() || chars[i] != ':' {
+            let kind = match word.to_uppercase().as_str() {
+                "AND" => TokenKind::And,
+                "OR" => TokenKind::Or,
+                "NOT" => TokenKind::Not,
+                _ => {
+                    return Err(QueryError {
+                        message: format!("Expected a term like type:Person, found '{}'", word),
+                        position,
+                    });
+                }
+            };
+            tokens.push(Token { kind, position });
+            continue;
+        }
+
+        // Skip the colon and read the value, quoted or bare
+        i += 1;
+        let value_position = i + 1;
+        let mut value = String::new();
+        if i < chars.len() && chars[i] == '"' {
+            i += 1;
+            let mut closed = false;
+            while i < chars.len() {
+                match chars[i] {
+                    '\\' if i + 1 < chars.len() => {
+                        value.push(chars[i + 1]);
+                        i += 2;
+                    }
+                    '"' => {
+                        closed = true;
+                        i += 1;
+                        break;
+                    }
+                    other => {
+                        value.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            if !closed {
+                return Err(QueryError { message: "Unterminated quoted value".to_string(), position: value_position });
+            }
+        } else {
+            while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '(' && chars[i] != ')' {
+                value.push(chars[i]);
+                i += 1;
+            }
+        }
+
+        tokens.push(Token { kind: TokenKind::Term { field: word, value, value_position }, position });
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over a token list
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    /// Position reported for errors at the end of input
+    end: usize,
+    /// Current nesting of parentheses and `NOT`
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut operands = vec![self.parse_and()?];
+        while self.peek().is_some_and(|token| token.kind == TokenKind::Or) {
+            self.index += 1;
+            operands.push(self.parse_and()?);
+        }
+        Ok(if operands.len() == 1 { operands.remove(0) } else { Query::Or(operands) })
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut operands = vec![self.parse_not()?];
+        while self.peek().is_some_and(|token| token.kind == TokenKind::And) {
+            self.index += 1;
+            operands.push(self.parse_not()?);
+        }
+        Ok(if operands.len() == 1 { operands.remove(0) } else { Query::And(operands) })
+    }
+
+    /// Enters one level of nesting, failing at the token's position when too deep
+    fn descend(&mut self, position: usize) -> Result<(), QueryError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(QueryError {
+                message: format!("Query is nested more than {} levels deep", MAX_NESTING_DEPTH),
+                position,
+            });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn parse_not(&mut self) -> Result<Query, QueryError> {
+        if let Some(token) = self.peek().filter(|token| token.kind == TokenKind::Not) {
+            let position = token.position;
+            self.index += 1;
+            self.descend(position)?;
+            let inner = self.parse_not()?;
+            self.depth -= 1;
+            return Ok(Query::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, QueryError> {
+        let Some(token) = self.next() else {
+            return Err(QueryError { message: "Unexpected end of query".to_string(), position: self.end });
+        };
+        match token.kind {
+            TokenKind::Open => {
+                self.descend(token.position)?;
+                let query = self.parse_or()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token { kind: TokenKind::Close, .. }) => Ok(query),
+                    Some(other) => Err(QueryError { message: "Expected ')'".to_string(), position: other.position }),
+                    None => Err(QueryError { message: "Missing ')'".to_string(), position: self.end }),
+                }
+            }
+            TokenKind::Term { field, value, value_position } => parse_term(&field, &value, token.position, value_position),
+            TokenKind::Close => Err(QueryError { message: "Unexpected ')'".to_string(), position: token.position }),
+            TokenKind::And | TokenKind::Or | TokenKind::Not => Err(QueryError {
+                message: "Expected a term or '(' before operator".to_string(),
+                position: token.position,
+            }),
+        }
+    }
+}
+
+/// Builds the query for a single `field:value` term
+fn parse_term(field: &str, value: &str, position: usize, value_position: usize) -> Result<Query, QueryError> {
+    if value.is_empty() {
+        return Err(QueryError { message: format!("Missing value for '{}:'", field), position: value_position });
+    }
+    match field.to_lowercase().as_str() {
+        "type" => crate::parse_node_type(value)
+            .map(Query::Type)
+            .map_err(|_| QueryError { message: format!("Unknown node type '{}'", value), position: value_position }),
+        "tag" => Ok(Query::Tag(value.to_lowercase())),
+        "label" => Ok(Query::Label(value.to_lowercase())),
+        "confidence" => {
+            let (comparison, number) = [
+                (">=", Comparison::GreaterOrEqual),
+                ("<=", Comparison::LessOrEqual),
+                (">", Comparison::Greater),
+                ("<", Comparison::Less),
+                ("=", Comparison::Equal),
+            ]
+            .iter()
+            .find_map(|(prefix, comparison)| value.strip_prefix(prefix).map(|rest| (*comparison, rest)))
+            .unwrap_or((Comparison::Equal, value));
+            number
+                .parse::<f32>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .map(|number| Query::Confidence(comparison, number))
+                .ok_or_else(|| QueryError {
+                    message: format!("Invalid confidence '{}'; expected e.g. >0.8", value),
+                    position: value_position,
+                })
+        }
+        _ => Err(QueryError {
+            message: format!("Unknown field '{}'; expected type, tag, label, or confidence", field),
+            position,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(node_type: NodeType, label: &str, tags: &[&str], confidence: f32) -> Node {
+        let mut node = Node::new(node_type, label.to_string());
+        node.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        node.confidence = confidence;
+        node
+    }
+
+    #[test]
+    fn compound_query_selects_exactly_the_intended_nodes() {
+        let nodes = [
+            node(NodeType::CryptoWallet, "Hot wallet", &["exchange"], 0.9),
+            node(NodeType::CryptoWallet, "Cold wallet", &["exchange"], 0.5),
+            node(NodeType::CryptoWallet, "Mixer", &["mixer"], 0.95),
+            node(NodeType::Organization, "Acme Exchange", &["Exchange"], 0.9),
+            node(NodeType::Person, "Acme Corp CEO", &[], 0.9),
+        ];
+        let labels = |expr: &str| -> Vec<&str> {
+            let query = Query::parse(expr).unwrap();
+            nodes.iter().filter(|node| query.matches(node)).map(|node| node.label.as_str()).collect()
+        };
+
+        assert_eq!(labels("type:CryptoWallet AND tag:exchange AND confidence:>0.8"), vec!["Hot wallet"]);
+        assert_eq!(labels("tag:EXCHANGE and not type:CryptoWallet"), vec!["Acme Exchange"]);
+        assert_eq!(
+            labels("(label:\"acme corp\" OR label:mixer) AND confidence:>=0.9"),
+            vec!["Mixer", "Acme Corp CEO"]
+        );
+        assert_eq!(labels("confidence:<0.6 OR type:Person AND tag:none"), vec!["Cold wallet"]);
+    }
+
+    #[test]
+    fn malformed_queries_report_the_position() {
+        let error = |expr: &str| Query::parse(expr).unwrap_err();
+
+        assert_eq!(error("type:CryptoWallet AND").position, 22);
+        assert_eq!(error("confidence:>abc").position, 12);
+        let unknown = error("type:Wallet");
+        assert_eq!(unknown.to_string(), "Unknown node type 'Wallet' at position 6");
+        assert_eq!(error("(tag:x").message, "Missing ')'");
+        assert_eq!(error("tag:x )").position, 7);
+        assert_eq!(error(&"(".repeat(MAX_NESTING_DEPTH + 1)).position, MAX_NESTING_DEPTH + 1);
+    }
+
+    #[test]
+    fn long_and_chain_parses_flat() {
+        let expr = vec!["tag:exchange"; 100_000].join(" AND ");
+        let query = Query::parse(&expr).unwrap();
+
+        assert!(matches!(&query, Query::And(operands) if operands.len() == 100_000));
+        assert!(query.matches(&node(NodeType::CryptoWallet, "Hot wallet", &["exchange"], 0.9)));
+        assert!(!query.matches(&node(NodeType::CryptoWallet, "Mixer", &["mixer"], 0.9)));
+    }
+}
@@ -20,6 +20,8 @@ interface Node {
   created_at: string;
   updated_at: string;
   confidence: number;
+  expires_at?: string | null;
+  pinned?: boolean;
 }
 
 interface Relationship {
@@ -42,6 +44,8 @@ function App() {
   const [loading, setLoading] = useState(false);
   const [showMarkdownEditor, setShowMarkdownEditor] = useState(false);
   const [markdownContent, setMarkdownContent] = useState("");
+  const [showProjectNotes, setShowProjectNotes] = useState(false);
+  const [projectNotes, setProjectNotes] = useState("");
   const [showReportGenerator, setShowReportGenerator] = useState(false);
 
   useEffect(() => {
@@ -98,8 +102,11 @@ function App() {
     try {
       setLoading(true);
       console.log("Creating relationship:", relationshipData);
-      await invoke("create_relationship", { request: relationshipData });
+      const result = await invoke<{ id: string; warnings: string[] }>("create_relationship", { request: relationshipData });
       await loadAllData();
+      if (result.warnings.length > 0) {
+        alert(result.warnings.join("\n"));
+      }
       console.log("Relationship created and data reloaded");
     } catch (error) {
       console.error("Failed to create relationship:", error);
@@ -154,6 +161,27 @@ function App() {
     console.log("Saved markdown:", content);
   };
 
+  const handleOpenProjectNotes = async () => {
+    try {
+      const notes = await invoke<{ notes: string; case_summary: string }>("get_project_notes");
+      setProjectNotes(notes.notes);
+    } catch (error) {
+      console.error("Failed to load project notes:", error);
+    }
+    setShowProjectNotes(true);
+  };
+
+  const handleProjectNotesSave = async (content: string) => {
+    setProjectNotes(content);
+    setShowProjectNotes(false);
+    try {
+      await invoke("set_project_notes", { notes: content });
+    } catch (error) {
+      console.error("Failed to save project notes:", error);
+      alert("Failed to save project notes: " + error);
+    }
+  };
+
   return (
     <ErrorBoundary>
       <div className="app">
@@ -171,6 +199,7 @@ function App() {
         onRefresh={loadAllData}
         onNewProject={handleNewProject}
         onGenerateReport={() => setShowReportGenerator(true)}
+        onOpenProjectNotes={handleOpenProjectNotes}
       />
       
       <div className="app-body">
@@ -214,6 +243,15 @@ function App() {
         />
       )}
 
+      {showProjectNotes && (
+        <MarkdownEditor
+          initialValue={projectNotes}
+          onSave={handleProjectNotesSave}
+          onCancel={() => setShowProjectNotes(false)}
+          title="Case Notes"
+        />
+      )}
+
       {showReportGenerator && (
         <ReportGenerator
           nodes={nodes}
@@ -12,6 +12,8 @@ interface Node {
   created_at: string;
   updated_at: string;
   confidence: number;
+  expires_at?: string | null;
+  pinned?: boolean;
 }
 
 interface Relationship {
@@ -31,6 +33,12 @@ interface NodePanelProps {
   onOpenMarkdown?: () => void;
 }
 
+// Formats an ISO timestamp for a datetime-local input, in local time
+const toLocalInput = (dateString: string) => {
+  const date = new Date(dateString);
+  return new Date(date.getTime() - date.getTimezoneOffset() * 60000).toISOString().slice(0, 16);
+};
+
 const NodePanel = ({ nodeId, onClose, onRefresh, onOpenMarkdown }: NodePanelProps) => {
   const [node, setNode] = useState<Node | null>(null);
   const [relationships, setRelationships] = useState<Relationship[]>([]);
@@ -44,6 +52,7 @@ const NodePanel = ({ nodeId, onClose, onRefresh, onOpenMarkdown }: NodePanelProp
     description: "",
     tags: "",
     confidence: 1.0,
+    expires_at: "",
   });
   const [relationshipEditForm, setRelationshipEditForm] = useState({
     relation_type: "",
@@ -70,6 +79,7 @@ const NodePanel = ({ nodeId, onClose, onRefresh, onOpenMarkdown }: NodePanelProp
           description: nodeData.description || "",
           tags: nodeData.tags.join(", "),
           confidence: nodeData.confidence,
+          expires_at: nodeData.expires_at ? toLocalInput(nodeData.expires_at) : "",
         });
       }
       setRelationships(relationshipsData);
@@ -91,6 +101,8 @@ const NodePanel = ({ nodeId, onClose, onRefresh, onOpenMarkdown }: NodePanelProp
           description: editForm.description || null,
           tags: editForm.tags.split(',').map(tag => tag.trim()).filter(tag => tag.length > 0),
           confidence: editForm.confidence,
+          expires_at: editForm.expires_at ? new Date(editForm.expires_at).toISOString() : null,
+          clear_expiry: !editForm.expires_at,
         }
       });
       setIsEditing(false);
@@ -106,7 +118,15 @@ const NodePanel = ({ nodeId, onClose, onRefresh, onOpenMarkdown }: NodePanelProp
   const handleDelete = async () => {
     if (!node) return;
     
-    const confirmDelete = confirm(`Are you sure you want to delete "${node.label}"? This will also remove all relationships connected to this entity. This action cannot be undone.`);
+    let relationshipCount = relationships.length;
+    try {
+      const preview = await invoke<{ relationships: Relationship[] }>("preview_node_deletion", { id: node.id });
+      relationshipCount = preview.relationships.length;
+    } catch (error) {
+      console.error("Failed to preview deletion:", error);
+    }
+
+    const confirmDelete = confirm(`Are you sure you want to delete "${node.label}"? This will also remove ${relationshipCount} relationship(s) connected to this entity. This action cannot be undone.`);
     if (!confirmDelete) return;
     
     try {
@@ -340,6 +360,21 @@ const NodePanel = ({ nodeId, onClose, onRefresh, onOpenMarkdown }: NodePanelProp
             <label>Last Updated</label>
             <p>{formatDate(node.updated_at)}</p>
           </div>
+          <div className="timeline-item">
+            <label>Expires</label>
+            {isEditing ? (
+              <input
+                type="datetime-local"
+                value={editForm.expires_at}
+                onChange={(e) => setEditForm({ ...editForm, expires_at: e.target.value })}
+              />
+            ) : (
+              <p>
+                {node.expires_at ? formatDate(node.expires_at) : "Never"}
+                {node.expires_at && node.pinned ? " (pinned, kept when purging)" : ""}
+              </p>
+            )}
+          </div>
         </div>
 
         <div className="node-section">
@@ -1,4 +1,5 @@
 import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { 
   User, 
   Building, 
@@ -45,9 +46,18 @@ const Sidebar = ({ onCreateNode }: SidebarProps) => {
     tags: "",
   });
 
-  const handleCreateClick = (type: string) => {
+  const handleCreateClick = async (type: string) => {
     setSelectedType(type);
     setShowCreateForm(true);
+
+    try {
+      const template = await invoke<string>("get_description_template", { nodeType: type });
+      setFormData(current =>
+        current.description ? current : { ...current, description: template }
+      );
+    } catch (error) {
+      console.error("Failed to load description template:", error);
+    }
   };
 
   const handleSubmit = (e: React.FormEvent) => {
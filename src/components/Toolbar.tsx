@@ -9,16 +9,18 @@ import {
   Trash2,
   Plus,
   FileJson,
-  FileBarChart
+  FileBarChart,
+  BookOpen
 } from "lucide-react";
 
 interface ToolbarProps {
   onRefresh: () => void;
   onNewProject: () => void;
   onGenerateReport?: () => void;
+  onOpenProjectNotes?: () => void;
 }
 
-const Toolbar = ({ onRefresh, onNewProject, onGenerateReport }: ToolbarProps) => {
+const Toolbar = ({ onRefresh, onNewProject, onGenerateReport, onOpenProjectNotes }: ToolbarProps) => {
   const [loading, setLoading] = useState(false);
 
   const handleSaveProject = async () => {
@@ -247,6 +249,17 @@ const Toolbar = ({ onRefresh, onNewProject, onGenerateReport }: ToolbarProps) =>
             <FolderOpen size={16} />
             Load
           </button>
+          {onOpenProjectNotes && (
+            <button 
+              onClick={onOpenProjectNotes}
+              disabled={loading}
+              className="toolbar-btn"
+              title="Project Case Notes"
+            >
+              <BookOpen size={16} />
+              Notes
+            </button>
+          )}
         </div>
       </div>
 